@@ -0,0 +1,1356 @@
+// 子命令定义与指令构建：每条程序指令对应一个子命令，另有地址派生、账户查询和只读视图命令。
+// 池子相关的账户（金库、Mint、奖励流、国库等）从链上的池子状态读取，
+// 用户代币账户缺省为签名者的关联代币账户，接收代币的账户不存在时先幂等创建；
+// Mint 带转账钩子时，按钩子的 ExtraAccountMetaList 解析额外账户并追加到指令末尾
+
+use crate::config::{Config, TokenTransfer};
+use crate::output::{account_json, Output, ToJson};
+use crate::Result;
+use clap::{Args, Subcommand, ValueEnum};
+use serde_json::{Map, Value};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey,
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use staking_program_client::{
+    instruction::{self, *},
+    pda::*,
+    state::{
+        ParameterBounds, PenaltyDestination, PendingRewardsView, Pool, PoolStateView, QueuedChange,
+        RewardStream, Role, TimelockAction, UserPositionView, UserStakeInfo,
+    },
+};
+
+// 关联代币账户程序
+const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+// 交易确认前链上时间还会前进，缺省的 eta 在最早可执行时间之后留出余量（秒）
+const ETA_MARGIN: i64 = 60;
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[command(about = "Derive the pool, vault, receipt mint and stake info addresses offline")]
+    Addresses(AddressesArgs),
+    #[command(about = "Show the decoded pool account")]
+    ShowPool,
+    #[command(about = "Show a user's decoded stake info account")]
+    ShowUser {
+        #[arg(long, help = "User wallet [default: --keypair]")]
+        user: Option<Pubkey>,
+    },
+    #[command(about = "Show a queued timelock change")]
+    ShowChange { change_id: u64 },
+    #[command(about = "Read a user's pending rewards through the get_pending_rewards view")]
+    PendingRewards {
+        #[arg(long, help = "User wallet [default: --keypair]")]
+        user: Option<Pubkey>,
+    },
+    #[command(about = "Read a user's position through the get_user_position view")]
+    Position {
+        #[arg(long, help = "User wallet [default: --keypair]")]
+        user: Option<Pubkey>,
+    },
+    #[command(about = "Read the settled pool state through the get_pool_state view")]
+    PoolState,
+
+    #[command(about = "Initialize a pool for a staking mint with its first reward stream")]
+    Initialize {
+        #[arg(long)]
+        staking_mint: Pubkey,
+        #[arg(long)]
+        reward_mint: Pubkey,
+        #[arg(long, default_value_t = 0)]
+        pool_id: u64,
+        #[arg(long, default_value_t = 0, help = "Lockup duration in seconds")]
+        lockup_duration: i64,
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Dangerous mint extensions to accept (MINT_EXT_* bitmask)"
+        )]
+        allowed_mint_extensions: u16,
+    },
+    #[command(about = "Stake tokens, optionally locking them for a boost")]
+    Stake {
+        amount: u64,
+        #[arg(long, default_value_t = 0, help = "Boost lock duration in seconds")]
+        lock_duration: i64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Unstake unlocked tokens")]
+    Unstake {
+        amount: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Unstake tokens before they unlock, paying the early unstake penalty")]
+    UnstakeEarly {
+        amount: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Start the unstake cooldown for an amount")]
+    RequestUnstake { amount: u64 },
+    #[command(about = "Cancel a pending unstake request")]
+    CancelUnstakeRequest { request_index: u8 },
+    #[command(about = "Withdraw unstake requests whose cooldown has ended")]
+    WithdrawUnstaked {
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Recompute a user's boosted weight")]
+    Poke {
+        #[arg(long, help = "User wallet [default: --keypair]")]
+        user: Option<Pubkey>,
+    },
+    #[command(about = "Stake tokens for receipt token shares")]
+    StakeLiquid {
+        amount: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+        #[arg(
+            long,
+            help = "Receipt token account [default: associated token account]"
+        )]
+        receipt_account: Option<Pubkey>,
+    },
+    #[command(about = "Burn receipt token shares for staking tokens")]
+    UnstakeLiquid {
+        shares: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+        #[arg(
+            long,
+            help = "Receipt token account [default: associated token account]"
+        )]
+        receipt_account: Option<Pubkey>,
+    },
+    #[command(about = "Compound staking token rewards into a user's stake")]
+    Compound {
+        #[arg(long, help = "User wallet [default: --keypair]")]
+        user: Option<Pubkey>,
+    },
+    #[command(about = "Claim the rewards of a reward stream")]
+    ClaimRewards {
+        reward_index: u8,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Close the stake info account and reclaim its rent")]
+    CloseUserStakeInfo,
+    #[command(about = "Withdraw the pro-rata share of principal after shutdown")]
+    EmergencyExit {
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Withdraw all principal while the pool is paused")]
+    EmergencyUnstake {
+        #[arg(long, help = "Forfeit unclaimed rewards and skip reward settlement")]
+        forfeit_rewards: bool,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+
+    #[command(about = "Update a reward stream's rate for the rest of the current period")]
+    UpdateRewardRate { reward_index: u8, new_rate: u64 },
+    #[command(about = "Fund a reward stream's vault")]
+    FundRewards {
+        reward_index: u8,
+        amount: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Fund a reward stream and start a new reward period")]
+    NotifyRewardAmount {
+        reward_index: u8,
+        amount: u64,
+        #[arg(help = "Reward period in seconds")]
+        duration: i64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Grant a role to an account")]
+    GrantRole {
+        #[arg(value_enum)]
+        role: RoleArg,
+        account: Pubkey,
+    },
+    #[command(about = "Revoke a role back to the admin")]
+    RevokeRole {
+        #[arg(value_enum)]
+        role: RoleArg,
+    },
+    #[command(about = "Nominate a new admin, who must accept with accept-admin")]
+    ProposeAdmin { new_admin: Pubkey },
+    #[command(about = "Accept a pending admin nomination")]
+    AcceptAdmin,
+    #[command(about = "Cancel a pending admin nomination")]
+    CancelAdminTransfer,
+    #[command(about = "Withdraw staked tokens and shut the pool down")]
+    EmergencyWithdrawStakedTokens {
+        amount: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Withdraw tokens from a reward stream's vault")]
+    EmergencyWithdrawRewardTokens {
+        reward_index: u8,
+        amount: u64,
+        #[command(flatten)]
+        wallet: WalletArgs,
+    },
+    #[command(about = "Add a reward stream in the first free slot")]
+    AddRewardStream {
+        reward_mint: Pubkey,
+        #[arg(
+            long,
+            default_value_t = 0,
+            help = "Dangerous mint extensions to accept (MINT_EXT_* bitmask)"
+        )]
+        allowed_mint_extensions: u16,
+    },
+    #[command(about = "Retire a reward stream")]
+    RetireRewardStream { reward_index: u8 },
+    #[command(about = "Create the pool's receipt token mint")]
+    EnableReceiptToken,
+    #[command(about = "Turn auto-compounding on or off")]
+    SetAutoCompound {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+    #[command(about = "Pause user operations")]
+    Pause,
+    #[command(about = "Resume user operations")]
+    Unpause,
+    #[command(about = "Set the maximum pause duration in seconds, 0 for no limit")]
+    UpdateMaxPauseDuration { new_duration: i64 },
+    #[command(about = "Update the lockup duration in seconds")]
+    UpdateLockupDuration { new_duration: i64 },
+    #[command(about = "Lengthen the timelock delay in seconds")]
+    UpdateTimelockDelay { new_delay: i64 },
+    #[command(about = "Queue a parameter change behind the timelock")]
+    ScheduleChange {
+        #[arg(
+            long,
+            help = "Earliest execution time as a unix timestamp [default: now + timelock delay]"
+        )]
+        eta: Option<i64>,
+        #[command(subcommand)]
+        action: ActionArg,
+    },
+    #[command(about = "Execute a queued change after its eta")]
+    ExecuteChange { change_id: u64 },
+    #[command(about = "Cancel a queued change before its eta")]
+    CancelChange { change_id: u64 },
+    #[command(about = "Update the maximum boost in basis points")]
+    UpdateMaxBoost { max_boost_bps: u16 },
+    #[command(about = "Update the early unstake penalty in basis points, 0 to disallow")]
+    UpdateEarlyUnstakePenalty {
+        penalty_bps: u16,
+        #[arg(long, help = "Scale the penalty with the remaining lock")]
+        scale_with_lock: bool,
+    },
+    #[command(about = "Update where early unstake penalties go")]
+    UpdatePenaltyDestination {
+        #[arg(value_enum)]
+        destination: DestinationArg,
+        #[arg(
+            long,
+            required_if_eq("destination", "treasury"),
+            help = "Staking token account receiving penalties"
+        )]
+        treasury: Option<Pubkey>,
+    },
+    #[command(about = "Update the unstake cooldown in seconds, 0 to disable")]
+    UpdateCooldownDuration { new_duration: i64 },
+    #[command(about = "Update the parameter bounds; omitted bounds keep their current value")]
+    UpdateParameterBounds {
+        #[command(flatten)]
+        bounds: BoundsArgs,
+    },
+    #[command(about = "Update the solvency horizon in seconds")]
+    UpdateSolvencyHorizon { new_horizon: i64 },
+}
+
+#[derive(Args)]
+pub struct AddressesArgs {
+    #[arg(long)]
+    staking_mint: Pubkey,
+    #[arg(long, default_value_t = 0)]
+    pool_id: u64,
+    #[arg(long, help = "Also derive this user's stake info address")]
+    user: Option<Pubkey>,
+    #[arg(long, help = "Also derive this reward mint's vault address")]
+    reward_mint: Option<Pubkey>,
+}
+
+// 用户一侧的代币账户，缺省为签名者的关联代币账户
+#[derive(Args)]
+pub struct WalletArgs {
+    #[arg(
+        long,
+        value_name = "ADDRESS",
+        help = "Token account to transfer from or to [default: associated token account]"
+    )]
+    token_account: Option<Pubkey>,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum RoleArg {
+    Pauser,
+    RateManager,
+    Funder,
+    Guardian,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum DestinationArg {
+    Burn,
+    Treasury,
+    Redistribute,
+}
+
+// 参数边界的命令行参数，未指定的边界沿用池子当前的值
+#[derive(Args, Clone, Copy)]
+pub struct BoundsArgs {
+    #[arg(long)]
+    min_lockup_duration: Option<i64>,
+    #[arg(long)]
+    max_lockup_duration: Option<i64>,
+    #[arg(long)]
+    max_reward_rate: Option<u64>,
+    #[arg(long)]
+    max_cooldown_duration: Option<i64>,
+    #[arg(long)]
+    max_timelock_delay: Option<i64>,
+    #[arg(
+        long,
+        help = "Upper bound for the maximum pause duration, 0 to allow unlimited pauses"
+    )]
+    max_pause_duration: Option<i64>,
+    #[arg(long)]
+    min_solvency_horizon: Option<i64>,
+    #[arg(long)]
+    max_solvency_horizon: Option<i64>,
+    #[arg(long)]
+    min_reward_duration: Option<i64>,
+    #[arg(long)]
+    max_reward_duration: Option<i64>,
+}
+
+impl BoundsArgs {
+    fn apply(&self, current: &ParameterBounds) -> ParameterBounds {
+        ParameterBounds {
+            min_lockup_duration: self
+                .min_lockup_duration
+                .unwrap_or(current.min_lockup_duration),
+            max_lockup_duration: self
+                .max_lockup_duration
+                .unwrap_or(current.max_lockup_duration),
+            max_reward_rate: self.max_reward_rate.unwrap_or(current.max_reward_rate),
+            max_cooldown_duration: self
+                .max_cooldown_duration
+                .unwrap_or(current.max_cooldown_duration),
+            max_timelock_delay: self
+                .max_timelock_delay
+                .unwrap_or(current.max_timelock_delay),
+            max_pause_duration: self
+                .max_pause_duration
+                .unwrap_or(current.max_pause_duration),
+            min_solvency_horizon: self
+                .min_solvency_horizon
+                .unwrap_or(current.min_solvency_horizon),
+            max_solvency_horizon: self
+                .max_solvency_horizon
+                .unwrap_or(current.max_solvency_horizon),
+            min_reward_duration: self
+                .min_reward_duration
+                .unwrap_or(current.min_reward_duration),
+            max_reward_duration: self
+                .max_reward_duration
+                .unwrap_or(current.max_reward_duration),
+        }
+    }
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub enum ActionArg {
+    #[command(about = "Queue a reward rate update")]
+    UpdateRewardRate { reward_index: u8, new_rate: u64 },
+    #[command(about = "Queue a lockup duration update")]
+    UpdateLockupDuration { new_duration: i64 },
+    #[command(about = "Queue an admin nomination")]
+    ProposeAdmin { new_admin: Pubkey },
+    #[command(about = "Queue a timelock delay update, including shortening it")]
+    UpdateTimelockDelay { new_delay: i64 },
+    #[command(about = "Queue a new reward period funded from the stream's unreserved balance")]
+    NotifyRewardAmount {
+        reward_index: u8,
+        amount: u64,
+        duration: i64,
+    },
+    #[command(about = "Queue retiring a reward stream")]
+    RetireRewardStream { reward_index: u8 },
+    #[command(about = "Queue a cooldown duration update")]
+    UpdateCooldownDuration { new_duration: i64 },
+    #[command(about = "Queue a max boost update")]
+    UpdateMaxBoost { max_boost_bps: u16 },
+    #[command(about = "Queue an early unstake penalty update")]
+    UpdateEarlyUnstakePenalty {
+        penalty_bps: u16,
+        #[arg(long, help = "Scale the penalty with the remaining lock")]
+        scale_with_lock: bool,
+    },
+    #[command(about = "Queue a maximum pause duration update")]
+    UpdateMaxPauseDuration { new_duration: i64 },
+    #[command(about = "Queue a parameter bounds update; omitted bounds keep their current value")]
+    UpdateParameterBounds {
+        #[command(flatten)]
+        bounds: BoundsArgs,
+    },
+    #[command(about = "Queue a penalty destination update")]
+    UpdatePenaltyDestination {
+        #[arg(value_enum)]
+        destination: DestinationArg,
+        #[arg(
+            long,
+            required_if_eq("destination", "treasury"),
+            help = "Staking token account receiving penalties"
+        )]
+        treasury: Option<Pubkey>,
+    },
+    #[command(about = "Queue enabling or disabling auto-compounding")]
+    SetAutoCompound {
+        #[arg(action = clap::ArgAction::Set)]
+        enabled: bool,
+    },
+}
+
+impl From<RoleArg> for Role {
+    fn from(role: RoleArg) -> Self {
+        match role {
+            RoleArg::Pauser => Role::Pauser,
+            RoleArg::RateManager => Role::RateManager,
+            RoleArg::Funder => Role::Funder,
+            RoleArg::Guardian => Role::Guardian,
+        }
+    }
+}
+
+impl From<DestinationArg> for PenaltyDestination {
+    fn from(destination: DestinationArg) -> Self {
+        match destination {
+            DestinationArg::Burn => PenaltyDestination::Burn,
+            DestinationArg::Treasury => PenaltyDestination::Treasury,
+            DestinationArg::Redistribute => PenaltyDestination::Redistribute,
+        }
+    }
+}
+
+impl ActionArg {
+    // 转换为排队的变更；参数边界中未指定的值取池子当前的边界
+    fn into_action(self, pool: &Pool) -> TimelockAction {
+        match self {
+            ActionArg::UpdateRewardRate {
+                reward_index,
+                new_rate,
+            } => TimelockAction::UpdateRewardRate {
+                reward_index,
+                new_rate,
+            },
+            ActionArg::UpdateLockupDuration { new_duration } => {
+                TimelockAction::UpdateLockupDuration { new_duration }
+            }
+            ActionArg::ProposeAdmin { new_admin } => TimelockAction::ProposeAdmin { new_admin },
+            ActionArg::UpdateTimelockDelay { new_delay } => {
+                TimelockAction::UpdateTimelockDelay { new_delay }
+            }
+            ActionArg::NotifyRewardAmount {
+                reward_index,
+                amount,
+                duration,
+            } => TimelockAction::NotifyRewardAmount {
+                reward_index,
+                amount,
+                duration,
+            },
+            ActionArg::RetireRewardStream { reward_index } => {
+                TimelockAction::RetireRewardStream { reward_index }
+            }
+            ActionArg::UpdateCooldownDuration { new_duration } => {
+                TimelockAction::UpdateCooldownDuration { new_duration }
+            }
+            ActionArg::UpdateMaxBoost { max_boost_bps } => {
+                TimelockAction::UpdateMaxBoost { max_boost_bps }
+            }
+            ActionArg::UpdateEarlyUnstakePenalty {
+                penalty_bps,
+                scale_with_lock,
+            } => TimelockAction::UpdateEarlyUnstakePenalty {
+                penalty_bps,
+                scale_with_lock,
+            },
+            ActionArg::UpdateMaxPauseDuration { new_duration } => {
+                TimelockAction::UpdateMaxPauseDuration { new_duration }
+            }
+            ActionArg::UpdateParameterBounds { bounds } => TimelockAction::UpdateParameterBounds {
+                bounds: bounds.apply(&pool.bounds),
+            },
+            ActionArg::UpdatePenaltyDestination {
+                destination,
+                treasury,
+            } => TimelockAction::UpdatePenaltyDestination {
+                destination: destination.into(),
+                treasury: treasury.unwrap_or_default(), // 非国库去向时程序忽略 treasury
+            },
+            ActionArg::SetAutoCompound { enabled } => TimelockAction::SetAutoCompound { enabled },
+        }
+    }
+}
+
+pub fn addresses(args: &AddressesArgs) -> Output {
+    let (pool, _) = find_pool_address(&args.staking_mint, args.pool_id);
+    let mut map = Map::new();
+    map.insert("pool".into(), pool.to_json());
+    map.insert(
+        "staking_vault".into(),
+        find_staking_vault_address(&pool).0.to_json(),
+    );
+    map.insert(
+        "receipt_mint".into(),
+        find_receipt_mint_address(&pool).0.to_json(),
+    );
+    if let Some(reward_mint) = args.reward_mint {
+        map.insert(
+            "reward_vault".into(),
+            find_reward_vault_address(&pool, &reward_mint).0.to_json(),
+        );
+    }
+    if let Some(user) = args.user {
+        map.insert(
+            "user_stake_info".into(),
+            find_user_stake_info_address(&pool, &user).0.to_json(),
+        );
+    }
+    Output::Value(Value::Object(map))
+}
+
+pub fn run(config: &Config, command: &Command) -> Result<Output> {
+    match command {
+        Command::Addresses(args) => Ok(addresses(args)),
+        Command::ShowPool => {
+            let (address, pool) = config.fetch_pool()?;
+            Ok(Output::Value(account_json(&address, &pool)))
+        }
+        Command::ShowUser { user } => {
+            let pool = config.pool_address()?;
+            let user = user.unwrap_or(config.authority());
+            let (address, _) = find_user_stake_info_address(&pool, &user);
+            let info: UserStakeInfo = config.fetch(&address)?;
+            Ok(Output::Value(account_json(&address, &info)))
+        }
+        Command::ShowChange { change_id } => {
+            let pool = config.pool_address()?;
+            let (address, _) = find_queued_change_address(&pool, *change_id);
+            let change: QueuedChange = config.fetch(&address)?;
+            Ok(Output::Value(account_json(&address, &change)))
+        }
+        Command::PendingRewards { user } => {
+            let pool = config.pool_address()?;
+            let user = user.unwrap_or(config.authority());
+            let accounts = GetPendingRewardsAccounts {
+                pool,
+                user_stake_info: find_user_stake_info_address(&pool, &user).0,
+            };
+            let view: PendingRewardsView =
+                config.view(instruction::get_pending_rewards(&accounts, user))?;
+            Ok(Output::Value(view.to_json()))
+        }
+        Command::Position { user } => {
+            let pool = config.pool_address()?;
+            let user = user.unwrap_or(config.authority());
+            let accounts = GetUserPositionAccounts {
+                pool,
+                user_stake_info: find_user_stake_info_address(&pool, &user).0,
+            };
+            let view: UserPositionView =
+                config.view(instruction::get_user_position(&accounts, user))?;
+            Ok(Output::Value(view.to_json()))
+        }
+        Command::PoolState => {
+            let (address, pool) = config.fetch_pool()?;
+            let accounts = GetPoolStateAccounts {
+                pool: address,
+                staking_vault: pool.staking_vault,
+            };
+            let mut view_instruction = instruction::get_pool_state(&accounts);
+            view_instruction
+                .accounts
+                .extend(instruction::reward_vault_metas(&pool));
+            let view: PoolStateView = config.view(view_instruction)?;
+            Ok(Output::Value(view.to_json()))
+        }
+        Command::Initialize {
+            staking_mint,
+            reward_mint,
+            pool_id,
+            lockup_duration,
+            allowed_mint_extensions,
+        } => {
+            let (pool, _) = find_pool_address(staking_mint, *pool_id);
+            let accounts = InitializeAccounts {
+                pool,
+                admin: config.authority(),
+                staking_mint: *staking_mint,
+                staking_vault: find_staking_vault_address(&pool).0,
+                reward_mint: *reward_mint,
+                reward_vault: find_reward_vault_address(&pool, reward_mint).0,
+                system_program: system_program::ID,
+                token_program: config.token_program(staking_mint)?,
+                reward_token_program: config.token_program(reward_mint)?,
+                rent: sysvar::rent::ID,
+            };
+            let instruction = instruction::initialize(
+                &accounts,
+                *pool_id,
+                *lockup_duration,
+                *allowed_mint_extensions,
+            );
+            config.process(pool, &[instruction])
+        }
+        command => {
+            let (address, pool) = config.fetch_pool()?;
+            let instructions = pool_instructions(config, command, address, &pool)?;
+            config.process(address, &instructions)
+        }
+    }
+}
+
+// 作用于已有池子的指令
+fn pool_instructions(
+    config: &Config,
+    command: &Command,
+    address: Pubkey,
+    pool: &Pool,
+) -> Result<Vec<Instruction>> {
+    let authority = config.authority();
+    let user_stake_info = |user: &Pubkey| find_user_stake_info_address(&address, user).0;
+    let staking_token_program = || config.token_program(&pool.staking_mint);
+    let mut instructions = Vec::new();
+    let mut transfers = Vec::new(); // 指令中的代币转账，用于解析转账钩子账户
+
+    let mut instruction = match command {
+        Command::Stake {
+            amount,
+            lock_duration,
+            wallet,
+        } => {
+            let token_program = staking_token_program()?;
+            let accounts = StakeAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_staking_wallet: wallet.source(&authority, &pool.staking_mint, &token_program),
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                system_program: system_program::ID,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.user_staking_wallet,
+                destination: accounts.staking_vault,
+                authority,
+                amount: *amount,
+            });
+            instruction::stake(&accounts, *amount, *lock_duration)
+        }
+        Command::Unstake { amount, wallet } => {
+            let token_program = staking_token_program()?;
+            let accounts = UnstakeAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_staking_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.user_staking_wallet,
+                authority: address,
+                amount: *amount,
+            });
+            instruction::unstake(&accounts, *amount)
+        }
+        Command::UnstakeEarly { amount, wallet } => {
+            let token_program = staking_token_program()?;
+            let accounts = UnstakeEarlyAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_staking_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                treasury: (pool.penalty_destination == PenaltyDestination::Treasury)
+                    .then_some(pool.penalty_treasury),
+                reward_vault: staking_stream(pool)
+                    .filter(|_| pool.penalty_destination == PenaltyDestination::Redistribute)
+                    .map(|stream| stream.vault),
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.user_staking_wallet,
+                authority: address,
+                amount: *amount,
+            });
+            // 罚金转入国库或质押代币奖励流金库
+            if let Some(penalty_destination) = accounts.treasury.or(accounts.reward_vault) {
+                transfers.push(TokenTransfer {
+                    mint: pool.staking_mint,
+                    source: accounts.staking_vault,
+                    destination: penalty_destination,
+                    authority: address,
+                    amount: 0,
+                });
+            }
+            instruction::unstake_early(&accounts, *amount)
+        }
+        Command::RequestUnstake { amount } => {
+            let accounts = RequestUnstakeAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+            };
+            instruction::request_unstake(&accounts, *amount)
+        }
+        Command::CancelUnstakeRequest { request_index } => {
+            let accounts = CancelUnstakeRequestAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+            };
+            instruction::cancel_unstake_request(&accounts, *request_index)
+        }
+        Command::WithdrawUnstaked { wallet } => {
+            let token_program = staking_token_program()?;
+            let accounts = WithdrawUnstakedAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_staking_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.user_staking_wallet,
+                authority: address,
+                amount: 0,
+            });
+            instruction::withdraw_unstaked(&accounts)
+        }
+        Command::Poke { user } => {
+            let accounts = PokeAccounts {
+                pool: address,
+                user_stake_info: user_stake_info(&user.unwrap_or(authority)),
+            };
+            instruction::poke(&accounts)
+        }
+        Command::StakeLiquid {
+            amount,
+            wallet,
+            receipt_account,
+        } => {
+            // 收据代币 Mint 与质押代币使用同一个 Token 程序
+            let token_program = staking_token_program()?;
+            let user_receipt_wallet = WalletArgs {
+                token_account: *receipt_account,
+            }
+            .destination(
+                &mut instructions,
+                &authority,
+                &pool.receipt_mint,
+                &token_program,
+            );
+            let accounts = StakeLiquidAccounts {
+                user: authority,
+                pool: address,
+                user_staking_wallet: wallet.source(&authority, &pool.staking_mint, &token_program),
+                user_receipt_wallet,
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                receipt_mint: pool.receipt_mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.user_staking_wallet,
+                destination: accounts.staking_vault,
+                authority,
+                amount: *amount,
+            });
+            instruction::stake_liquid(&accounts, *amount)
+        }
+        Command::UnstakeLiquid {
+            shares,
+            wallet,
+            receipt_account,
+        } => {
+            let token_program = staking_token_program()?;
+            let user_receipt_wallet = WalletArgs {
+                token_account: *receipt_account,
+            }
+            .source(&authority, &pool.receipt_mint, &token_program);
+            let accounts = UnstakeLiquidAccounts {
+                user: authority,
+                pool: address,
+                user_staking_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                user_receipt_wallet,
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                receipt_mint: pool.receipt_mint,
+                reward_vault: staking_stream(pool).map(|stream| stream.vault),
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.user_staking_wallet,
+                authority: address,
+                amount: 0,
+            });
+            // 份额对应的质押代币奖励从奖励流金库转出
+            if let Some(reward_vault) = accounts.reward_vault {
+                transfers.push(TokenTransfer {
+                    mint: pool.staking_mint,
+                    source: reward_vault,
+                    destination: accounts.user_staking_wallet,
+                    authority: address,
+                    amount: 0,
+                });
+            }
+            instruction::unstake_liquid(&accounts, *shares)
+        }
+        Command::Compound { user } => {
+            let stream = staking_stream(pool)
+                .ok_or("the pool has no reward stream paying out the staking token")?;
+            let accounts = CompoundAccounts {
+                caller: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&user.unwrap_or(authority)),
+                staking_vault: pool.staking_vault,
+                reward_vault: stream.vault,
+                staking_mint: pool.staking_mint,
+                token_program: staking_token_program()?,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.reward_vault,
+                destination: accounts.staking_vault,
+                authority: address,
+                amount: 0,
+            });
+            instruction::compound(&accounts)
+        }
+        Command::ClaimRewards {
+            reward_index,
+            wallet,
+        } => {
+            let stream = reward_stream(pool, *reward_index)?;
+            let token_program = config.token_program(&stream.mint)?;
+            let accounts = ClaimRewardsAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_reward_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &stream.mint,
+                    &token_program,
+                ),
+                reward_vault: stream.vault,
+                reward_mint: stream.mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: stream.mint,
+                source: stream.vault,
+                destination: accounts.user_reward_wallet,
+                authority: address,
+                amount: 0,
+            });
+            instruction::claim_rewards(&accounts, *reward_index)
+        }
+        Command::CloseUserStakeInfo => {
+            let accounts = CloseUserStakeInfoAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+            };
+            instruction::close_user_stake_info(&accounts)
+        }
+        Command::EmergencyExit { wallet } => {
+            let token_program = staking_token_program()?;
+            let accounts = EmergencyExitAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_staking_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.user_staking_wallet,
+                authority: address,
+                amount: 0,
+            });
+            instruction::emergency_exit(&accounts)
+        }
+        Command::EmergencyUnstake {
+            forfeit_rewards,
+            wallet,
+        } => {
+            let token_program = staking_token_program()?;
+            let accounts = EmergencyUnstakeAccounts {
+                user: authority,
+                pool: address,
+                user_stake_info: user_stake_info(&authority),
+                user_staking_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.user_staking_wallet,
+                authority: address,
+                amount: 0,
+            });
+            instruction::emergency_unstake(&accounts, *forfeit_rewards)
+        }
+
+        Command::UpdateRewardRate {
+            reward_index,
+            new_rate,
+        } => {
+            let accounts = UpdateRewardRateAccounts {
+                pool: address,
+                rate_manager: authority,
+            };
+            instruction::update_reward_rate(&accounts, *reward_index, *new_rate)
+        }
+        Command::FundRewards {
+            reward_index,
+            amount,
+            wallet,
+        } => {
+            let stream = reward_stream(pool, *reward_index)?;
+            let token_program = config.token_program(&stream.mint)?;
+            let accounts = FundRewardsAccounts {
+                pool: address,
+                funder: authority,
+                funder_wallet: wallet.source(&authority, &stream.mint, &token_program),
+                reward_vault: stream.vault,
+                reward_mint: stream.mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: stream.mint,
+                source: accounts.funder_wallet,
+                destination: stream.vault,
+                authority,
+                amount: *amount,
+            });
+            instruction::fund_rewards(&accounts, *reward_index, *amount)
+        }
+        Command::NotifyRewardAmount {
+            reward_index,
+            amount,
+            duration,
+            wallet,
+        } => {
+            let stream = reward_stream(pool, *reward_index)?;
+            let token_program = config.token_program(&stream.mint)?;
+            let accounts = NotifyRewardAmountAccounts {
+                pool: address,
+                funder: authority,
+                funder_wallet: wallet.source(&authority, &stream.mint, &token_program),
+                reward_vault: stream.vault,
+                reward_mint: stream.mint,
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: stream.mint,
+                source: accounts.funder_wallet,
+                destination: stream.vault,
+                authority,
+                amount: *amount,
+            });
+            instruction::notify_reward_amount(&accounts, *reward_index, *amount, *duration)
+        }
+        Command::GrantRole { role, account } => {
+            let accounts = GrantRoleAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::grant_role(&accounts, (*role).into(), *account)
+        }
+        Command::RevokeRole { role } => {
+            let accounts = RevokeRoleAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::revoke_role(&accounts, (*role).into())
+        }
+        Command::ProposeAdmin { new_admin } => {
+            let accounts = ProposeAdminAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::propose_admin(&accounts, *new_admin)
+        }
+        Command::AcceptAdmin => {
+            let accounts = AcceptAdminAccounts {
+                pool: address,
+                new_admin: authority,
+            };
+            instruction::accept_admin(&accounts)
+        }
+        Command::CancelAdminTransfer => {
+            let accounts = CancelAdminTransferAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::cancel_admin_transfer(&accounts)
+        }
+        Command::EmergencyWithdrawStakedTokens { amount, wallet } => {
+            let token_program = staking_token_program()?;
+            let accounts = EmergencyWithdrawStakedTokensAccounts {
+                pool: address,
+                guardian: authority,
+                staking_vault: pool.staking_vault,
+                staking_mint: pool.staking_mint,
+                destination_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &pool.staking_mint,
+                    &token_program,
+                ),
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: pool.staking_mint,
+                source: accounts.staking_vault,
+                destination: accounts.destination_wallet,
+                authority: address,
+                amount: *amount,
+            });
+            instruction::emergency_withdraw_staked_tokens(&accounts, *amount)
+        }
+        Command::EmergencyWithdrawRewardTokens {
+            reward_index,
+            amount,
+            wallet,
+        } => {
+            let stream = reward_stream(pool, *reward_index)?;
+            let token_program = config.token_program(&stream.mint)?;
+            let accounts = EmergencyWithdrawRewardTokensAccounts {
+                pool: address,
+                guardian: authority,
+                reward_mint: stream.mint,
+                reward_vault: stream.vault,
+                destination_wallet: wallet.destination(
+                    &mut instructions,
+                    &authority,
+                    &stream.mint,
+                    &token_program,
+                ),
+                token_program,
+            };
+            transfers.push(TokenTransfer {
+                mint: stream.mint,
+                source: stream.vault,
+                destination: accounts.destination_wallet,
+                authority: address,
+                amount: *amount,
+            });
+            instruction::emergency_withdraw_reward_tokens(&accounts, *reward_index, *amount)
+        }
+        Command::AddRewardStream {
+            reward_mint,
+            allowed_mint_extensions,
+        } => {
+            let accounts = AddRewardStreamAccounts {
+                pool: address,
+                admin: authority,
+                reward_mint: *reward_mint,
+                reward_vault: find_reward_vault_address(&address, reward_mint).0,
+                system_program: system_program::ID,
+                token_program: config.token_program(reward_mint)?,
+            };
+            instruction::add_reward_stream(&accounts, *allowed_mint_extensions)
+        }
+        Command::RetireRewardStream { reward_index } => {
+            let accounts = RetireRewardStreamAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::retire_reward_stream(&accounts, *reward_index)
+        }
+        Command::EnableReceiptToken => {
+            let accounts = EnableReceiptTokenAccounts {
+                pool: address,
+                admin: authority,
+                staking_mint: pool.staking_mint,
+                receipt_mint: find_receipt_mint_address(&address).0,
+                system_program: system_program::ID,
+                token_program: staking_token_program()?,
+            };
+            instruction::enable_receipt_token(&accounts)
+        }
+        Command::SetAutoCompound { enabled } => {
+            let accounts = SetAutoCompoundAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::set_auto_compound(&accounts, *enabled)
+        }
+        Command::Pause => {
+            let accounts = PauseAccounts {
+                pool: address,
+                pauser: authority,
+            };
+            instruction::pause(&accounts)
+        }
+        Command::Unpause => {
+            let accounts = UnpauseAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::unpause(&accounts)
+        }
+        Command::UpdateMaxPauseDuration { new_duration } => {
+            let accounts = UpdateMaxPauseDurationAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_max_pause_duration(&accounts, *new_duration)
+        }
+        Command::UpdateLockupDuration { new_duration } => {
+            let accounts = UpdateLockupDurationAccounts {
+                pool: address,
+                rate_manager: authority,
+            };
+            instruction::update_lockup_duration(&accounts, *new_duration)
+        }
+        Command::UpdateTimelockDelay { new_delay } => {
+            let accounts = UpdateTimelockDelayAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_timelock_delay(&accounts, *new_delay)
+        }
+        Command::ScheduleChange { eta, action } => {
+            let eta = match eta {
+                Some(eta) => *eta,
+                None => config.clock()?.unix_timestamp + pool.timelock_delay + ETA_MARGIN,
+            };
+            let accounts = ScheduleChangeAccounts {
+                pool: address,
+                queued_change: find_queued_change_address(&address, pool.next_change_id).0,
+                admin: authority,
+                system_program: system_program::ID,
+            };
+            instruction::schedule_change(&accounts, action.into_action(pool), eta)
+        }
+        Command::ExecuteChange { change_id } => {
+            let queued_change = find_queued_change_address(&address, *change_id).0;
+            let change: QueuedChange = config.fetch(&queued_change)?;
+            let accounts = ExecuteChangeAccounts {
+                pool: address,
+                queued_change,
+                rent_payer: change.rent_payer,
+            };
+            instruction::execute_change(&accounts)
+        }
+        Command::CancelChange { change_id } => {
+            let queued_change = find_queued_change_address(&address, *change_id).0;
+            let change: QueuedChange = config.fetch(&queued_change)?;
+            let accounts = CancelChangeAccounts {
+                pool: address,
+                queued_change,
+                admin: authority,
+                rent_payer: change.rent_payer,
+            };
+            instruction::cancel_change(&accounts)
+        }
+        Command::UpdateMaxBoost { max_boost_bps } => {
+            let accounts = UpdateMaxBoostAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_max_boost(&accounts, *max_boost_bps)
+        }
+        Command::UpdateEarlyUnstakePenalty {
+            penalty_bps,
+            scale_with_lock,
+        } => {
+            let accounts = UpdateEarlyUnstakePenaltyAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_early_unstake_penalty(&accounts, *penalty_bps, *scale_with_lock)
+        }
+        Command::UpdatePenaltyDestination {
+            destination,
+            treasury,
+        } => {
+            let accounts = UpdatePenaltyDestinationAccounts {
+                pool: address,
+                admin: authority,
+            };
+            // 非国库去向时程序忽略 treasury 参数
+            instruction::update_penalty_destination(
+                &accounts,
+                (*destination).into(),
+                treasury.unwrap_or_default(),
+            )
+        }
+        Command::UpdateCooldownDuration { new_duration } => {
+            let accounts = UpdateCooldownDurationAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_cooldown_duration(&accounts, *new_duration)
+        }
+        Command::UpdateParameterBounds { bounds } => {
+            let bounds = bounds.apply(&pool.bounds);
+            let accounts = UpdateParameterBoundsAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_parameter_bounds(&accounts, bounds)
+        }
+        Command::UpdateSolvencyHorizon { new_horizon } => {
+            let accounts = UpdateSolvencyHorizonAccounts {
+                pool: address,
+                admin: authority,
+            };
+            instruction::update_solvency_horizon(&accounts, *new_horizon)
+        }
+        Command::Addresses(_)
+        | Command::ShowPool
+        | Command::ShowUser { .. }
+        | Command::ShowChange { .. }
+        | Command::PendingRewards { .. }
+        | Command::Position { .. }
+        | Command::PoolState
+        | Command::Initialize { .. } => unreachable!("handled in run"),
+    };
+    config.add_transfer_hook_accounts(&mut instruction, &transfers)?;
+    instructions.push(instruction);
+    Ok(instructions)
+}
+
+impl WalletArgs {
+    // 转出代币的账户：缺省为关联代币账户，不存在时由程序报错
+    fn source(&self, owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+        self.token_account
+            .unwrap_or_else(|| associated_token_address(owner, mint, token_program))
+    }
+
+    // 接收代币的账户：缺省为关联代币账户，并在指令前幂等创建
+    fn destination(
+        &self,
+        instructions: &mut Vec<Instruction>,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        if let Some(token_account) = self.token_account {
+            return token_account;
+        }
+        let address = associated_token_address(owner, mint, token_program);
+        instructions.push(Instruction {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*owner, true), // 支付租金
+                AccountMeta::new(address, false),
+                AccountMeta::new_readonly(*owner, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(*token_program, false),
+            ],
+            data: vec![1], // CreateIdempotent
+        });
+        address
+    }
+}
+
+// 关联代币账户地址：[所有者, Token 程序, Mint]
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+// 已配置的奖励流
+fn reward_stream(pool: &Pool, reward_index: u8) -> Result<&RewardStream> {
+    pool.reward_streams
+        .get(reward_index as usize)
+        .filter(|stream| stream.mint != Pubkey::default())
+        .ok_or_else(|| format!("reward stream {reward_index} is not configured").into())
+}
+
+// 奖励代币与质押代币相同的奖励流，复利、份额桶奖励和罚金再分配都使用该奖励流
+fn staking_stream(pool: &Pool) -> Option<&RewardStream> {
+    pool.reward_streams
+        .iter()
+        .find(|stream| stream.mint == pool.staking_mint)
+}
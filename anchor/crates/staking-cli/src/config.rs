@@ -0,0 +1,274 @@
+// 命令执行环境：RPC 连接、签名者和池子地址，以及发送、模拟交易、读取账户和解析转账钩子账户的辅助方法
+
+use crate::output::{diff, Output, Simulation, ToJson};
+use crate::{signer, Cli, Result};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use borsh::BorshDeserialize;
+use serde_json::json;
+use solana_account_decoder_client_types::UiAccountEncoding;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::{
+    client_error::ErrorKind as ClientErrorKind,
+    config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+    request::{RpcError, RpcResponseErrorData},
+    response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    account::from_account, clock::Clock, commitment_config::CommitmentConfig,
+    instruction::Instruction, message::Message, pubkey::Pubkey, signature::Signature,
+    signer::Signer, sysvar, transaction::Transaction,
+};
+use spl_token_2022::{
+    extension::{transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+use spl_transfer_hook_interface::offchain::add_extra_account_metas_for_execute;
+use staking_program_client::{
+    state::{Pool, ProgramAccount},
+    ID,
+};
+
+// 程序返回数据的最大长度；运行时会截掉返回数据末尾的零字节，解码前需要补齐
+const MAX_RETURN_DATA: usize = 1024;
+
+// 指令中的一笔代币转账，用于解析 Mint 的转账钩子需要的额外账户；
+// amount 只在钩子按指令数据推导账户时使用，由链上计算的金额记为 0
+pub struct TokenTransfer {
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+pub struct Config {
+    pub rpc: RpcClient,
+    pub signer: Box<dyn Signer>,        // 管理员或用户签名者
+    fee_payer: Option<Box<dyn Signer>>, // 单独的手续费支付者，缺省由 signer 支付
+    pool: Option<Pubkey>,               // --pool 指定的池子地址
+    dry_run: bool,                      // 只模拟交易，不发送
+}
+
+impl Config {
+    pub fn new(cli: &Cli) -> Result<Self> {
+        let fee_payer = cli
+            .fee_payer
+            .as_deref()
+            .map(|path| signer::signer_from_path(path, "fee payer"))
+            .transpose()?;
+        Ok(Config {
+            rpc: RpcClient::new_with_commitment(
+                normalize_url(&cli.url),
+                CommitmentConfig::confirmed(),
+            ),
+            signer: signer::signer_from_path(&cli.keypair, "keypair")?,
+            fee_payer,
+            pool: cli.pool,
+            dry_run: cli.dry_run,
+        })
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    fn fee_payer(&self) -> &dyn Signer {
+        self.fee_payer.as_deref().unwrap_or(self.signer.as_ref())
+    }
+
+    pub fn pool_address(&self) -> Result<Pubkey> {
+        Ok(self.pool.ok_or("this command requires --pool <ADDRESS>")?)
+    }
+
+    pub fn fetch_pool(&self) -> Result<(Pubkey, Pool)> {
+        let address = self.pool_address()?;
+        Ok((address, self.fetch(&address)?))
+    }
+
+    // 读取并解码程序拥有的账户
+    pub fn fetch<A: ProgramAccount>(&self, address: &Pubkey) -> Result<A> {
+        let account = self.rpc.get_account(address)?;
+        if account.owner != ID {
+            return Err(format!("account {address} is not owned by the staking program").into());
+        }
+        A::decode(&account.data).map_err(|err| format!("account {address}: {err}").into())
+    }
+
+    // Mint 所属的 Token 程序（Token 或 Token-2022）
+    pub fn token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        Ok(self.rpc.get_account(mint)?.owner)
+    }
+
+    // Mint 启用 TransferHook 扩展时，按钩子程序的 ExtraAccountMetaList 解析每笔转账需要的额外账户，
+    // 连同钩子程序和 ExtraAccountMetaList 追加到 instruction 末尾，程序将其作为 remaining accounts 转发
+    pub fn add_transfer_hook_accounts(
+        &self,
+        instruction: &mut Instruction,
+        transfers: &[TokenTransfer],
+    ) -> Result<()> {
+        for transfer in transfers {
+            let Some(hook_program) = self.transfer_hook_program(&transfer.mint)? else {
+                continue;
+            };
+            let fetch = |address: Pubkey| {
+                let data = self
+                    .rpc
+                    .get_account_with_commitment(&address, self.rpc.commitment())
+                    .map(|response| response.value.map(|account| account.data))
+                    .map_err(Into::into);
+                std::future::ready(data)
+            };
+            futures_executor::block_on(add_extra_account_metas_for_execute(
+                instruction,
+                &hook_program,
+                &transfer.source,
+                &transfer.mint,
+                &transfer.destination,
+                &transfer.authority,
+                transfer.amount,
+                fetch,
+            ))
+            .map_err(|err| format!("transfer hook accounts for mint {}: {err}", transfer.mint))?;
+        }
+        Ok(())
+    }
+
+    // Token-2022 Mint 的转账钩子程序，未启用 TransferHook 扩展或未设置程序时为 None
+    fn transfer_hook_program(&self, mint: &Pubkey) -> Result<Option<Pubkey>> {
+        let account = self.rpc.get_account(mint)?;
+        if account.owner != spl_token_2022::ID {
+            return Ok(None);
+        }
+        let state = StateWithExtensions::<Mint>::unpack(&account.data)?;
+        Ok(state
+            .get_extension::<TransferHook>()
+            .ok()
+            .and_then(|hook| Option::<Pubkey>::from(hook.program_id)))
+    }
+
+    pub fn clock(&self) -> Result<Clock> {
+        let account = self.rpc.get_account(&sysvar::clock::ID)?;
+        Ok(from_account(&account).ok_or("failed to decode the clock sysvar")?)
+    }
+
+    // 发送交易，或在 --dry-run 时模拟交易并比较池子状态
+    pub fn process(&self, pool: Pubkey, instructions: &[Instruction]) -> Result<Output> {
+        if self.dry_run {
+            return self.dry_run(pool, instructions);
+        }
+        let signature = self.send(instructions)?;
+        Ok(Output::Value(json!({
+            "pool": pool.to_string(),
+            "signature": signature.to_string(),
+        })))
+    }
+
+    // 通过模拟交易调用只读视图，按 Borsh 解码返回数据
+    pub fn view<T: BorshDeserialize>(&self, instruction: Instruction) -> Result<T> {
+        let result = self.simulate(&[instruction], &[])?;
+        let return_data = result
+            .return_data
+            .filter(|return_data| return_data.program_id == ID.to_string())
+            .ok_or("the view returned no data")?;
+        let mut data = BASE64_STANDARD.decode(&return_data.data.0)?;
+        data.resize(MAX_RETURN_DATA.max(data.len()), 0);
+        Ok(T::deserialize(&mut data.as_slice())?)
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let fee_payer = self.fee_payer();
+        let message = Message::new(instructions, Some(&fee_payer.pubkey()));
+        // 只有指令要求签名的账户参与签名，例如 poke 和 execute_change 只需要手续费支付者
+        let signer_keys = message.signer_keys();
+        let mut signers = vec![fee_payer];
+        if self.signer.pubkey() != fee_payer.pubkey() && signer_keys.contains(&&self.authority()) {
+            signers.push(self.signer.as_ref());
+        }
+        let mut transaction = Transaction::new_unsigned(message);
+        transaction.try_sign(&signers, self.rpc.get_latest_blockhash()?)?;
+        self.rpc
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|err| {
+                if let ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+                    ..
+                }) = err.kind()
+                {
+                    print_logs(result.logs.as_deref());
+                }
+                err.into()
+            })
+    }
+
+    fn dry_run(&self, pool: Pubkey, instructions: &[Instruction]) -> Result<Output> {
+        let before = self
+            .rpc
+            .get_account_with_commitment(&pool, self.rpc.commitment())?
+            .value
+            .and_then(|account| Pool::decode(&account.data).ok());
+        let result = self.simulate(instructions, &[pool])?;
+        let after = result
+            .accounts
+            .into_iter()
+            .flatten()
+            .next()
+            .flatten()
+            .and_then(|account| account.data.decode())
+            .and_then(|data| Pool::decode(&data).ok());
+        Ok(Output::Simulation(Simulation {
+            units_consumed: result.units_consumed,
+            changes: diff(
+                before.map(|pool| pool.to_json()).as_ref(),
+                after.map(|pool| pool.to_json()).as_ref(),
+            ),
+            logs: result.logs.unwrap_or_default(),
+        }))
+    }
+
+    // 模拟交易不需要签名，硬件钱包无需确认；addresses 为需要返回模拟后状态的账户
+    fn simulate(
+        &self,
+        instructions: &[Instruction],
+        addresses: &[Pubkey],
+    ) -> Result<RpcSimulateTransactionResult> {
+        let message = Message::new(instructions, Some(&self.fee_payer().pubkey()));
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.rpc.commitment()),
+            accounts: (!addresses.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: addresses.iter().map(Pubkey::to_string).collect(),
+            }),
+            ..Default::default()
+        };
+        let result = self
+            .rpc
+            .simulate_transaction_with_config(&Transaction::new_unsigned(message), config)?
+            .value;
+        if let Some(err) = &result.err {
+            print_logs(result.logs.as_deref());
+            return Err(format!("transaction simulation failed: {err}").into());
+        }
+        Ok(result)
+    }
+}
+
+// 交易失败时把程序日志输出到标准错误，便于定位失败的约束
+fn print_logs(logs: Option<&[String]>) {
+    for log in logs.unwrap_or_default() {
+        eprintln!("  {log}");
+    }
+}
+
+// 与 Solana CLI 相同的集群简称
+fn normalize_url(url: &str) -> String {
+    match url {
+        "l" | "localhost" => "http://127.0.0.1:8899",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
@@ -0,0 +1,87 @@
+// staking_program 的命令行工具：每条管理员和用户指令对应一个子命令，
+// 指令由 staking-program-client 构建，所需账户从链上的池子状态推导。
+// --dry-run 只模拟交易并输出池子状态的变化，--output json 输出便于脚本处理的 JSON
+
+mod command;
+mod config;
+mod output;
+mod signer;
+
+use clap::Parser;
+use command::Command;
+use config::Config;
+use output::OutputFormat;
+use solana_sdk::pubkey::Pubkey;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Parser)]
+#[command(
+    name = "staking-cli",
+    version,
+    about = "Administer staking pools and manage staking positions",
+    after_help = signer::HARDWARE_WALLET_NOTE
+)]
+pub struct Cli {
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        default_value = "localhost",
+        value_name = "URL_OR_MONIKER",
+        help = "RPC URL or moniker: localhost, devnet, testnet, mainnet-beta"
+    )]
+    pub url: String,
+    #[arg(
+        long,
+        short = 'k',
+        global = true,
+        default_value = signer::DEFAULT_KEYPAIR_PATH,
+        value_name = "SIGNER_PATH",
+        help = signer::KEYPAIR_HELP
+    )]
+    pub keypair: String,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SIGNER_PATH",
+        help = "Transaction fee payer [default: --keypair]"
+    )]
+    pub fee_payer: Option<String>,
+    #[arg(long, global = true, value_name = "ADDRESS", help = "Pool address")]
+    pub pool: Option<Pubkey>,
+    #[arg(
+        long,
+        global = true,
+        help = "Simulate the transaction and print the resulting pool changes without sending it"
+    )]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value_t = OutputFormat::Display,
+        help = "Output format"
+    )]
+    pub output: OutputFormat,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+fn run(cli: &Cli) -> Result<()> {
+    let output = match &cli.command {
+        // 地址派生不需要连接集群和签名者
+        Command::Addresses(args) => command::addresses(args),
+        command => command::run(&Config::new(cli)?, command)?,
+    };
+    output.print(cli.output);
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(&cli) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,345 @@
+// 命令输出：账户和视图转换为 JSON，模拟结果按字段列出池子状态的变化。
+// display 格式把 JSON 展开为每行一个字段，json 格式原样输出
+
+use clap::ValueEnum;
+use serde_json::{json, Map, Value};
+use solana_sdk::pubkey::Pubkey;
+use staking_program_client::state::{
+    ParameterBounds, PenaltyDestination, PendingRewardsView, Pool, PoolStateView, QueuedChange,
+    RewardStream, RewardStreamView, Role, StakeLot, TimelockAction, UnbondingRequest,
+    UserPositionView, UserStakeInfo,
+};
+use std::collections::HashMap;
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    Display, // 便于阅读的文本
+    Json,    // 便于脚本处理的 JSON
+}
+
+pub enum Output {
+    Value(Value),           // 账户、视图或交易签名
+    Simulation(Simulation), // --dry-run 的模拟结果
+}
+
+pub struct Simulation {
+    pub units_consumed: Option<u64>, // 消耗的计算单元
+    pub changes: Vec<Change>,        // 池子状态的变化
+    pub logs: Vec<String>,           // 程序日志
+}
+
+// 池子状态中单个字段的变化，账户不存在时对应的值为 null
+pub struct Change {
+    pub field: String,
+    pub before: Value,
+    pub after: Value,
+}
+
+impl Output {
+    pub fn print(&self, format: OutputFormat) {
+        match (self, format) {
+            (Output::Value(value), OutputFormat::Json) => println!("{value:#}"),
+            (Output::Value(value), OutputFormat::Display) => {
+                for (field, value) in flatten(value) {
+                    println!("{field}: {}", display(&value));
+                }
+            }
+            (Output::Simulation(simulation), OutputFormat::Json) => {
+                let changes: Vec<Value> = simulation
+                    .changes
+                    .iter()
+                    .map(|change| {
+                        json!({"field": change.field, "before": change.before, "after": change.after})
+                    })
+                    .collect();
+                let value = json!({
+                    "dry_run": true,
+                    "units_consumed": simulation.units_consumed,
+                    "changes": changes,
+                    "logs": simulation.logs,
+                });
+                println!("{value:#}");
+            }
+            (Output::Simulation(simulation), OutputFormat::Display) => {
+                match simulation.units_consumed {
+                    Some(units) => println!("Simulation succeeded ({units} compute units)"),
+                    None => println!("Simulation succeeded"),
+                }
+                if simulation.changes.is_empty() {
+                    println!("Pool unchanged");
+                } else {
+                    println!("Pool changes:");
+                    for change in &simulation.changes {
+                        println!(
+                            "  {}: {} -> {}",
+                            change.field,
+                            display(&change.before),
+                            display(&change.after)
+                        );
+                    }
+                }
+                println!("Logs:");
+                for log in &simulation.logs {
+                    println!("  {log}");
+                }
+            }
+        }
+    }
+}
+
+// 字符串不带引号输出，其余值按 JSON 输出
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+// 将 JSON 值展开为 (路径, 叶子值) 列表，路径形如 reward_streams[0].reward_rate
+pub fn flatten(value: &Value) -> Vec<(String, Value)> {
+    fn walk(path: String, value: &Value, fields: &mut Vec<(String, Value)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    walk(path, value, fields);
+                }
+            }
+            Value::Array(items) if !items.is_empty() => {
+                for (index, item) in items.iter().enumerate() {
+                    walk(format!("{path}[{index}]"), item, fields);
+                }
+            }
+            value => fields.push((path, value.clone())),
+        }
+    }
+    let mut fields = Vec::new();
+    walk(String::new(), value, &mut fields);
+    fields
+}
+
+// 按字段比较两个账户状态，None 表示账户不存在（尚未创建或已关闭）
+pub fn diff(before: Option<&Value>, after: Option<&Value>) -> Vec<Change> {
+    let before = before.map(flatten).unwrap_or_default();
+    let after = after.map(flatten).unwrap_or_default();
+    let before_fields: HashMap<&str, &Value> = before
+        .iter()
+        .map(|(field, value)| (field.as_str(), value))
+        .collect();
+    let after_fields: HashMap<&str, &Value> = after
+        .iter()
+        .map(|(field, value)| (field.as_str(), value))
+        .collect();
+
+    let mut changes: Vec<Change> = after
+        .iter()
+        .filter(|(field, value)| before_fields.get(field.as_str()) != Some(&value))
+        .map(|(field, value)| Change {
+            field: field.clone(),
+            before: before_fields
+                .get(field.as_str())
+                .map_or(Value::Null, |value| (*value).clone()),
+            after: value.clone(),
+        })
+        .collect();
+    // 只存在于变化前的字段，例如关闭的账户或被移除的数组元素
+    changes.extend(
+        before
+            .iter()
+            .filter(|(field, _)| !after_fields.contains_key(field.as_str()))
+            .map(|(field, value)| Change {
+                field: field.clone(),
+                before: value.clone(),
+                after: Value::Null,
+            }),
+    );
+    changes
+}
+
+// 带地址的账户 JSON，地址排在账户字段之前
+pub fn account_json(address: &Pubkey, account: &impl ToJson) -> Value {
+    let mut map = Map::new();
+    map.insert("address".into(), address.to_string().into());
+    if let Value::Object(fields) = account.to_json() {
+        map.extend(fields);
+    }
+    Value::Object(map)
+}
+
+// 转换为 JSON：公钥输出为 base58 字符串，u128 超出 JSON 安全整数范围，输出为十进制字符串
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+macro_rules! json_number {
+    ($($ty:ty),*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self) -> Value {
+                    Value::from(*self)
+                }
+            }
+        )*
+    };
+}
+
+json_number!(bool, u8, u16, u64, i64);
+
+impl ToJson for u128 {
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl ToJson for Pubkey {
+    fn to_json(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        Value::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl ToJson for Role {
+    fn to_json(&self) -> Value {
+        Value::String(format!("{self:?}"))
+    }
+}
+
+impl ToJson for PenaltyDestination {
+    fn to_json(&self) -> Value {
+        Value::String(format!("{self:?}"))
+    }
+}
+
+impl ToJson for TimelockAction {
+    fn to_json(&self) -> Value {
+        match *self {
+            TimelockAction::UpdateRewardRate {
+                reward_index,
+                new_rate,
+            } => {
+                json!({"type": "UpdateRewardRate", "reward_index": reward_index, "new_rate": new_rate})
+            }
+            TimelockAction::UpdateLockupDuration { new_duration } => {
+                json!({"type": "UpdateLockupDuration", "new_duration": new_duration})
+            }
+            TimelockAction::ProposeAdmin { new_admin } => {
+                json!({"type": "ProposeAdmin", "new_admin": new_admin.to_string()})
+            }
+            TimelockAction::UpdateTimelockDelay { new_delay } => {
+                json!({"type": "UpdateTimelockDelay", "new_delay": new_delay})
+            }
+            TimelockAction::NotifyRewardAmount {
+                reward_index,
+                amount,
+                duration,
+            } => {
+                json!({"type": "NotifyRewardAmount", "reward_index": reward_index, "amount": amount, "duration": duration})
+            }
+            TimelockAction::RetireRewardStream { reward_index } => {
+                json!({"type": "RetireRewardStream", "reward_index": reward_index})
+            }
+            TimelockAction::UpdateCooldownDuration { new_duration } => {
+                json!({"type": "UpdateCooldownDuration", "new_duration": new_duration})
+            }
+            TimelockAction::UpdateMaxBoost { max_boost_bps } => {
+                json!({"type": "UpdateMaxBoost", "max_boost_bps": max_boost_bps})
+            }
+            TimelockAction::UpdateEarlyUnstakePenalty {
+                penalty_bps,
+                scale_with_lock,
+            } => {
+                json!({"type": "UpdateEarlyUnstakePenalty", "penalty_bps": penalty_bps, "scale_with_lock": scale_with_lock})
+            }
+            TimelockAction::UpdateMaxPauseDuration { new_duration } => {
+                json!({"type": "UpdateMaxPauseDuration", "new_duration": new_duration})
+            }
+            TimelockAction::UpdateParameterBounds { bounds } => {
+                json!({"type": "UpdateParameterBounds", "bounds": bounds.to_json()})
+            }
+            TimelockAction::UpdatePenaltyDestination {
+                destination,
+                treasury,
+            } => {
+                json!({"type": "UpdatePenaltyDestination", "destination": destination.to_json(), "treasury": treasury.to_string()})
+            }
+            TimelockAction::SetAutoCompound { enabled } => {
+                json!({"type": "SetAutoCompound", "enabled": enabled})
+            }
+        }
+    }
+}
+
+// 结构体按字段声明顺序输出；解构要求列出全部字段，类型新增字段时这里会编译失败
+macro_rules! json_struct {
+    ($($ty:ident { $($field:ident),* $(,)? })*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self) -> Value {
+                    let $ty { $($field),* } = self;
+                    let mut map = Map::new();
+                    $(map.insert(stringify!($field).into(), $field.to_json());)*
+                    Value::Object(map)
+                }
+            }
+        )*
+    };
+}
+
+json_struct! {
+    RewardStream {
+        mint, vault, reward_rate, reward_per_token_stored, period_finish, vault_balance,
+        total_rewards_owed, is_active, mint_extensions,
+    }
+    Pool {
+        admin, pending_admin, pauser, rate_manager, funder, guardian, pool_id, staking_mint,
+        staking_vault, staking_mint_extensions, reward_streams, last_update_timestamp, total_staked,
+        total_weighted_stake, total_unbonding, pool_bump, lockup_duration, max_boost_bps,
+        cooldown_duration, solvency_horizon, timelock_delay, next_change_id, bounds, is_shutdown,
+        shutdown_claims, shutdown_balance, is_paused, paused_at, max_pause_duration, receipt_mint,
+        liquid_principal, liquid_rewards, liquid_reward_per_token_paid, auto_compound,
+        early_unstake_penalty_bps, penalty_scales_with_lock, penalty_destination, penalty_treasury,
+        penalties_burned, penalties_to_treasury, penalties_redistributed,
+    }
+    ParameterBounds {
+        min_lockup_duration, max_lockup_duration, max_reward_rate, max_cooldown_duration,
+        max_timelock_delay, max_pause_duration, min_solvency_horizon, max_solvency_horizon,
+        min_reward_duration, max_reward_duration,
+    }
+    QueuedChange { pool, change_id, action, eta, rent_payer, bump }
+    StakeLot { amount, unlock_timestamp, lock_duration }
+    UnbondingRequest { amount, available_at }
+    UserStakeInfo {
+        pool, user, stake_amount, weighted_amount, stake_start_timestamp, lots, unbonding,
+        reward_per_token_paid, rewards,
+    }
+    PendingRewardsView { pool, user, timestamp, reward_mints, rewards }
+    UserPositionView {
+        pool, user, timestamp, stake_amount, locked_amount, unlocked_amount, next_unlock_timestamp,
+        final_unlock_timestamp, stake_start_timestamp, weighted_amount, current_weight,
+        unbonding_amount, withdrawable_amount, rewards,
+    }
+    RewardStreamView {
+        mint, vault, reward_rate, current_reward_rate, period_finish, reward_per_token_stored,
+        vault_balance, vault_amount, total_rewards_owed, unreserved_balance, is_active,
+    }
+    PoolStateView {
+        pool, timestamp, is_paused, is_shutdown, total_staked, total_weighted_stake,
+        total_unbonding, staking_vault_balance, liquid_principal, liquid_rewards, lockup_duration,
+        cooldown_duration, max_boost_bps, reward_streams,
+    }
+}
@@ -0,0 +1,143 @@
+// 命令行工具的离线测试：子命令覆盖程序的全部指令、地址派生与客户端一致、签名者路径的错误提示
+
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use staking_program_client::pda::{
+    find_pool_address, find_receipt_mint_address, find_reward_vault_address,
+    find_staking_vault_address, find_user_stake_info_address,
+};
+use std::process::{Command, Output};
+
+const PROGRAM_SOURCE: &str = include_str!("../../../programs/staking_program/src/lib.rs");
+
+fn staking_cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_staking-cli"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// #[program] 模块中的指令名：第一个参数为 ctx: Context<...> 的公开函数
+fn program_instructions() -> Vec<String> {
+    PROGRAM_SOURCE
+        .split("pub fn ")
+        .skip(1)
+        .filter_map(|rest| {
+            let (signature, args) = rest.split_once('(')?;
+            // 去掉泛型生命周期参数，例如 stake<'info>
+            let name = signature.split('<').next()?;
+            args.trim_start()
+                .starts_with("ctx: Context<")
+                .then(|| name.to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn every_instruction_has_a_subcommand() {
+    let output = staking_cli(&["--help"]);
+    assert!(output.status.success());
+    let help = String::from_utf8(output.stdout).unwrap();
+    let subcommands: Vec<&str> = help
+        .lines()
+        .filter_map(|line| line.strip_prefix("  ")?.split_whitespace().next())
+        .collect();
+
+    let instructions = program_instructions();
+    assert!(instructions.len() >= 40, "{instructions:?}");
+    for name in instructions {
+        // 只读视图去掉 get_ 前缀，get_user_position 简写为 position
+        let subcommand = match name.as_str() {
+            "get_user_position" => "position".to_string(),
+            name => name.trim_start_matches("get_").replace('_', "-"),
+        };
+        assert!(
+            subcommands.contains(&subcommand.as_str()),
+            "no subcommand for {name}"
+        );
+    }
+}
+
+#[test]
+fn derives_addresses_offline() {
+    let staking_mint = Pubkey::new_unique();
+    let reward_mint = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let output = staking_cli(&[
+        "addresses",
+        "--staking-mint",
+        &staking_mint.to_string(),
+        "--pool-id",
+        "7",
+        "--reward-mint",
+        &reward_mint.to_string(),
+        "--user",
+        &user.to_string(),
+        "--output",
+        "json",
+    ]);
+    assert!(output.status.success());
+    let addresses: Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    let (pool, _) = find_pool_address(&staking_mint, 7);
+    let expected = [
+        ("pool", pool),
+        ("staking_vault", find_staking_vault_address(&pool).0),
+        ("receipt_mint", find_receipt_mint_address(&pool).0),
+        (
+            "reward_vault",
+            find_reward_vault_address(&pool, &reward_mint).0,
+        ),
+        (
+            "user_stake_info",
+            find_user_stake_info_address(&pool, &user).0,
+        ),
+    ];
+    for (field, address) in expected {
+        assert_eq!(addresses[field], address.to_string(), "{field}");
+    }
+}
+
+#[test]
+fn reports_unreadable_signers() {
+    let pool = Pubkey::new_unique().to_string();
+    let output = staking_cli(&[
+        "--keypair",
+        "/nonexistent/id.json",
+        "--pool",
+        &pool,
+        "pause",
+    ]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("failed to read keypair from /nonexistent/id.json"),
+        "{stderr}"
+    );
+
+    // 未启用 ledger feature 时，硬件钱包路径给出重新编译的提示
+    if !cfg!(feature = "ledger") {
+        let output = staking_cli(&["--keypair", "usb://ledger?key=0", "--pool", &pool, "pause"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("--features ledger"), "{stderr}");
+    }
+}
+
+// --help 说明当前构建是否支持硬件钱包
+#[test]
+fn help_states_hardware_wallet_support() {
+    let output = staking_cli(&["--help"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    if cfg!(feature = "ledger") {
+        assert!(stdout.contains("Hardware wallets: supported"), "{stdout}");
+    } else {
+        assert!(
+            stdout.contains("Hardware wallets: NOT supported by this build"),
+            "{stdout}"
+        );
+        assert!(stdout.contains("--features ledger"), "{stdout}");
+        assert!(!stdout.contains("usb://ledger[?key"), "{stdout}");
+    }
+}
@@ -0,0 +1,338 @@
+// 奖励累加器的纯算术，链上程序的 Pool::update_rewards、收据代币份额桶结算、加权质押量同步和质押批次记账
+// 与客户端的离线预测共用同一份实现，保证两边的结果逐位一致。
+// 不依赖 Anchor 或 Solana 运行时，溢出以 None 返回，由调用方转换为各自的错误类型
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+// 精度因子，10^12 提供足够精度，适配 u128 和 u64
+pub const PRECISION: u128 = 1_000_000_000_000;
+// 加速倍数的基准（基点），10000 表示 1x
+pub const BOOST_BASE_BPS: u16 = 10_000;
+// 加速锁定期上限：4 年，锁定满 4 年获得最大倍数
+pub const MAX_BOOST_LOCK: i64 = 4 * 365 * 24 * 60 * 60;
+
+// 一条奖励流在一次结算中的累积结果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Accrual {
+    pub rewards_accrued: u64,             // 本次累积的奖励，计入奖励流负债
+    pub reward_per_token_increment: u128, // 每单位加权质押的奖励指数增量（高精度）
+}
+
+// 奖励流从 last_update 到 now 的累积：奖励只累积到 period_finish，且不超过金库中未被占用的余额；
+// 无人质押时不累积
+pub fn accrue(
+    last_update: i64,
+    now: i64,
+    period_finish: i64,
+    reward_rate: u64,
+    unreserved_balance: u64,
+    total_weighted_stake: u64,
+) -> Option<Accrual> {
+    if total_weighted_stake == 0 {
+        return Some(Accrual::default());
+    }
+    // saturating_sub 防止时间回退导致负数
+    let time_elapsed = now
+        .min(period_finish)
+        .saturating_sub(last_update.min(period_finish)) as u128;
+    if time_elapsed == 0 {
+        return Some(Accrual::default());
+    }
+    // 总奖励 = 时间 * 奖励率，且不超过未占用余额，因此必然可转为 u64
+    let rewards_accrued = time_elapsed
+        .checked_mul(reward_rate as u128)?
+        .min(unreserved_balance as u128);
+    // 指数增量 = (总奖励 * 精度) / 总加权质押量
+    let reward_per_token_increment = rewards_accrued
+        .checked_mul(PRECISION)?
+        .checked_div(total_weighted_stake as u128)?;
+    Some(Accrual {
+        rewards_accrued: rewards_accrued as u64,
+        reward_per_token_increment,
+    })
+}
+
+// 按奖励指数差计算 weight 自上次同步以来应得的奖励：(权重 * 指数差) / 精度，溢出时返回 None
+pub fn earned(
+    weight: u64,
+    reward_per_token_stored: u128,
+    reward_per_token_paid: u128,
+) -> Option<u64> {
+    (weight as u128)
+        .checked_mul(reward_per_token_stored.saturating_sub(reward_per_token_paid))?
+        .checked_div(PRECISION)?
+        .try_into()
+        .ok()
+}
+
+// 用户在某条奖励流上的待领奖励，异常情况返回 0
+pub fn pending_rewards(
+    weighted_amount: u64,
+    reward_per_token_stored: u128,
+    reward_per_token_paid: u128,
+) -> u64 {
+    earned(
+        weighted_amount,
+        reward_per_token_stored,
+        reward_per_token_paid,
+    )
+    .unwrap_or(0)
+}
+
+// 质押批次在 now 时刻的加速倍数（基点）：1x + (最大倍数 - 1x) * 剩余锁定期 / 4 年，随剩余锁定期线性衰减至 1x
+pub fn boost_multiplier_bps(unlock_timestamp: i64, now: i64, max_boost_bps: u16) -> u128 {
+    let extra_bps = max_boost_bps.saturating_sub(BOOST_BASE_BPS) as u128;
+    let remaining = unlock_timestamp
+        .saturating_sub(now)
+        .clamp(0, MAX_BOOST_LOCK) as u128;
+    BOOST_BASE_BPS as u128 + extra_bps * remaining / MAX_BOOST_LOCK as u128
+}
+
+// 一组质押批次 (金额, 解锁时间) 在 now 时刻的加权质押量
+pub fn boosted_weight(
+    lots: impl IntoIterator<Item = (u64, i64)>,
+    now: i64,
+    max_boost_bps: u16,
+) -> Option<u64> {
+    let mut weight: u128 = 0;
+    for (amount, unlock_timestamp) in lots {
+        let multiplier_bps = boost_multiplier_bps(unlock_timestamp, now, max_boost_bps);
+        weight = weight
+            .checked_add((amount as u128).checked_mul(multiplier_bps)? / BOOST_BASE_BPS as u128)?;
+    }
+    weight.try_into().ok()
+}
+
+// 用 new_weight 替换总加权质押量中的 old_weight
+pub fn replace_weight(total_weighted_stake: u64, old_weight: u64, new_weight: u64) -> Option<u64> {
+    total_weighted_stake
+        .checked_sub(old_weight)?
+        .checked_add(new_weight)
+}
+
+// 按 now 时刻重新计算一组质押批次的加权质押量，并同步到总加权质押量
+pub fn sync_weight(
+    lots: impl IntoIterator<Item = (u64, i64)>,
+    now: i64,
+    max_boost_bps: u16,
+    weighted_amount: &mut u64,
+    total_weighted_stake: &mut u64,
+) -> Option<()> {
+    let new_weight = boosted_weight(lots, now, max_boost_bps)?;
+    *total_weighted_stake = replace_weight(*total_weighted_stake, *weighted_amount, new_weight)?;
+    *weighted_amount = new_weight;
+    Some(())
+}
+
+// 一条奖励流参与全局结算的账本，字段与 RewardStream 的同名字段对应
+pub struct RewardLedger<'a> {
+    pub period_finish: i64,
+    pub reward_rate: u64,
+    pub vault_balance: u64,
+    pub reward_per_token_stored: &'a mut u128,
+    pub total_rewards_owed: &'a mut u64,
+}
+
+// 将每条奖励流从 last_update 结算到 now：更新奖励指数并记入负债，
+// 单次累积不超过金库余额中未被已累积奖励占用的部分
+pub fn accrue_streams<'a>(
+    streams: impl IntoIterator<Item = RewardLedger<'a>>,
+    last_update: i64,
+    now: i64,
+    total_weighted_stake: u64,
+) -> Option<()> {
+    for stream in streams {
+        let accrual = accrue(
+            last_update,
+            now,
+            stream.period_finish,
+            stream.reward_rate,
+            stream
+                .vault_balance
+                .saturating_sub(*stream.total_rewards_owed),
+            total_weighted_stake,
+        )?;
+        *stream.reward_per_token_stored = stream
+            .reward_per_token_stored
+            .checked_add(accrual.reward_per_token_increment)?;
+        *stream.total_rewards_owed = stream
+            .total_rewards_owed
+            .checked_add(accrual.rewards_accrued)?;
+    }
+    Some(())
+}
+
+// 收据代币份额桶参与奖励分配的权重：自动复利时包含已累积的质押代币奖励
+pub fn liquid_weight(auto_compound: bool, principal: u64, rewards: u64) -> Option<u64> {
+    if auto_compound {
+        principal.checked_add(rewards)
+    } else {
+        Some(principal)
+    }
+}
+
+// 收据代币份额桶在一条奖励流上的结算账本
+pub struct LiquidRewardLedger<'a> {
+    pub reward_per_token_stored: u128,
+    pub compounds: bool, // 奖励代币与质押代币相同，奖励可计入份额价格
+    pub reward_per_token_paid: &'a mut u128,
+    pub total_rewards_owed: &'a mut u64,
+}
+
+// 结算份额桶的奖励：质押代币计价的奖励计入 liquid_rewards，
+// 其他奖励代币无法按份额价格分配，释放回奖励流的未占用余额；
+// 自动复利时新增奖励计入份额桶权重，并同步到总加权质押量
+pub fn settle_liquid_rewards<'a>(
+    streams: impl IntoIterator<Item = LiquidRewardLedger<'a>>,
+    auto_compound: bool,
+    liquid_principal: u64,
+    liquid_rewards: &mut u64,
+    total_weighted_stake: &mut u64,
+) -> Option<()> {
+    let old_weight = liquid_weight(auto_compound, liquid_principal, *liquid_rewards)?;
+    for stream in streams {
+        let pending = earned(
+            old_weight,
+            stream.reward_per_token_stored,
+            *stream.reward_per_token_paid,
+        )?;
+        *stream.reward_per_token_paid = stream.reward_per_token_stored;
+        if pending == 0 {
+            continue;
+        }
+        if stream.compounds {
+            *liquid_rewards = liquid_rewards.checked_add(pending)?;
+        } else {
+            *stream.total_rewards_owed = stream.total_rewards_owed.saturating_sub(pending);
+        }
+    }
+    let new_weight = liquid_weight(auto_compound, liquid_principal, *liquid_rewards)?;
+    *total_weighted_stake = replace_weight(*total_weighted_stake, old_weight, new_weight)?;
+    Some(())
+}
+
+// 单笔质押批次的纯数值表示，链上程序和客户端的 StakeLot 与之互相转换
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lot {
+    pub amount: u64,           // 批次剩余金额
+    pub unlock_timestamp: i64, // 批次解锁时间戳
+    pub lock_duration: i64,    // 批次存入时的锁定期（秒）
+}
+
+// 质押批次记账失败的原因，由调用方转换为各自的错误类型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LotError {
+    ArithmeticOverflow,   // 算术溢出
+    TooManyStakeLots,     // 质押批次数量已达上限
+    LockupPeriodNotEnded, // 已解锁的金额不足
+}
+
+// 一组质押批次 (金额, 解锁时间) 在 now 时刻仍处于锁定期的金额
+pub fn locked_amount(lots: impl IntoIterator<Item = (u64, i64)>, now: i64) -> u64 {
+    lots.into_iter()
+        .filter(|&(_, unlock_timestamp)| unlock_timestamp > now)
+        .map(|(amount, _)| amount)
+        .sum()
+}
+
+// 记录一笔新的质押批次，批次数量不超过 max_lots。已解锁的批次倍数均为 1x 且不再计罚金，
+// 先合并为一个批次，避免批次数量只增不减；新批次已解锁时并入该批次，否则与解锁时间相同的末尾批次合并
+pub fn add_lot<L: Copy + From<Lot> + Into<Lot>>(
+    lots: &mut Vec<L>,
+    lot: Lot,
+    max_lots: usize,
+    now: i64,
+) -> Result<(), LotError> {
+    let unlocked = compact_unlocked_lots(lots, now)?;
+    if let Some(index) = unlocked.filter(|_| lot.unlock_timestamp <= now) {
+        return merge_lot(&mut lots[index], lot);
+    }
+    if let Some(last) = lots.last_mut() {
+        if (*last).into().unlock_timestamp == lot.unlock_timestamp {
+            return merge_lot(last, lot);
+        }
+    }
+    if lots.len() >= max_lots {
+        return Err(LotError::TooManyStakeLots);
+    }
+    lots.push(lot.into());
+    Ok(())
+}
+
+// 将 lot 并入 slot：金额相加，解锁时间和锁定期取较大值
+fn merge_lot<L: Copy + From<Lot> + Into<Lot>>(slot: &mut L, lot: Lot) -> Result<(), LotError> {
+    let mut merged: Lot = (*slot).into();
+    merged.amount = merged
+        .amount
+        .checked_add(lot.amount)
+        .ok_or(LotError::ArithmeticOverflow)?;
+    merged.unlock_timestamp = merged.unlock_timestamp.max(lot.unlock_timestamp);
+    merged.lock_duration = merged.lock_duration.max(lot.lock_duration);
+    *slot = merged.into();
+    Ok(())
+}
+
+// 把 now 时刻所有已解锁的批次合并到第一个已解锁批次的位置，返回该位置
+fn compact_unlocked_lots<L: Copy + From<Lot> + Into<Lot>>(
+    lots: &mut Vec<L>,
+    now: i64,
+) -> Result<Option<usize>, LotError> {
+    let Some(first) = lots
+        .iter()
+        .position(|&lot| lot.into().unlock_timestamp <= now)
+    else {
+        return Ok(None);
+    };
+    let mut index = first + 1;
+    while index < lots.len() {
+        let lot: Lot = lots[index].into();
+        if lot.unlock_timestamp <= now {
+            merge_lot(&mut lots[first], lot)?;
+            lots.remove(index);
+        } else {
+            index += 1;
+        }
+    }
+    Ok(Some(first))
+}
+
+// 按先进先出顺序从已解锁批次中扣减 amount，返回扣减后仍锁定的金额；
+// stake_amount 为批次金额之和
+pub fn consume_unlocked_lots<L: Copy + From<Lot> + Into<Lot>>(
+    lots: &mut Vec<L>,
+    stake_amount: u64,
+    amount: u64,
+    now: i64,
+) -> Result<u64, LotError> {
+    let locked = locked_amount(
+        lots.iter().map(|&lot| {
+            let lot: Lot = lot.into();
+            (lot.amount, lot.unlock_timestamp)
+        }),
+        now,
+    );
+    if stake_amount.saturating_sub(locked) < amount {
+        return Err(LotError::LockupPeriodNotEnded);
+    }
+
+    let mut remaining = amount;
+    for slot in lots.iter_mut() {
+        if remaining == 0 {
+            break;
+        }
+        let mut lot: Lot = (*slot).into();
+        if lot.unlock_timestamp > now {
+            continue; // 跳过仍在锁定期的批次
+        }
+        let taken = lot.amount.min(remaining);
+        lot.amount -= taken;
+        remaining -= taken;
+        *slot = lot.into();
+    }
+    lots.retain(|&lot| Into::<Lot>::into(lot).amount > 0); // 移除已清空的批次
+    Ok(locked)
+}
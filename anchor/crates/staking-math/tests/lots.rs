@@ -0,0 +1,97 @@
+// 质押批次记账：合并、数量上限与已解锁批次的压缩
+
+use staking_math::{add_lot, consume_unlocked_lots, Lot, LotError};
+
+const NOW: i64 = 1_700_000_000;
+const DAY: i64 = 24 * 60 * 60;
+const MAX_LOTS: usize = 4;
+
+fn lot(amount: u64, unlock_timestamp: i64, lock_duration: i64) -> Lot {
+    Lot {
+        amount,
+        unlock_timestamp,
+        lock_duration,
+    }
+}
+
+#[test]
+fn merges_lots_with_same_unlock_timestamp() {
+    let mut lots: Vec<Lot> = Vec::new();
+    add_lot(&mut lots, lot(100, NOW + 30 * DAY, 30 * DAY), MAX_LOTS, NOW).unwrap();
+    add_lot(&mut lots, lot(50, NOW + 30 * DAY, 30 * DAY), MAX_LOTS, NOW).unwrap();
+    assert_eq!(lots, vec![lot(150, NOW + 30 * DAY, 30 * DAY)]);
+
+    // 解锁时间不同的批次单独记录
+    add_lot(&mut lots, lot(10, NOW + 60 * DAY, 60 * DAY), MAX_LOTS, NOW).unwrap();
+    assert_eq!(lots.len(), 2);
+}
+
+#[test]
+fn rejects_locked_lots_beyond_cap() {
+    let mut lots: Vec<Lot> = Vec::new();
+    for i in 1..=MAX_LOTS as i64 {
+        add_lot(&mut lots, lot(10, NOW + i * DAY, i * DAY), MAX_LOTS, NOW).unwrap();
+    }
+    assert_eq!(
+        add_lot(&mut lots, lot(10, NOW + 10 * DAY, 10 * DAY), MAX_LOTS, NOW),
+        Err(LotError::TooManyStakeLots)
+    );
+    assert_eq!(lots.len(), MAX_LOTS);
+
+    // 与末尾批次解锁时间相同的存入仍可合并
+    add_lot(
+        &mut lots,
+        lot(5, NOW + MAX_LOTS as i64 * DAY, 0),
+        MAX_LOTS,
+        NOW,
+    )
+    .unwrap();
+    assert_eq!(lots[MAX_LOTS - 1].amount, 15);
+}
+
+#[test]
+fn unlocked_deposits_never_grow_lot_count() {
+    let mut lots: Vec<Lot> = Vec::new();
+    add_lot(&mut lots, lot(100, NOW + 30 * DAY, 30 * DAY), MAX_LOTS, NOW).unwrap();
+    // 复投等已解锁的存入每个时刻各记一笔，全部并入同一个已解锁批次
+    for i in 0..(MAX_LOTS as i64 * 4) {
+        let now = NOW + i;
+        add_lot(&mut lots, lot(1, now, 0), MAX_LOTS, now).unwrap();
+    }
+    assert_eq!(
+        lots,
+        vec![
+            lot(100, NOW + 30 * DAY, 30 * DAY),
+            lot(MAX_LOTS as u64 * 4, NOW + MAX_LOTS as i64 * 4 - 1, 0),
+        ]
+    );
+}
+
+#[test]
+fn compacts_unlocked_lots_before_cap_check() {
+    let mut lots: Vec<Lot> = Vec::new();
+    for i in 1..=MAX_LOTS as i64 {
+        add_lot(&mut lots, lot(10, NOW + i * DAY, i * DAY), MAX_LOTS, NOW).unwrap();
+    }
+
+    // 前三个批次到期后合并为一个，腾出位置记录新的锁定批次
+    let now = NOW + 3 * DAY;
+    add_lot(&mut lots, lot(20, now + 7 * DAY, 7 * DAY), MAX_LOTS, now).unwrap();
+    assert_eq!(
+        lots,
+        vec![
+            lot(30, NOW + 3 * DAY, 3 * DAY),
+            lot(10, NOW + 4 * DAY, 4 * DAY),
+            lot(20, now + 7 * DAY, 7 * DAY),
+        ]
+    );
+
+    // 压缩不改变可提取金额
+    let locked = consume_unlocked_lots(&mut lots, 60, 30, now).unwrap();
+    assert_eq!(locked, 30);
+    assert_eq!(lots.len(), 2);
+    assert_eq!(
+        consume_unlocked_lots(&mut lots, 30, 1, now),
+        Err(LotError::LockupPeriodNotEnded)
+    );
+}
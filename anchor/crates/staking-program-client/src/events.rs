@@ -0,0 +1,478 @@
+// 程序事件的镜像类型，以及从交易日志中解析事件。
+// Anchor 的 emit! 以 `Program data: <base64>` 输出 8 字节事件判别符加 Borsh 编码的事件
+
+use crate::state::{ParameterBounds, PenaltyDestination, Role, TimelockAction};
+use crate::{ClientError, ID};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct InitializePoolEvent {
+    pub pool: Pubkey,                 // 池子地址
+    pub pool_id: u64,                 // 池子编号
+    pub staking_mint: Pubkey,         // 质押代币 Mint 地址
+    pub reward_mint: Pubkey,          // 奖励代币 Mint 地址
+    pub staking_mint_extensions: u16, // 质押代币已接受的扩展
+    pub reward_mint_extensions: u16,  // 奖励代币已接受的扩展
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct StakeEvent {
+    pub pool: Pubkey,          // 池子地址
+    pub user: Pubkey,          // 质押用户公钥
+    pub amount: u64,           // 转出的质押金额
+    pub net_amount: u64,       // 扣除转账手续费后实际记入的质押金额
+    pub unlock_timestamp: i64, // 本批次解锁时间戳
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UnstakeEvent {
+    pub pool: Pubkey,          // 池子地址
+    pub user: Pubkey,          // 取消质押用户公钥
+    pub amount: u64,           // 取消质押金额
+    pub net_amount: u64,       // 扣除转账手续费后用户实际收到的金额
+    pub remaining_locked: u64, // 仍处于锁定期的质押金额
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct ClaimEvent {
+    pub pool: Pubkey,        // 池子地址
+    pub user: Pubkey,        // 领取奖励用户公钥
+    pub reward_mint: Pubkey, // 奖励代币 Mint 地址
+    pub amount: u64,         // 领取奖励金额
+    pub net_amount: u64,     // 扣除转账手续费后用户实际收到的金额
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UnstakeRequestedEvent {
+    pub pool: Pubkey,      // 池子地址
+    pub user: Pubkey,      // 用户公钥
+    pub amount: u64,       // 请求提取的金额
+    pub available_at: i64, // 可提取时间戳
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UnstakeRequestCancelledEvent {
+    pub pool: Pubkey, // 池子地址
+    pub user: Pubkey, // 用户公钥
+    pub amount: u64,  // 重新计入质押的金额
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UnstakedWithdrawnEvent {
+    pub pool: Pubkey,    // 池子地址
+    pub user: Pubkey,    // 用户公钥
+    pub amount: u64,     // 提取金额
+    pub net_amount: u64, // 扣除转账手续费后用户实际收到的金额
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PokeEvent {
+    pub pool: Pubkey,         // 池子地址
+    pub user: Pubkey,         // 被重新加权的用户公钥
+    pub weighted_amount: u64, // 新的加权质押量
+}
+
+// 复利事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct CompoundEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+}
+
+// 设置自动复利事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct SetAutoCompoundEvent {
+    pub pool: Pubkey,
+    pub enabled: bool,
+}
+
+// 提前取消质押事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct EarlyUnstakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub penalty: u64,
+}
+
+// 罚金销毁事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PenaltyBurnedEvent {
+    pub pool: Pubkey,
+    pub amount: u64,
+}
+
+// 罚金转入国库事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PenaltyToTreasuryEvent {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+}
+
+// 罚金分配给剩余质押者事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PenaltyRedistributedEvent {
+    pub pool: Pubkey,
+    pub amount: u64,
+}
+
+// 更新提前取消质押罚金事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateEarlyUnstakePenaltyEvent {
+    pub pool: Pubkey,
+    pub penalty_bps: u16,
+    pub scale_with_lock: bool,
+}
+
+// 更新罚金去向事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdatePenaltyDestinationEvent {
+    pub pool: Pubkey,
+    pub destination: PenaltyDestination,
+    pub treasury: Pubkey,
+}
+
+// 开启收据代币事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct EnableReceiptTokenEvent {
+    pub pool: Pubkey,
+    pub receipt_mint: Pubkey,
+}
+
+// 流动性质押事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct LiquidStakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub shares: u64,
+}
+
+// 流动性取消质押事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct LiquidUnstakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub rewards: u64,
+    pub net_rewards: u64,
+}
+
+// 奖励率更新事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateRewardRateEvent {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub new_rate: u64,
+}
+
+// 注资事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct FundRewardsEvent {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+}
+
+// 新增奖励流事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct AddRewardStreamEvent {
+    pub pool: Pubkey,
+    pub reward_index: u8,
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub mint_extensions: u16,
+}
+
+// 开启奖励周期事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct NotifyRewardAmountEvent {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub duration: i64,
+    pub reward_rate: u64,
+    pub period_finish: i64,
+}
+
+// 退役奖励流事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct RetireRewardStreamEvent {
+    pub pool: Pubkey,
+    pub reward_index: u8,
+    pub reward_mint: Pubkey,
+}
+
+// 更新最大加速倍数事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateMaxBoostEvent {
+    pub pool: Pubkey,
+    pub max_boost_bps: u16,
+}
+
+// 更新冷却期事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateCooldownDurationEvent {
+    pub pool: Pubkey,
+    pub new_duration: i64,
+}
+
+// 更新偿付能力检查期限事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateSolvencyHorizonEvent {
+    pub pool: Pubkey,
+    pub new_horizon: i64,
+}
+
+// 提名管理员事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct AdminTransferProposedEvent {
+    pub pool: Pubkey,
+    pub current_admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+// 接受管理员事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct AdminTransferAcceptedEvent {
+    pub pool: Pubkey,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+// 撤销管理员提名事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct AdminTransferCancelledEvent {
+    pub pool: Pubkey,
+    pub cancelled_admin: Pubkey,
+}
+
+// 暂停事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct PauseEvent {
+    pub pool: Pubkey,
+}
+
+// 恢复事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UnpauseEvent {
+    pub pool: Pubkey,
+}
+
+// 更新锁定期事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateLockupDurationEvent {
+    pub pool: Pubkey,
+    pub new_duration: i64,
+}
+
+// 授予角色事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct RoleGrantedEvent {
+    pub pool: Pubkey,
+    pub role: Role,
+    pub account: Pubkey,
+    pub previous: Pubkey,
+}
+
+// 撤销角色事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct RoleRevokedEvent {
+    pub pool: Pubkey,
+    pub role: Role,
+    pub account: Pubkey,
+}
+
+// 更新时间锁延迟事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateTimelockDelayEvent {
+    pub pool: Pubkey,
+    pub new_delay: i64,
+}
+
+// 排队变更事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct ChangeScheduledEvent {
+    pub pool: Pubkey,
+    pub change_id: u64,
+    pub action: TimelockAction,
+    pub eta: i64,
+}
+
+// 执行变更事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct ChangeExecutedEvent {
+    pub pool: Pubkey,
+    pub change_id: u64,
+    pub action: TimelockAction,
+}
+
+// 撤销变更事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct ChangeCancelledEvent {
+    pub pool: Pubkey,
+    pub change_id: u64,
+    pub action: TimelockAction,
+}
+
+// 更新参数边界事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateParameterBoundsEvent {
+    pub pool: Pubkey,
+    pub bounds: ParameterBounds,
+}
+
+// 紧急提取质押代币并关停事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct ShutdownEvent {
+    pub pool: Pubkey,
+    pub guardian: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub remaining_balance: u64,
+    pub outstanding_claims: u64,
+}
+
+// 关停后紧急退出事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct EmergencyExitEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub claim: u64,
+    pub amount: u64,
+    pub net_amount: u64,
+}
+
+// 暂停期间紧急取消质押事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct EmergencyUnstakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub forfeit_rewards: bool,
+}
+
+// 更新最长暂停时间事件
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct UpdateMaxPauseDurationEvent {
+    pub pool: Pubkey,
+    pub new_duration: i64,
+}
+
+// 为每个事件生成判别符常量，并汇总为 StakingEvent
+macro_rules! staking_events {
+    ($($variant:ident($event:ident) = $discriminator:expr,)*) => {
+        $(
+            impl $event {
+                pub const DISCRIMINATOR: [u8; 8] = $discriminator;
+            }
+        )*
+
+        // 程序触发的全部事件
+        #[derive(Clone, PartialEq, Debug)]
+        pub enum StakingEvent {
+            $($variant($event),)*
+        }
+
+        impl StakingEvent {
+            // 解码一条事件数据（判别符加 Borsh 编码），判别符未知时返回 None
+            pub fn decode(data: &[u8]) -> Result<Option<Self>, ClientError> {
+                let Some((discriminator, mut body)) = data.split_first_chunk::<8>() else {
+                    return Ok(None);
+                };
+                $(
+                    if *discriminator == $event::DISCRIMINATOR {
+                        return Ok(Some(StakingEvent::$variant($event::deserialize(&mut body)?)));
+                    }
+                )*
+                Ok(None)
+            }
+        }
+    };
+}
+
+staking_events! {
+    InitializePool(InitializePoolEvent) = [114, 34, 145, 114, 237, 44, 216, 235],
+    Stake(StakeEvent) = [226, 134, 188, 173, 19, 33, 75, 175],
+    Unstake(UnstakeEvent) = [162, 104, 137, 228, 81, 3, 79, 197],
+    Claim(ClaimEvent) = [93, 15, 70, 170, 48, 140, 212, 219],
+    UnstakeRequested(UnstakeRequestedEvent) = [168, 232, 211, 86, 207, 240, 252, 16],
+    UnstakeRequestCancelled(UnstakeRequestCancelledEvent) = [145, 253, 7, 87, 188, 112, 69, 174],
+    UnstakedWithdrawn(UnstakedWithdrawnEvent) = [240, 239, 7, 90, 142, 156, 89, 166],
+    Poke(PokeEvent) = [7, 174, 157, 14, 206, 4, 184, 155],
+    Compound(CompoundEvent) = [23, 126, 132, 123, 205, 16, 57, 20],
+    SetAutoCompound(SetAutoCompoundEvent) = [219, 253, 247, 13, 115, 170, 83, 39],
+    EarlyUnstake(EarlyUnstakeEvent) = [164, 30, 47, 111, 182, 167, 138, 243],
+    PenaltyBurned(PenaltyBurnedEvent) = [12, 55, 67, 188, 98, 126, 6, 120],
+    PenaltyToTreasury(PenaltyToTreasuryEvent) = [125, 125, 228, 187, 190, 162, 31, 43],
+    PenaltyRedistributed(PenaltyRedistributedEvent) = [4, 99, 242, 30, 167, 205, 200, 36],
+    UpdateEarlyUnstakePenalty(UpdateEarlyUnstakePenaltyEvent) = [160, 51, 59, 160, 79, 12, 184, 0],
+    UpdatePenaltyDestination(UpdatePenaltyDestinationEvent) = [23, 131, 146, 237, 6, 251, 226, 52],
+    EnableReceiptToken(EnableReceiptTokenEvent) = [215, 169, 50, 101, 118, 249, 125, 54],
+    LiquidStake(LiquidStakeEvent) = [93, 246, 139, 108, 106, 236, 111, 61],
+    LiquidUnstake(LiquidUnstakeEvent) = [173, 5, 147, 15, 5, 14, 194, 116],
+    UpdateRewardRate(UpdateRewardRateEvent) = [205, 45, 190, 210, 23, 74, 0, 53],
+    FundRewards(FundRewardsEvent) = [201, 122, 109, 250, 235, 114, 171, 27],
+    AddRewardStream(AddRewardStreamEvent) = [50, 67, 252, 195, 162, 97, 184, 116],
+    NotifyRewardAmount(NotifyRewardAmountEvent) = [212, 37, 49, 203, 81, 236, 204, 11],
+    RetireRewardStream(RetireRewardStreamEvent) = [31, 211, 16, 99, 128, 179, 57, 68],
+    UpdateMaxBoost(UpdateMaxBoostEvent) = [253, 227, 243, 173, 17, 220, 203, 197],
+    UpdateCooldownDuration(UpdateCooldownDurationEvent) = [99, 241, 148, 202, 148, 104, 47, 197],
+    UpdateSolvencyHorizon(UpdateSolvencyHorizonEvent) = [172, 201, 62, 116, 162, 229, 115, 249],
+    AdminTransferProposed(AdminTransferProposedEvent) = [71, 85, 100, 58, 178, 123, 116, 206],
+    AdminTransferAccepted(AdminTransferAcceptedEvent) = [126, 246, 18, 138, 98, 63, 176, 92],
+    AdminTransferCancelled(AdminTransferCancelledEvent) = [175, 140, 104, 221, 194, 183, 79, 71],
+    Pause(PauseEvent) = [32, 51, 61, 169, 156, 104, 130, 43],
+    Unpause(UnpauseEvent) = [134, 156, 8, 215, 185, 128, 192, 217],
+    UpdateLockupDuration(UpdateLockupDurationEvent) = [27, 195, 229, 136, 143, 246, 53, 50],
+    RoleGranted(RoleGrantedEvent) = [221, 21, 92, 108, 136, 12, 105, 112],
+    RoleRevoked(RoleRevokedEvent) = [104, 105, 52, 114, 39, 94, 217, 251],
+    UpdateTimelockDelay(UpdateTimelockDelayEvent) = [30, 166, 240, 131, 129, 14, 66, 180],
+    ChangeScheduled(ChangeScheduledEvent) = [0, 111, 236, 177, 82, 66, 79, 150],
+    ChangeExecuted(ChangeExecutedEvent) = [210, 68, 107, 108, 205, 125, 171, 37],
+    ChangeCancelled(ChangeCancelledEvent) = [204, 167, 210, 132, 250, 90, 112, 81],
+    UpdateParameterBounds(UpdateParameterBoundsEvent) = [242, 58, 55, 17, 57, 21, 72, 252],
+    Shutdown(ShutdownEvent) = [26, 105, 92, 121, 24, 214, 1, 198],
+    EmergencyExit(EmergencyExitEvent) = [126, 12, 221, 148, 178, 115, 125, 228],
+    EmergencyUnstake(EmergencyUnstakeEvent) = [7, 90, 36, 159, 199, 94, 190, 125],
+    UpdateMaxPauseDuration(UpdateMaxPauseDurationEvent) = [94, 189, 32, 30, 186, 23, 143, 237],
+}
+
+// 从交易日志中按顺序解析本程序触发的事件。通过 `invoke [n]` 和 `success`/`failed`
+// 日志跟踪调用栈，只解析本程序处于栈顶时输出的数据，忽略 CPI 调用的其他程序
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<StakingEvent>, ClientError> {
+    let program_id = ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+    for log in logs {
+        let Some(rest) = log.as_ref().strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                if let Some(event) = StakingEvent::decode(&STANDARD.decode(data)?)? {
+                    events.push(event);
+                }
+            }
+        } else if let Some((id, status)) = rest.split_once(' ') {
+            if status.starts_with("invoke [") {
+                stack.push(id);
+            } else if status == "success" || status.starts_with("failed") {
+                stack.pop();
+            }
+        }
+    }
+    Ok(events)
+}
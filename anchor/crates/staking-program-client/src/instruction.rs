@@ -0,0 +1,479 @@
+// 每条指令的账户结构和构建函数。账户顺序、可写和签名标记与 staking_program 的 Accounts 结构一致，
+// 可选账户传 None 时按 Anchor 约定以程序 ID 占位；转账钩子需要的额外账户可追加到返回指令的 accounts 末尾
+
+use crate::state::{ParameterBounds, PenaltyDestination, Pool, Role, TimelockAction};
+use crate::ID;
+use borsh::BorshSerialize;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_program::pubkey::Pubkey;
+
+// 指令账户转换为 AccountMeta
+trait ToAccountMeta {
+    fn to_account_meta(&self, is_writable: bool, is_signer: bool) -> AccountMeta;
+}
+
+impl ToAccountMeta for Pubkey {
+    fn to_account_meta(&self, is_writable: bool, is_signer: bool) -> AccountMeta {
+        AccountMeta {
+            pubkey: *self,
+            is_writable,
+            is_signer,
+        }
+    }
+}
+
+impl ToAccountMeta for Option<Pubkey> {
+    fn to_account_meta(&self, is_writable: bool, is_signer: bool) -> AccountMeta {
+        match self {
+            Some(key) => key.to_account_meta(is_writable, is_signer),
+            None => AccountMeta::new_readonly(ID, false), // 缺省的可选账户以程序 ID 占位
+        }
+    }
+}
+
+macro_rules! is_writable {
+    (mut $($rest:tt)*) => {
+        true
+    };
+    ($($rest:tt)*) => {
+        false
+    };
+}
+
+macro_rules! is_signer {
+    ($(mut)? signer) => {
+        true
+    };
+    ($($rest:tt)*) => {
+        false
+    };
+}
+
+// 为每条指令生成账户结构（名称为指令名的驼峰形式加 Accounts）和构建函数，
+// 指令数据为 8 字节判别符加按参数顺序 Borsh 编码的参数
+macro_rules! instructions {
+    ($(
+        $name:ident($accounts:ident) = $discriminator:tt {
+            $($account:ident: $account_ty:ty [$($flag:tt)*],)*
+        } ($($arg:ident: $arg_ty:ty),*);
+    )*) => {
+        $(
+            #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+            pub struct $accounts {
+                $(pub $account: $account_ty,)*
+            }
+
+            impl $accounts {
+                pub fn to_account_metas(&self) -> Vec<AccountMeta> {
+                    vec![$(self.$account.to_account_meta(is_writable!($($flag)*), is_signer!($($flag)*)),)*]
+                }
+            }
+
+            pub fn $name(accounts: &$accounts, $($arg: $arg_ty),*) -> Instruction {
+                #[allow(unused_mut)]
+                let mut data = $discriminator.to_vec();
+                $($arg.serialize(&mut data).expect("writing to a Vec cannot fail");)*
+                Instruction {
+                    program_id: ID,
+                    accounts: accounts.to_account_metas(),
+                    data,
+                }
+            }
+        )*
+    };
+}
+
+instructions! {
+    // 初始化质押池，每个 (质押代币, pool_id) 组合只能初始化一次，设置池子参数；
+    // allowed_mint_extensions 为管理员明确接受的危险 Mint 扩展（MINT_EXT_* 位掩码）
+    initialize(InitializeAccounts) = [175, 175, 109, 31, 13, 152, 155, 237] {
+        pool: Pubkey [mut],
+        admin: Pubkey [mut signer],
+        staking_mint: Pubkey [],
+        staking_vault: Pubkey [mut],
+        reward_mint: Pubkey [],
+        reward_vault: Pubkey [mut],
+        system_program: Pubkey [],
+        token_program: Pubkey [],
+        reward_token_program: Pubkey [],
+        rent: Pubkey [],
+    } (pool_id: u64, lockup_duration: i64, allowed_mint_extensions: u16);
+
+    // 用户质押代币，将代币转入金库并更新状态；lock_duration 为可选的加速锁定期（0 表示不加速）
+    stake(StakeAccounts) = [206, 176, 202, 18, 200, 209, 179, 108] {
+        user: Pubkey [mut signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        system_program: Pubkey [],
+        token_program: Pubkey [],
+    } (amount: u64, lock_duration: i64);
+
+    // 用户取消质押，按先进先出顺序仅提取已解锁的质押批次
+    unstake(UnstakeAccounts) = [90, 95, 107, 42, 205, 124, 50, 225] {
+        user: Pubkey [mut signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        token_program: Pubkey [],
+    } (amount: u64);
+
+    // 用户提前取消质押：先提取已解锁批次，不足部分从最早解锁的锁定批次中扣减并支付罚金，
+    // 罚金按池子配置销毁、转入国库或分配给剩余质押者
+    unstake_early(UnstakeEarlyAccounts) = [246, 212, 81, 180, 65, 2, 126, 125] {
+        user: Pubkey [mut signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [mut],
+        treasury: Option<Pubkey> [mut],
+        reward_vault: Option<Pubkey> [mut],
+        token_program: Pubkey [],
+    } (amount: u64);
+
+    // 用户发起取消质押请求：金额立即停止计息，冷却期结束后可提取
+    request_unstake(RequestUnstakeAccounts) = [44, 154, 110, 253, 160, 202, 54, 34] {
+        user: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+    } (amount: u64);
+
+    // 用户撤销冷却中的取消质押请求，金额重新计入质押并恢复计息
+    cancel_unstake_request(CancelUnstakeRequestAccounts) = [146, 92, 252, 229, 122, 129, 37, 141] {
+        user: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+    } (request_index: u8);
+
+    // 用户提取所有冷却期已结束的取消质押请求
+    withdraw_unstaked(WithdrawUnstakedAccounts) = [19, 202, 68, 255, 216, 40, 205, 61] {
+        user: Pubkey [mut signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        token_program: Pubkey [],
+    } ();
+
+    // 任何人可调用：按当前时间重新计算某个用户的加权质押量，使已衰减或到期的加速锁定及时生效
+    poke(PokeAccounts) = [46, 24, 16, 107, 212, 9, 17, 5] {
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+    } ();
+
+    // 只读视图：返回用户按当前时间结算后的各奖励流待领奖励，结果与立即调用 claim_rewards 的金额一致；
+    // 返回值由 Anchor 以 Borsh 编码写入 set_return_data，客户端通过模拟交易读取，不修改任何账户
+    get_pending_rewards(GetPendingRewardsAccounts) = [7, 168, 204, 27, 53, 141, 151, 162] {
+        pool: Pubkey [],
+        user_stake_info: Pubkey [],
+    } (user: Pubkey);
+
+    // 只读视图：返回用户仓位，包括锁定与解锁金额、解锁时间、当前加权质押量、冷却中的请求和待领奖励
+    get_user_position(GetUserPositionAccounts) = [134, 165, 10, 74, 46, 176, 252, 181] {
+        pool: Pubkey [],
+        user_stake_info: Pubkey [],
+    } (user: Pubkey);
+
+    // 只读视图：返回池子按当前时间结算后的总量、金库余额和各奖励流的年化收益率计算输入；
+    // 需要按槽位顺序追加已配置奖励流的金库（只读），见 reward_vault_metas
+    get_pool_state(GetPoolStateAccounts) = [101, 112, 27, 99, 82, 23, 72, 17] {
+        pool: Pubkey [],
+        staking_vault: Pubkey [],
+    } ();
+
+    // 用户以流动性方式质押：代币计入池子的份额桶，按份额价格铸造收据代币（xTOKEN）给用户
+    stake_liquid(StakeLiquidAccounts) = [212, 161, 148, 67, 2, 41, 43, 254] {
+        user: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        user_receipt_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        receipt_mint: Pubkey [mut],
+        token_program: Pubkey [],
+    } (amount: u64);
+
+    // 收据代币持有人销毁份额，按份额价格取回本金及份额桶累积的质押代币奖励；
+    // 池子关停后不受暂停和冷却期限制，本金按关停时的剩余比例折算
+    unstake_liquid(UnstakeLiquidAccounts) = [22, 243, 185, 165, 91, 8, 114, 206] {
+        user: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        user_receipt_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        receipt_mint: Pubkey [mut],
+        reward_vault: Option<Pubkey> [mut],
+        token_program: Pubkey [],
+    } (shares: u64);
+
+    // 将用户在质押代币奖励流中累积的奖励直接转入质押金库并计入质押量，不影响已有批次的锁定期；
+    // 池子开启自动复利时任何人都可以为用户调用
+    compound(CompoundAccounts) = [165, 208, 251, 78, 242, 160, 141, 47] {
+        caller: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        reward_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        token_program: Pubkey [],
+    } ();
+
+    // 用户领取指定奖励流中累积的奖励代币
+    claim_rewards(ClaimRewardsAccounts) = [4, 144, 132, 71, 116, 23, 151, 80] {
+        user: Pubkey [mut signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_reward_wallet: Pubkey [mut],
+        reward_vault: Pubkey [mut],
+        reward_mint: Pubkey [],
+        token_program: Pubkey [],
+    } (reward_index: u8);
+
+    // 关闭用户质押信息账户，回收租金
+    close_user_stake_info(CloseUserStakeInfoAccounts) = [158, 181, 54, 43, 106, 197, 50, 109] {
+        user: Pubkey [mut signer],
+        pool: Pubkey [],
+        user_stake_info: Pubkey [mut],
+    } ();
+
+    // 奖励率管理者更新指定奖励流在当前奖励周期剩余时间内的奖励率
+    update_reward_rate(UpdateRewardRateAccounts) = [105, 157, 0, 185, 21, 144, 163, 159] {
+        pool: Pubkey [mut],
+        rate_manager: Pubkey [signer],
+    } (reward_index: u8, new_rate: u64);
+
+    // 注资者从任意钱包向指定奖励流的金库注资
+    fund_rewards(FundRewardsAccounts) = [114, 64, 163, 112, 175, 167, 19, 121] {
+        pool: Pubkey [mut],
+        funder: Pubkey [signer],
+        funder_wallet: Pubkey [mut],
+        reward_vault: Pubkey [mut],
+        reward_mint: Pubkey [],
+        token_program: Pubkey [],
+    } (reward_index: u8, amount: u64);
+
+    // 注资并开启新的奖励周期：reward_rate = (实际到账金额 + 上一周期剩余奖励) / duration
+    notify_reward_amount(NotifyRewardAmountAccounts) = [228, 154, 113, 244, 155, 76, 153, 136] {
+        pool: Pubkey [mut],
+        funder: Pubkey [signer],
+        funder_wallet: Pubkey [mut],
+        reward_vault: Pubkey [mut],
+        reward_mint: Pubkey [],
+        token_program: Pubkey [],
+    } (reward_index: u8, amount: u64, duration: i64);
+
+    // 管理员将角色授予指定地址，覆盖该角色原持有者；管理员本身始终拥有全部角色
+    grant_role(GrantRoleAccounts) = [218, 234, 128, 15, 82, 33, 236, 253] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (role: Role, account: Pubkey);
+
+    // 管理员撤销角色，撤销后该角色仅由管理员持有
+    revoke_role(RevokeRoleAccounts) = [179, 232, 2, 180, 48, 227, 82, 7] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (role: Role);
+
+    // 管理员提名新管理员，需由新管理员调用 accept_admin 签名确认后才生效
+    propose_admin(ProposeAdminAccounts) = [121, 214, 199, 212, 87, 39, 117, 234] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (new_admin: Pubkey);
+
+    // 被提名的新管理员签名接受管理员权限
+    accept_admin(AcceptAdminAccounts) = [112, 42, 45, 90, 116, 181, 13, 170] {
+        pool: Pubkey [mut],
+        new_admin: Pubkey [signer],
+    } ();
+
+    // 管理员撤销尚未被接受的管理员提名
+    cancel_admin_transfer(CancelAdminTransferAccounts) = [38, 131, 157, 31, 240, 137, 44, 215] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } ();
+
+    // 守护者紧急提取质押代币，池子随之进入不可逆的关停状态：
+    // 奖励停止累积，用户通过 emergency_exit 按比例取回金库剩余的质押代币
+    emergency_withdraw_staked_tokens(EmergencyWithdrawStakedTokensAccounts) = [250, 227, 48, 38, 183, 181, 26, 89] {
+        pool: Pubkey [mut],
+        guardian: Pubkey [signer],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        destination_wallet: Pubkey [mut],
+        token_program: Pubkey [],
+    } (amount: u64);
+
+    // 池子关停后，用户取回质押本金（含冷却中的请求）按比例对应的剩余代币；
+    // 关停前累积的奖励保留在用户账户中，仍可通过 claim_rewards 领取。不受暂停限制
+    emergency_exit(EmergencyExitAccounts) = [164, 174, 48, 163, 191, 65, 91, 245] {
+        user: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        token_program: Pubkey [],
+    } ();
+
+    // 池子暂停期间，用户无视锁定期和冷却期取回全部质押本金（含冷却中的请求）。
+    // forfeit_rewards 为 true 时放弃未领取的奖励且不执行奖励结算，即使奖励计算出错也能退出
+    emergency_unstake(EmergencyUnstakeAccounts) = [123, 69, 168, 195, 183, 213, 199, 214] {
+        user: Pubkey [signer],
+        pool: Pubkey [mut],
+        user_stake_info: Pubkey [mut],
+        user_staking_wallet: Pubkey [mut],
+        staking_vault: Pubkey [mut],
+        staking_mint: Pubkey [],
+        token_program: Pubkey [],
+    } (forfeit_rewards: bool);
+
+    // 守护者紧急提取指定奖励流的奖励代币
+    emergency_withdraw_reward_tokens(EmergencyWithdrawRewardTokensAccounts) = [155, 218, 30, 78, 244, 156, 83, 117] {
+        pool: Pubkey [mut],
+        guardian: Pubkey [signer],
+        reward_mint: Pubkey [],
+        reward_vault: Pubkey [mut],
+        destination_wallet: Pubkey [mut],
+        token_program: Pubkey [],
+    } (reward_index: u8, amount: u64);
+
+    // 管理员新增一条奖励流，占用第一个空闲槽位并创建对应奖励金库；
+    // allowed_mint_extensions 含义与 initialize 相同
+    add_reward_stream(AddRewardStreamAccounts) = [126, 23, 174, 31, 202, 0, 137, 186] {
+        pool: Pubkey [mut],
+        admin: Pubkey [mut signer],
+        reward_mint: Pubkey [],
+        reward_vault: Pubkey [mut],
+        system_program: Pubkey [],
+        token_program: Pubkey [],
+    } (allowed_mint_extensions: u16);
+
+    // 管理员退役奖励流：停止发放新奖励，用户仍可领取已累积的奖励
+    retire_reward_stream(RetireRewardStreamAccounts) = [240, 71, 38, 248, 160, 195, 134, 142] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (reward_index: u8);
+
+    // 管理员为池子开启收据代币，创建由池子 PDA 控制的 Token-2022 份额 Mint
+    enable_receipt_token(EnableReceiptTokenAccounts) = [131, 210, 66, 241, 206, 116, 137, 58] {
+        pool: Pubkey [mut],
+        admin: Pubkey [mut signer],
+        staking_mint: Pubkey [],
+        receipt_mint: Pubkey [mut],
+        system_program: Pubkey [],
+        token_program: Pubkey [],
+    } ();
+
+    // 管理员开启或关闭自动复利：开启后收据代币份额桶累积的质押代币奖励按份额价格继续计息，
+    // 且任何人都可以为用户调用 compound
+    set_auto_compound(SetAutoCompoundAccounts) = [101, 22, 46, 161, 189, 86, 250, 244] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (enabled: bool);
+
+    // 暂停者（或管理员）暂停程序，阻止用户操作
+    pause(PauseAccounts) = [211, 22, 221, 251, 74, 121, 193, 47] {
+        pool: Pubkey [mut],
+        pauser: Pubkey [signer],
+    } ();
+
+    // 管理员恢复程序，允许用户操作
+    unpause(UnpauseAccounts) = [169, 144, 4, 38, 10, 141, 188, 255] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } ();
+
+    // 管理员设置最长暂停时间（秒），超过后池子自动按未暂停处理；0 表示不限制
+    update_max_pause_duration(UpdateMaxPauseDurationAccounts) = [93, 117, 63, 171, 128, 149, 151, 83] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (new_duration: i64);
+
+    // 奖励率管理者更新锁定期
+    update_lockup_duration(UpdateLockupDurationAccounts) = [61, 91, 174, 32, 209, 193, 36, 149] {
+        pool: Pubkey [mut],
+        rate_manager: Pubkey [signer],
+    } (new_duration: i64);
+
+    // 管理员延长时间锁延迟；缩短延迟本身也必须经过时间锁排队
+    update_timelock_delay(UpdateTimelockDelayAccounts) = [19, 95, 20, 123, 143, 109, 247, 30] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (new_delay: i64);
+
+    // 管理员排队一项参数变更，eta 至少为当前时间加 timelock_delay；变更存放在独立的 PDA 中
+    schedule_change(ScheduleChangeAccounts) = [58, 73, 61, 73, 6, 51, 136, 197] {
+        pool: Pubkey [mut],
+        queued_change: Pubkey [mut],
+        admin: Pubkey [mut signer],
+        system_program: Pubkey [],
+    } (action: TimelockAction, eta: i64);
+
+    // 任何人都可以在 eta 之后执行已排队的变更，执行后关闭变更账户并返还租金
+    execute_change(ExecuteChangeAccounts) = [104, 53, 136, 238, 82, 222, 200, 42] {
+        pool: Pubkey [mut],
+        queued_change: Pubkey [mut],
+        rent_payer: Pubkey [mut],
+    } ();
+
+    // 管理员在 eta 之前撤销已排队的变更
+    cancel_change(CancelChangeAccounts) = [100, 30, 4, 148, 3, 244, 243, 168] {
+        pool: Pubkey [],
+        queued_change: Pubkey [mut],
+        admin: Pubkey [signer],
+        rent_payer: Pubkey [mut],
+    } ();
+
+    // 管理员更新最长锁定期对应的最大加速倍数（基点），10000 表示不加速
+    update_max_boost(UpdateMaxBoostAccounts) = [116, 3, 54, 234, 114, 75, 36, 125] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (max_boost_bps: u16);
+
+    // 管理员更新提前取消质押的罚金（基点），0 表示不允许提前退出；
+    // scale_with_lock 为 true 时罚金按批次剩余锁定期占总锁定期的比例线性递减
+    update_early_unstake_penalty(UpdateEarlyUnstakePenaltyAccounts) = [205, 240, 109, 122, 219, 233, 204, 234] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (penalty_bps: u16, scale_with_lock: bool);
+
+    // 管理员更新罚金去向；转入国库时 treasury 为接收罚金的质押代币账户，其他去向忽略该参数
+    update_penalty_destination(UpdatePenaltyDestinationAccounts) = [199, 210, 174, 74, 71, 114, 232, 125] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (destination: PenaltyDestination, treasury: Pubkey);
+
+    // 管理员更新取消质押冷却期，0 表示关闭两阶段退出
+    update_cooldown_duration(UpdateCooldownDurationAccounts) = [217, 204, 49, 231, 216, 29, 46, 212] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (new_duration: i64);
+
+    // 管理员更新参数边界；新边界不能超过程序硬上限，且必须容纳当前生效的参数
+    update_parameter_bounds(UpdateParameterBoundsAccounts) = [207, 127, 175, 239, 193, 164, 203, 209] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (bounds: ParameterBounds);
+
+    // 管理员更新偿付能力检查期限：调整奖励率时要求金库能覆盖该期限内的发放
+    update_solvency_horizon(UpdateSolvencyHorizonAccounts) = [41, 12, 173, 146, 184, 245, 159, 208] {
+        pool: Pubkey [mut],
+        admin: Pubkey [signer],
+    } (new_horizon: i64);
+}
+
+// get_pool_state 需要追加的账户：已配置奖励流的金库，按槽位顺序只读传入
+pub fn reward_vault_metas(pool: &Pool) -> Vec<AccountMeta> {
+    pool.reward_streams
+        .iter()
+        .filter(|stream| stream.mint != Pubkey::default())
+        .map(|stream| AccountMeta::new_readonly(stream.vault, false))
+        .collect()
+}
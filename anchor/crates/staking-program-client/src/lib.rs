@@ -0,0 +1,49 @@
+// staking_program 的 Rust 客户端：PDA 派生、指令构建、账户解码和事件解析，
+// 以及 projection 功能下的离线奖励预测。
+// 只依赖 solana-program 的类型和 Borsh 编码，不引入 Anchor 运行时；
+// 类型布局与链上程序逐字节一致，由 tests/program_parity.rs 对照程序本身校验
+
+use solana_program::{pubkey, pubkey::Pubkey};
+use std::fmt;
+
+pub mod events;
+pub mod instruction;
+pub mod pda;
+#[cfg(feature = "projection")]
+pub mod projection;
+pub mod state;
+
+// 程序 ID，与 staking_program 的 declare_id! 一致
+pub const ID: Pubkey = pubkey!("GjQvMVAgqV8UJmBdMxv2o6B3kNj7fZvw6LBctkQdFK7r");
+
+// 解码账户、返回数据或事件时的错误
+#[derive(Debug)]
+pub enum ClientError {
+    DiscriminatorMismatch,       // 账户数据的判别符与期望的账户类型不符
+    Borsh(std::io::Error),       // Borsh 解码失败
+    Base64(base64::DecodeError), // 事件日志不是合法的 base64
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::DiscriminatorMismatch => write!(f, "account discriminator mismatch"),
+            ClientError::Borsh(err) => write!(f, "borsh decode error: {err}"),
+            ClientError::Base64(err) => write!(f, "base64 decode error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<std::io::Error> for ClientError {
+    fn from(err: std::io::Error) -> Self {
+        ClientError::Borsh(err)
+    }
+}
+
+impl From<base64::DecodeError> for ClientError {
+    fn from(err: base64::DecodeError) -> Self {
+        ClientError::Base64(err)
+    }
+}
@@ -0,0 +1,41 @@
+// 程序派生地址（PDA），种子与 staking_program 的账户约束一致
+
+use crate::ID;
+use solana_program::pubkey::Pubkey;
+
+// 池子 PDA：[b"pool", 质押代币 Mint, pool_id 小端 8 字节]
+pub fn find_pool_address(staking_mint: &Pubkey, pool_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"pool", staking_mint.as_ref(), &pool_id.to_le_bytes()],
+        &ID,
+    )
+}
+
+// 质押金库 PDA：[b"staking_vault", 池子]
+pub fn find_staking_vault_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"staking_vault", pool.as_ref()], &ID)
+}
+
+// 奖励金库 PDA：[b"reward_vault", 池子, 奖励代币 Mint]
+pub fn find_reward_vault_address(pool: &Pubkey, reward_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"reward_vault", pool.as_ref(), reward_mint.as_ref()], &ID)
+}
+
+// 收据代币 Mint PDA：[b"receipt_mint", 池子]
+pub fn find_receipt_mint_address(pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"receipt_mint", pool.as_ref()], &ID)
+}
+
+// 用户质押信息 PDA：[b"stake_info", 池子, 用户]
+pub fn find_user_stake_info_address(pool: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"stake_info", pool.as_ref(), user.as_ref()], &ID)
+}
+
+// 排队变更 PDA：[b"queued_change", 池子, change_id 小端 8 字节]；
+// 新排队的变更使用池子当前的 next_change_id
+pub fn find_queued_change_address(pool: &Pubkey, change_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"queued_change", pool.as_ref(), &change_id.to_le_bytes()],
+        &ID,
+    )
+}
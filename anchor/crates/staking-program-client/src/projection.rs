@@ -0,0 +1,492 @@
+// 离线奖励预测：在客户端镜像类型上复现 Pool::update_rewards 的结算，奖励流累积、份额桶结算、
+// 加权质押量同步和质押批次记账均来自与链上程序共用的 staking_math，结果与链上逐位一致。
+// 支持时间推进、模拟质押与取消质押序列，以及年化收益率计算
+
+use crate::state::{
+    Pool, RewardStream, StakeLot, UserStakeInfo, MAX_BOOST_LOCK, MAX_REWARD_STREAMS,
+    MAX_STAKE_LOTS, MIN_BOOST_LOCK,
+};
+use solana_program::pubkey::Pubkey;
+use staking_math::{LiquidRewardLedger, Lot, LotError, RewardLedger};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// 一年的秒数，用于年化收益率
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+// 模拟操作失败的原因，与链上对应的 StakingError 一致
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProjectionError {
+    ArithmeticOverflow,       // 算术溢出
+    TimeWentBackwards,        // 只能向前推进时间
+    ZeroAmount,               // 金额必须大于 0
+    InvalidLockDuration,      // 加速锁定期需在 1 周到 4 年之间
+    TooManyStakeLots,         // 质押批次数量已达上限
+    InsufficientStakeAmount,  // 质押金额不足
+    LockupPeriodNotEnded,     // 已解锁的金额不足
+    CooldownRequired,         // 设置了冷却期的池子需走两阶段退出
+    InvalidRewardIndex,       // 奖励流索引无效
+    InsufficientVaultBalance, // 奖励金库账本余额不足
+    UnknownUser,              // 模拟中没有该用户
+}
+
+impl fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for ProjectionError {}
+
+impl From<LotError> for ProjectionError {
+    fn from(err: LotError) -> Self {
+        match err {
+            LotError::ArithmeticOverflow => ProjectionError::ArithmeticOverflow,
+            LotError::TooManyStakeLots => ProjectionError::TooManyStakeLots,
+            LotError::LockupPeriodNotEnded => ProjectionError::LockupPeriodNotEnded,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, ProjectionError>;
+
+impl From<Lot> for StakeLot {
+    fn from(lot: Lot) -> Self {
+        Self {
+            amount: lot.amount,
+            unlock_timestamp: lot.unlock_timestamp,
+            lock_duration: lot.lock_duration,
+        }
+    }
+}
+
+impl From<StakeLot> for Lot {
+    fn from(lot: StakeLot) -> Self {
+        Self {
+            amount: lot.amount,
+            unlock_timestamp: lot.unlock_timestamp,
+            lock_duration: lot.lock_duration,
+        }
+    }
+}
+
+impl RewardStream {
+    // 金库中未被已累积奖励占用的余额
+    pub fn unreserved_balance(&self) -> u64 {
+        self.vault_balance.saturating_sub(self.total_rewards_owed)
+    }
+}
+
+impl Pool {
+    // 按给定时间更新全局和用户奖励，与链上 Pool::update_rewards_at 一致
+    pub fn update_rewards_at(
+        &mut self,
+        current_time: i64,
+        user_stake_info: Option<&mut UserStakeInfo>,
+    ) -> Result<()> {
+        staking_math::accrue_streams(
+            self.reward_streams.iter_mut().map(|stream| RewardLedger {
+                period_finish: stream.period_finish,
+                reward_rate: stream.reward_rate,
+                vault_balance: stream.vault_balance,
+                reward_per_token_stored: &mut stream.reward_per_token_stored,
+                total_rewards_owed: &mut stream.total_rewards_owed,
+            }),
+            self.last_update_timestamp,
+            current_time,
+            self.total_weighted_stake,
+        )
+        .ok_or(ProjectionError::ArithmeticOverflow)?;
+
+        self.last_update_timestamp = current_time;
+        self.settle_liquid_rewards()?;
+
+        if let Some(info) = user_stake_info {
+            for index in 0..MAX_REWARD_STREAMS {
+                let pending_rewards = info.calculate_pending_rewards(self, index);
+                info.rewards[index] = info.rewards[index]
+                    .checked_add(pending_rewards)
+                    .ok_or(ProjectionError::ArithmeticOverflow)?;
+                info.reward_per_token_paid[index] =
+                    self.reward_streams[index].reward_per_token_stored;
+            }
+        }
+        Ok(())
+    }
+
+    // 结算收据代币份额桶的奖励，与链上 settle_liquid_rewards 一致
+    fn settle_liquid_rewards(&mut self) -> Result<()> {
+        let staking_mint = self.staking_mint;
+        staking_math::settle_liquid_rewards(
+            self.reward_streams
+                .iter_mut()
+                .zip(self.liquid_reward_per_token_paid.iter_mut())
+                .map(|(stream, paid)| LiquidRewardLedger {
+                    reward_per_token_stored: stream.reward_per_token_stored,
+                    compounds: stream.mint == staking_mint,
+                    reward_per_token_paid: paid,
+                    total_rewards_owed: &mut stream.total_rewards_owed,
+                }),
+            self.auto_compound,
+            self.liquid_principal,
+            &mut self.liquid_rewards,
+            &mut self.total_weighted_stake,
+        )
+        .ok_or(ProjectionError::ArithmeticOverflow)
+    }
+
+    // 按 now 时刻重新计算用户加权质押量，并同步池子总加权质押量
+    pub fn sync_weight(&mut self, info: &mut UserStakeInfo, now: i64) -> Result<()> {
+        staking_math::sync_weight(
+            info.lots
+                .iter()
+                .map(|lot| (lot.amount, lot.unlock_timestamp)),
+            now,
+            self.max_boost_bps,
+            &mut info.weighted_amount,
+            &mut self.total_weighted_stake,
+        )
+        .ok_or(ProjectionError::ArithmeticOverflow)
+    }
+
+    // 与链上 stake 指令相同的记账：结算奖励后记录新的质押批次并同步加权质押量，返回批次解锁时间。
+    // amount 为质押金库实际收到的金额（已扣除 Token-2022 转账手续费）
+    pub fn record_stake(
+        &mut self,
+        info: &mut UserStakeInfo,
+        amount: u64,
+        lock_duration: i64,
+        now: i64,
+    ) -> Result<i64> {
+        self.update_rewards_at(now, Some(info))?;
+        if info.stake_amount == 0 {
+            info.stake_start_timestamp = now;
+        }
+        let lot_lock = self.lockup_duration.max(lock_duration);
+        let unlock_timestamp = now
+            .checked_add(lot_lock)
+            .ok_or(ProjectionError::ArithmeticOverflow)?;
+        info.add_lot(amount, unlock_timestamp, lot_lock, now)?;
+        info.stake_amount = info
+            .stake_amount
+            .checked_add(amount)
+            .ok_or(ProjectionError::ArithmeticOverflow)?;
+        self.total_staked = self
+            .total_staked
+            .checked_add(amount)
+            .ok_or(ProjectionError::ArithmeticOverflow)?;
+        self.sync_weight(info, now)?;
+        Ok(unlock_timestamp)
+    }
+
+    // 与链上 unstake 指令相同的记账：从已解锁的批次中扣减，结算奖励后同步质押量和加权质押量，
+    // 返回仍锁定的金额
+    pub fn record_unstake(
+        &mut self,
+        info: &mut UserStakeInfo,
+        amount: u64,
+        now: i64,
+    ) -> Result<u64> {
+        if info.stake_amount < amount {
+            return Err(ProjectionError::InsufficientStakeAmount);
+        }
+        let remaining_locked = info.consume_unlocked_lots(amount, now)?;
+        self.update_rewards_at(now, Some(info))?;
+        info.stake_amount -= amount;
+        self.total_staked = self
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(ProjectionError::ArithmeticOverflow)?;
+        self.sync_weight(info, now)?;
+        if info.stake_amount == 0 {
+            info.stake_start_timestamp = 0;
+        }
+        Ok(remaining_locked)
+    }
+
+    // 与链上 claim_rewards 指令相同的记账：结算奖励后清零用户该奖励流的待领奖励，返回领取金额
+    pub fn record_claim(
+        &mut self,
+        info: &mut UserStakeInfo,
+        index: usize,
+        now: i64,
+    ) -> Result<u64> {
+        self.update_rewards_at(now, Some(info))?;
+        let amount = info.rewards[index];
+        info.rewards[index] = 0;
+        let stream = &mut self.reward_streams[index];
+        stream.total_rewards_owed = stream.total_rewards_owed.saturating_sub(amount);
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_sub(amount)
+            .ok_or(ProjectionError::InsufficientVaultBalance)?;
+        Ok(amount)
+    }
+
+    // 与链上 poke 指令相同的记账：先按旧权重结算奖励，再按当前剩余锁定期重新计算权重
+    pub fn record_poke(&mut self, info: &mut UserStakeInfo, now: i64) -> Result<()> {
+        self.update_rewards_at(now, Some(info))?;
+        self.sync_weight(info, now)
+    }
+
+    // 奖励流在 now 时刻的年化奖励率：每单位加权质押一年可获得的奖励代币数量（未换算价格）。
+    // 按当前奖励率外推，周期结束后为 0；无人质押时返回 None
+    pub fn reward_apr(&self, reward_index: usize, now: i64) -> Option<f64> {
+        let stream = self.reward_streams.get(reward_index)?;
+        if self.total_weighted_stake == 0 {
+            return None;
+        }
+        if now >= stream.period_finish {
+            return Some(0.0);
+        }
+        Some(stream.reward_rate as f64 * SECONDS_PER_YEAR as f64 / self.total_weighted_stake as f64)
+    }
+}
+
+impl UserStakeInfo {
+    // 用户在指定奖励流上自上次同步以来的待领奖励
+    pub fn calculate_pending_rewards(&self, pool: &Pool, index: usize) -> u64 {
+        staking_math::pending_rewards(
+            self.weighted_amount,
+            pool.reward_streams[index].reward_per_token_stored,
+            self.reward_per_token_paid[index],
+        )
+    }
+
+    // 在 now 时刻的加权质押量
+    pub fn boosted_weight(&self, now: i64, max_boost_bps: u16) -> Result<u64> {
+        staking_math::boosted_weight(
+            self.lots
+                .iter()
+                .map(|lot| (lot.amount, lot.unlock_timestamp)),
+            now,
+            max_boost_bps,
+        )
+        .ok_or(ProjectionError::ArithmeticOverflow)
+    }
+
+    // 在 now 时刻仍处于锁定期的质押金额
+    pub fn locked_amount(&self, now: i64) -> u64 {
+        staking_math::locked_amount(
+            self.lots
+                .iter()
+                .map(|lot| (lot.amount, lot.unlock_timestamp)),
+            now,
+        )
+    }
+
+    // 记录一笔新的质押批次：now 时刻已解锁的批次合并为一个，解锁时间相同的相邻批次合并
+    pub fn add_lot(
+        &mut self,
+        amount: u64,
+        unlock_timestamp: i64,
+        lock_duration: i64,
+        now: i64,
+    ) -> Result<()> {
+        let lot = Lot {
+            amount,
+            unlock_timestamp,
+            lock_duration,
+        };
+        Ok(staking_math::add_lot(
+            &mut self.lots,
+            lot,
+            MAX_STAKE_LOTS,
+            now,
+        )?)
+    }
+
+    // 按先进先出顺序从已解锁批次中扣减 amount，返回扣减后仍锁定的金额
+    pub fn consume_unlocked_lots(&mut self, amount: u64, now: i64) -> Result<u64> {
+        Ok(staking_math::consume_unlocked_lots(
+            &mut self.lots,
+            self.stake_amount,
+            amount,
+            now,
+        )?)
+    }
+
+    // 用户在 now 时刻的年化奖励率：池子年化奖励率乘以用户的加权倍数（已同步的加权质押量 / 质押金额）
+    pub fn reward_apr(&self, pool: &Pool, reward_index: usize, now: i64) -> Option<f64> {
+        if self.stake_amount == 0 {
+            return None;
+        }
+        Some(
+            pool.reward_apr(reward_index, now)? * self.weighted_amount as f64
+                / self.stake_amount as f64,
+        )
+    }
+}
+
+// 年化奖励率按每年 compounds_per_year 次复利换算为年化收益率
+pub fn apy(apr: f64, compounds_per_year: u32) -> f64 {
+    if compounds_per_year == 0 {
+        return apr;
+    }
+    let periods = compounds_per_year as f64;
+    (1.0 + apr / periods).powf(periods) - 1.0
+}
+
+// 质押代币 Mint 的 Token-2022 转账手续费配置（当前 epoch 生效的一档）
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransferFee {
+    pub basis_points: u16, // 手续费基点
+    pub maximum_fee: u64,  // 单笔手续费上限
+}
+
+impl TransferFee {
+    // 转账 amount 时收取的手续费，与 Token-2022 的计算方式一致（向上取整，不超过上限）
+    pub fn calculate_fee(&self, amount: u64) -> Option<u64> {
+        if self.basis_points == 0 || amount == 0 {
+            return Some(0);
+        }
+        let fee: u64 = (amount as u128)
+            .checked_mul(self.basis_points as u128)?
+            .div_ceil(10_000)
+            .try_into()
+            .ok()?;
+        Some(fee.min(self.maximum_fee))
+    }
+
+    // 转账 amount 后接收方实际收到的金额
+    pub fn amount_after_fee(&self, amount: u64) -> Option<u64> {
+        amount.checked_sub(self.calculate_fee(amount)?)
+    }
+}
+
+// 一个池子及其部分用户的离线模拟。时间只能向前推进；与链上一样，奖励在操作或查询时才按时间结算，
+// 用户加权质押量在质押、取消质押或 poke 时才随剩余锁定期衰减。
+// 质押时按 staking_transfer_fee 扣除转账手续费后记入净额；取消质押和领取返回的是从金库转出的金额，
+// 用户实际收到的金额还需扣除各自 Mint 的转账手续费
+#[derive(Clone, Debug)]
+pub struct Projection {
+    pub pool: Pool,                             // 模拟中的池子状态
+    pub users: BTreeMap<Pubkey, UserStakeInfo>, // 模拟中的用户质押信息，按用户公钥索引
+    pub now: i64,                               // 模拟的当前时间
+    pub staking_transfer_fee: TransferFee,      // 质押代币的转账手续费，默认无手续费
+}
+
+impl Projection {
+    // 从链上读取的池子状态和当前时间开始模拟，操作失败时模拟状态保持不变
+    pub fn new(pool: Pool, now: i64) -> Self {
+        Projection {
+            pool,
+            users: BTreeMap::new(),
+            now,
+            staking_transfer_fee: TransferFee::default(),
+        }
+    }
+
+    // 设置质押代币的转账手续费，模拟 Token-2022 TransferFee 扩展
+    pub fn with_transfer_fee(mut self, fee: TransferFee) -> Self {
+        self.staking_transfer_fee = fee;
+        self
+    }
+
+    // 加入从链上读取的用户质押信息
+    pub fn with_user(mut self, info: UserStakeInfo) -> Self {
+        self.users.insert(info.user, info);
+        self
+    }
+
+    // 将模拟时间推进到 timestamp
+    pub fn advance_to(&mut self, timestamp: i64) -> Result<()> {
+        if timestamp < self.now {
+            return Err(ProjectionError::TimeWentBackwards);
+        }
+        self.now = timestamp;
+        Ok(())
+    }
+
+    // 将模拟时间推进 seconds 秒
+    pub fn advance_by(&mut self, seconds: i64) -> Result<()> {
+        let timestamp = self
+            .now
+            .checked_add(seconds)
+            .ok_or(ProjectionError::ArithmeticOverflow)?;
+        self.advance_to(timestamp)
+    }
+
+    // 在池子和用户质押信息的副本上执行一次操作，成功后才写回，与链上交易失败时整体回滚一致
+    fn transact<T>(
+        &mut self,
+        user: Pubkey,
+        op: impl FnOnce(&mut Pool, &mut UserStakeInfo, i64) -> Result<T>,
+    ) -> Result<T> {
+        let mut pool = self.pool.clone();
+        let mut info = self
+            .users
+            .get(&user)
+            .cloned()
+            .ok_or(ProjectionError::UnknownUser)?;
+        let result = op(&mut pool, &mut info, self.now)?;
+        self.pool = pool;
+        self.users.insert(user, info);
+        Ok(result)
+    }
+
+    // 模拟 stake：新用户自动创建质押信息。与链上一样只记入扣除转账手续费后的净额，返回该净额
+    pub fn stake(&mut self, user: Pubkey, amount: u64, lock_duration: i64) -> Result<u64> {
+        if amount == 0 {
+            return Err(ProjectionError::ZeroAmount);
+        }
+        if lock_duration != 0 && !(MIN_BOOST_LOCK..=MAX_BOOST_LOCK).contains(&lock_duration) {
+            return Err(ProjectionError::InvalidLockDuration);
+        }
+        let net_amount = self
+            .staking_transfer_fee
+            .amount_after_fee(amount)
+            .ok_or(ProjectionError::ArithmeticOverflow)?;
+        if net_amount == 0 {
+            return Err(ProjectionError::ZeroAmount);
+        }
+        self.users.entry(user).or_insert_with(|| UserStakeInfo {
+            user,
+            ..Default::default()
+        });
+        self.transact(user, |pool, info, now| {
+            pool.record_stake(info, net_amount, lock_duration, now)?;
+            Ok(net_amount)
+        })
+    }
+
+    // 模拟 unstake，返回仍处于锁定期的金额
+    pub fn unstake(&mut self, user: Pubkey, amount: u64) -> Result<u64> {
+        if amount == 0 {
+            return Err(ProjectionError::ZeroAmount);
+        }
+        if self.pool.cooldown_duration != 0 {
+            return Err(ProjectionError::CooldownRequired);
+        }
+        self.transact(user, |pool, info, now| {
+            pool.record_unstake(info, amount, now)
+        })
+    }
+
+    // 模拟 claim_rewards，返回领取的奖励
+    pub fn claim(&mut self, user: Pubkey, reward_index: usize) -> Result<u64> {
+        if reward_index >= MAX_REWARD_STREAMS {
+            return Err(ProjectionError::InvalidRewardIndex);
+        }
+        self.transact(user, |pool, info, now| {
+            pool.record_claim(info, reward_index, now)
+        })
+    }
+
+    // 模拟 poke：按当前剩余锁定期重新计算用户权重
+    pub fn poke(&mut self, user: Pubkey) -> Result<()> {
+        self.transact(user, |pool, info, now| pool.record_poke(info, now))
+    }
+
+    // 用户在模拟当前时间的待领奖励，不修改模拟状态
+    pub fn pending_rewards(&self, user: Pubkey) -> Result<[u64; MAX_REWARD_STREAMS]> {
+        let mut pool = self.pool.clone();
+        let mut info = self
+            .users
+            .get(&user)
+            .cloned()
+            .ok_or(ProjectionError::UnknownUser)?;
+        pool.update_rewards_at(self.now, Some(&mut info))?;
+        Ok(info.rewards)
+    }
+}
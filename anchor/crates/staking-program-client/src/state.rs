@@ -0,0 +1,353 @@
+// 链上账户、指令参数和视图返回值的镜像类型，字段顺序与 staking_program 完全一致，按 Borsh 编解码
+
+use crate::ClientError;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+
+// 每个池子最多同时维护的奖励流数量
+pub const MAX_REWARD_STREAMS: usize = 3;
+// 每个用户在每个池子中最多同时持有的质押批次数量
+pub const MAX_STAKE_LOTS: usize = 16;
+// 每个用户在每个池子中最多同时存在的取消质押请求数量
+pub const MAX_UNBONDING_REQUESTS: usize = 8;
+
+// 加速倍数的基准（基点），10000 表示 1x
+pub const BOOST_BASE_BPS: u16 = 10_000;
+// 最大加速倍数上限：4x
+pub const MAX_BOOST_BPS: u16 = 40_000;
+// 加速锁定期下限：1 周
+pub const MIN_BOOST_LOCK: i64 = 7 * 24 * 60 * 60;
+// 加速锁定期上限：4 年
+pub const MAX_BOOST_LOCK: i64 = 4 * 365 * 24 * 60 * 60;
+// 罚金上限：100%
+pub const MAX_PENALTY_BPS: u16 = 10_000;
+
+// Mint 扩展位掩码（与 Pool.staking_mint_extensions、RewardStream.mint_extensions 对应）
+pub const MINT_EXT_FREEZE_AUTHORITY: u16 = 1 << 0; // 设置了冻结权限
+pub const MINT_EXT_PERMANENT_DELEGATE: u16 = 1 << 1; // 永久委托人可转走任意账户的代币
+pub const MINT_EXT_NON_TRANSFERABLE: u16 = 1 << 2; // 不可转让
+pub const MINT_EXT_CONFIDENTIAL_TRANSFER: u16 = 1 << 3; // 机密转账，余额可能不可见
+pub const MINT_EXT_CLOSE_AUTHORITY: u16 = 1 << 4; // Mint 可被关闭
+pub const MINT_EXT_DEFAULT_FROZEN: u16 = 1 << 5; // 新账户默认冻结
+pub const MINT_EXT_TRANSFER_FEE: u16 = 1 << 8; // 转账手续费
+pub const MINT_EXT_TRANSFER_HOOK: u16 = 1 << 9; // 转账钩子
+
+// 参数硬上限，ParameterBounds 不能超过这些值
+pub const MAX_LOCKUP_DURATION: i64 = MAX_BOOST_LOCK;
+pub const MAX_COOLDOWN_DURATION: i64 = 365 * 24 * 60 * 60;
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+pub const MAX_PAUSE_DURATION: i64 = 365 * 24 * 60 * 60;
+pub const MAX_SOLVENCY_HORIZON: i64 = 365 * 24 * 60 * 60;
+pub const REWARD_RATE_SIMULATION_HORIZON: i64 = 10 * 365 * 24 * 60 * 60;
+pub const MAX_REWARD_RATE: u64 = u64::MAX / REWARD_RATE_SIMULATION_HORIZON as u64;
+pub const MAX_REWARD_DURATION: i64 = REWARD_RATE_SIMULATION_HORIZON;
+
+// 单条奖励流，拥有独立的奖励代币、金库、奖励率和累计指数
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct RewardStream {
+    pub mint: Pubkey,                  // 奖励代币 Mint 地址，默认值表示空闲槽位
+    pub vault: Pubkey,                 // 奖励金库地址
+    pub reward_rate: u64,              // 每秒奖励代币数量
+    pub reward_per_token_stored: u128, // 每单位代币的累计奖励（高精度）
+    pub period_finish: i64,            // 当前奖励周期结束时间戳，之后停止累积
+    pub vault_balance: u64,            // 奖励金库账本余额（注资减去已支付）
+    pub total_rewards_owed: u64,       // 已累积但尚未领取的奖励（负债）
+    pub is_active: bool,               // 是否仍在发放，退役后停止累积但可领取
+    pub mint_extensions: u16,          // 奖励代币 Mint 的扩展（MINT_EXT_* 位掩码）
+}
+
+// 池子状态账户，存储全局质押信息
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct Pool {
+    pub admin: Pubkey,                                            // 管理员公钥
+    pub pending_admin: Pubkey, // 已提名、待接受的新管理员，默认值表示没有提名
+    pub pauser: Pubkey,        // 暂停者，只能暂停程序，默认值表示未授予
+    pub rate_manager: Pubkey,  // 奖励率管理者，可调整奖励率和锁定期
+    pub funder: Pubkey,        // 注资者，可从任意钱包注资奖励
+    pub guardian: Pubkey,      // 守护者，可执行紧急提取
+    pub pool_id: u64,          // 池子编号，由管理员选择，参与 PDA 派生
+    pub staking_mint: Pubkey,  // 质押代币 Mint 地址
+    pub staking_vault: Pubkey, // 质押金库地址
+    pub staking_mint_extensions: u16, // 质押代币 Mint 的扩展（MINT_EXT_* 位掩码）
+    pub reward_streams: [RewardStream; MAX_REWARD_STREAMS], // 奖励流列表
+    pub last_update_timestamp: i64, // 最后更新时间戳
+    pub total_staked: u64,     // 总质押量
+    pub total_weighted_stake: u64, // 按锁定期加权后的总质押量，用于分配奖励
+    pub total_unbonding: u64,  // 冷却中、已停止计息但尚未提取的质押量
+    pub pool_bump: u8,         // 池子 PDA bump 值
+    pub lockup_duration: i64,  // 锁定期（秒）
+    pub max_boost_bps: u16,    // 锁定 4 年对应的最大加速倍数（基点）
+    pub cooldown_duration: i64, // 取消质押冷却期（秒），0 表示可直接 unstake
+    pub solvency_horizon: i64, // 调整奖励率时的偿付能力检查期限（秒）
+    pub timelock_delay: i64,   // 参数变更的最短排队时间（秒），0 表示不启用时间锁
+    pub next_change_id: u64,   // 下一个排队变更的序号，参与变更 PDA 派生
+    pub bounds: ParameterBounds, // 管理员可设置参数的上下限
+    pub is_shutdown: bool,     // 紧急提取质押代币后进入的不可逆关停状态
+    pub shutdown_claims: u64,  // 关停后尚未退出的本金债权总额
+    pub shutdown_balance: u64, // 关停后尚未分配的质押金库余额
+    pub is_paused: bool,       // 程序暂停状态
+    pub paused_at: i64,        // 最近一次暂停的时间戳
+    pub max_pause_duration: i64, // 最长暂停时间（秒），超过后自动按未暂停处理，0 表示不限制
+    pub receipt_mint: Pubkey,  // 收据代币 Mint 地址，默认值表示未开启
+    pub liquid_principal: u64, // 收据代币份额桶中的质押本金，按 1x 权重计入总质押量
+    pub liquid_rewards: u64,   // 份额桶累积的质押代币奖励，计入份额价格
+    pub liquid_reward_per_token_paid: [u128; MAX_REWARD_STREAMS], // 份额桶在每条奖励流的奖励基准
+    pub auto_compound: bool,   // 是否开启自动复利
+    pub early_unstake_penalty_bps: u16, // 提前取消质押的罚金（基点），0 表示不允许
+    pub penalty_scales_with_lock: bool, // 罚金是否按剩余锁定期线性递减
+    pub penalty_destination: PenaltyDestination, // 罚金去向
+    pub penalty_treasury: Pubkey, // 接收罚金的国库账户
+    pub penalties_burned: u64, // 累计销毁的罚金
+    pub penalties_to_treasury: u64, // 累计转入国库的罚金
+    pub penalties_redistributed: u64, // 累计分配给剩余质押者的罚金
+}
+
+// 可由管理员授予的权限角色，管理员本身始终拥有全部角色
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Pauser,      // 暂停程序
+    RateManager, // 调整奖励率和锁定期
+    Funder,      // 注资奖励
+    Guardian,    // 紧急提取
+}
+
+// 管理员可设置参数的上下限，每个 setter 都会按此校验
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParameterBounds {
+    pub min_lockup_duration: i64,   // 最短锁定期（秒）
+    pub max_lockup_duration: i64,   // 最长锁定期（秒）
+    pub max_reward_rate: u64,       // 每条奖励流的最大奖励率
+    pub max_cooldown_duration: i64, // 最长取消质押冷却期（秒）
+    pub max_timelock_delay: i64,    // 最长时间锁延迟（秒）
+    pub max_pause_duration: i64,    // 最长暂停时间的上限（秒），0 表示允许不限制暂停时间
+    pub min_solvency_horizon: i64,  // 最短偿付能力检查期限（秒）
+    pub max_solvency_horizon: i64,  // 最长偿付能力检查期限（秒）
+    pub min_reward_duration: i64,   // 最短奖励周期（秒）
+    pub max_reward_duration: i64,   // 最长奖励周期（秒）
+}
+
+// 可通过时间锁排队的参数变更
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TimelockAction {
+    // 更新奖励率
+    UpdateRewardRate {
+        reward_index: u8,
+        new_rate: u64,
+    },
+    // 更新锁定期
+    UpdateLockupDuration {
+        new_duration: i64,
+    },
+    // 提名新管理员
+    ProposeAdmin {
+        new_admin: Pubkey,
+    },
+    // 修改时间锁延迟
+    UpdateTimelockDelay {
+        new_delay: i64,
+    },
+    // 从已注资的未占用余额中划出 amount 开启新奖励周期
+    NotifyRewardAmount {
+        reward_index: u8,
+        amount: u64,
+        duration: i64,
+    },
+    // 退役奖励流
+    RetireRewardStream {
+        reward_index: u8,
+    },
+    // 更新冷却期
+    UpdateCooldownDuration {
+        new_duration: i64,
+    },
+    // 更新最大加速倍数
+    UpdateMaxBoost {
+        max_boost_bps: u16,
+    },
+    // 更新提前取消质押罚金
+    UpdateEarlyUnstakePenalty {
+        penalty_bps: u16,
+        scale_with_lock: bool,
+    },
+    // 更新最长暂停时间
+    UpdateMaxPauseDuration {
+        new_duration: i64,
+    },
+    // 更新参数边界
+    UpdateParameterBounds {
+        bounds: ParameterBounds,
+    },
+    // 更新罚金去向
+    UpdatePenaltyDestination {
+        destination: PenaltyDestination,
+        treasury: Pubkey,
+    },
+    // 开启或关闭自动复利
+    SetAutoCompound {
+        enabled: bool,
+    },
+}
+
+// 排队中的参数变更，每项变更一个 PDA，便于前端展示即将生效的变更
+#[derive(BorshSerialize, BorshDeserialize, Clone, PartialEq, Debug)]
+pub struct QueuedChange {
+    pub pool: Pubkey,           // 所属池子
+    pub change_id: u64,         // 变更序号
+    pub action: TimelockAction, // 变更内容
+    pub eta: i64,               // 最早可执行时间
+    pub rent_payer: Pubkey,     // 支付租金的账户，变更关闭时返还
+    pub bump: u8,               // PDA bump 值
+}
+
+// 提前取消质押罚金的去向
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum PenaltyDestination {
+    #[default]
+    Burn, // 从质押金库销毁
+    Treasury,     // 转入国库
+    Redistribute, // 通过质押代币奖励流分配给剩余质押者
+}
+
+// 单笔质押批次，拥有独立的金额和解锁时间
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct StakeLot {
+    pub amount: u64,           // 批次剩余金额
+    pub unlock_timestamp: i64, // 批次解锁时间戳
+    pub lock_duration: i64,    // 批次存入时的锁定期（秒），用于按比例计算提前退出罚金
+}
+
+// 冷却中的取消质押请求
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct UnbondingRequest {
+    pub amount: u64,       // 请求提取的金额
+    pub available_at: i64, // 冷却期结束、可提取的时间戳
+}
+
+// 用户质押信息账户，存储用户特定数据
+#[derive(BorshSerialize, BorshDeserialize, Clone, Default, PartialEq, Debug)]
+pub struct UserStakeInfo {
+    pub pool: Pubkey,                                      // 所属池子地址
+    pub user: Pubkey,                                      // 所属用户公钥
+    pub stake_amount: u64,                                 // 用户质押金额
+    pub weighted_amount: u64,                              // 上次同步时按锁定期加权后的质押量
+    pub stake_start_timestamp: i64,                        // 质押开始时间戳
+    pub lots: Vec<StakeLot>, // 质押批次，按存入顺序排列，金额之和等于 stake_amount
+    pub unbonding: Vec<UnbondingRequest>, // 冷却中的取消质押请求
+    pub reward_per_token_paid: [u128; MAX_REWARD_STREAMS], // 用户在每条奖励流上次同步的奖励基准
+    pub rewards: [u64; MAX_REWARD_STREAMS], // 每条奖励流已计算但未领取的奖励
+}
+
+// get_pending_rewards 的返回值
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct PendingRewardsView {
+    pub pool: Pubkey,                               // 池子地址
+    pub user: Pubkey,                               // 用户公钥
+    pub timestamp: i64,                             // 结算时间戳
+    pub reward_mints: [Pubkey; MAX_REWARD_STREAMS], // 每条奖励流的奖励代币 Mint，默认值表示空闲槽位
+    pub rewards: [u64; MAX_REWARD_STREAMS],         // 每条奖励流的待领奖励
+}
+
+// get_user_position 的返回值
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct UserPositionView {
+    pub pool: Pubkey,                       // 池子地址
+    pub user: Pubkey,                       // 用户公钥
+    pub timestamp: i64,                     // 结算时间戳
+    pub stake_amount: u64,                  // 质押金额
+    pub locked_amount: u64,                 // 仍处于锁定期的金额
+    pub unlocked_amount: u64,               // 已解锁、可取消质押的金额
+    pub next_unlock_timestamp: i64,         // 最近一个锁定批次的解锁时间，0 表示没有锁定中的批次
+    pub final_unlock_timestamp: i64,        // 最后一个锁定批次的解锁时间，0 表示没有锁定中的批次
+    pub stake_start_timestamp: i64,         // 质押开始时间戳
+    pub weighted_amount: u64,               // 上次同步的加权质押量，决定当前的奖励份额
+    pub current_weight: u64,                // 按当前剩余锁定期计算的加权质押量，poke 后生效
+    pub unbonding_amount: u64,              // 冷却中的取消质押总额
+    pub withdrawable_amount: u64,           // 冷却期已结束、可提取的金额
+    pub rewards: [u64; MAX_REWARD_STREAMS], // 每条奖励流的待领奖励
+}
+
+// 奖励流的只读视图，包含计算年化收益率所需的输入
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct RewardStreamView {
+    pub mint: Pubkey,                  // 奖励代币 Mint 地址，默认值表示空闲槽位
+    pub vault: Pubkey,                 // 奖励金库地址
+    pub reward_rate: u64,              // 配置的每秒奖励代币数量
+    pub current_reward_rate: u64,      // 当前实际发放速率，周期结束后为 0
+    pub period_finish: i64,            // 当前奖励周期结束时间戳
+    pub reward_per_token_stored: u128, // 每单位加权质押的累计奖励（高精度）
+    pub vault_balance: u64,            // 奖励金库账本余额
+    pub vault_amount: u64,             // 奖励金库的实际代币余额，可能因直接转入而高于账本余额
+    pub total_rewards_owed: u64,       // 已累积但尚未领取的奖励
+    pub unreserved_balance: u64,       // 可继续发放的余额
+    pub is_active: bool,               // 是否仍在发放
+}
+
+// get_pool_state 的返回值
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Debug)]
+pub struct PoolStateView {
+    pub pool: Pubkey,                                           // 池子地址
+    pub timestamp: i64,                                         // 结算时间戳
+    pub is_paused: bool,            // 当前是否暂停（已考虑最长暂停时间）
+    pub is_shutdown: bool,          // 是否已关停
+    pub total_staked: u64,          // 总质押量
+    pub total_weighted_stake: u64,  // 总加权质押量，年化收益率的分母
+    pub total_unbonding: u64,       // 冷却中的质押量
+    pub staking_vault_balance: u64, // 质押金库的实际代币余额
+    pub liquid_principal: u64,      // 收据代币份额桶中的质押本金
+    pub liquid_rewards: u64,        // 份额桶累积的质押代币奖励
+    pub lockup_duration: i64,       // 锁定期（秒）
+    pub cooldown_duration: i64,     // 取消质押冷却期（秒）
+    pub max_boost_bps: u16,         // 最大加速倍数（基点）
+    pub reward_streams: [RewardStreamView; MAX_REWARD_STREAMS], // 奖励流视图
+}
+
+impl Default for ParameterBounds {
+    // 默认边界即程序硬上限
+    fn default() -> Self {
+        ParameterBounds {
+            min_lockup_duration: 0,
+            max_lockup_duration: MAX_LOCKUP_DURATION,
+            max_reward_rate: MAX_REWARD_RATE,
+            max_cooldown_duration: MAX_COOLDOWN_DURATION,
+            max_timelock_delay: MAX_TIMELOCK_DELAY,
+            max_pause_duration: 0,
+            min_solvency_horizon: 1,
+            max_solvency_horizon: MAX_SOLVENCY_HORIZON,
+            min_reward_duration: 1,
+            max_reward_duration: MAX_REWARD_DURATION,
+        }
+    }
+}
+
+// 程序拥有的账户类型，数据以 8 字节 Anchor 判别符开头
+pub trait ProgramAccount: BorshSerialize + BorshDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+
+    // 校验判别符后解码账户数据；账户按最大长度分配，尾部未使用的字节被忽略
+    fn decode(data: &[u8]) -> Result<Self, ClientError> {
+        let mut body = data
+            .strip_prefix(&Self::DISCRIMINATOR[..])
+            .ok_or(ClientError::DiscriminatorMismatch)?;
+        Ok(Self::deserialize(&mut body)?)
+    }
+
+    // 编码为带判别符的账户数据，不含尾部填充
+    fn encode(&self) -> Vec<u8> {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        self.serialize(&mut data)
+            .expect("writing to a Vec cannot fail");
+        data
+    }
+}
+
+impl ProgramAccount for Pool {
+    const DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+}
+
+impl ProgramAccount for QueuedChange {
+    const DISCRIMINATOR: [u8; 8] = [178, 209, 212, 240, 49, 65, 50, 3];
+}
+
+impl ProgramAccount for UserStakeInfo {
+    const DISCRIMINATOR: [u8; 8] = [219, 233, 236, 123, 28, 113, 89, 56];
+}
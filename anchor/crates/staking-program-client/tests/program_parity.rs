@@ -0,0 +1,1420 @@
+// 对照链上程序本身校验客户端：指令数据和账户元数据、账户布局、事件判别符和日志解析
+
+use anchor_lang::{
+    AccountDeserialize, AccountSerialize, Discriminator, Event, InstructionData, ToAccountMetas,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_program::pubkey::Pubkey;
+use staking_program_client::events::{self, StakingEvent};
+use staking_program_client::instruction;
+use staking_program_client::state::{self, ProgramAccount};
+
+// 测试用的确定性公钥
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+// 客户端构建的指令必须与 Anchor 为同一组账户和参数生成的指令完全一致
+macro_rules! assert_instruction {
+    ($client:expr, $accounts:expr, $data:expr) => {{
+        let ix = $client;
+        assert_eq!(ix.program_id, staking_program::ID);
+        assert_eq!(ix.accounts, $accounts.to_account_metas(None));
+        assert_eq!(ix.data, $data.data());
+    }};
+}
+
+#[test]
+fn instructions_match_program() {
+    assert_instruction!(
+        instruction::initialize(
+            &instruction::InitializeAccounts {
+                pool: key(1),
+                admin: key(2),
+                staking_mint: key(3),
+                staking_vault: key(4),
+                reward_mint: key(5),
+                reward_vault: key(6),
+                system_program: key(7),
+                token_program: key(8),
+                reward_token_program: key(9),
+                rent: key(10)
+            },
+            123_456_789,
+            86_400,
+            1_500
+        ),
+        staking_program::accounts::Initialize {
+            pool: key(1),
+            admin: key(2),
+            staking_mint: key(3),
+            staking_vault: key(4),
+            reward_mint: key(5),
+            reward_vault: key(6),
+            system_program: key(7),
+            token_program: key(8),
+            reward_token_program: key(9),
+            rent: key(10)
+        },
+        staking_program::instruction::Initialize {
+            pool_id: 123_456_789,
+            lockup_duration: 86_400,
+            allowed_mint_extensions: 1_500
+        }
+    );
+    assert_instruction!(
+        instruction::stake(
+            &instruction::StakeAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3),
+                user_staking_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                system_program: key(7),
+                token_program: key(8)
+            },
+            123_456_789,
+            86_400
+        ),
+        staking_program::accounts::Stake {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            system_program: key(7),
+            token_program: key(8)
+        },
+        staking_program::instruction::Stake {
+            amount: 123_456_789,
+            lock_duration: 86_400
+        }
+    );
+    assert_instruction!(
+        instruction::unstake(
+            &instruction::UnstakeAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3),
+                user_staking_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                token_program: key(7)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::UnStake {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        },
+        staking_program::instruction::Unstake {
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::unstake_early(
+            &instruction::UnstakeEarlyAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3),
+                user_staking_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                treasury: Some(key(7)),
+                reward_vault: Some(key(8)),
+                token_program: key(9)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::UnstakeEarly {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            treasury: Some(key(7)),
+            reward_vault: Some(key(8)),
+            token_program: key(9)
+        },
+        staking_program::instruction::UnstakeEarly {
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::unstake_early(
+            &instruction::UnstakeEarlyAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3),
+                user_staking_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                treasury: None,
+                reward_vault: None,
+                token_program: key(9)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::UnstakeEarly {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            treasury: None,
+            reward_vault: None,
+            token_program: key(9)
+        },
+        staking_program::instruction::UnstakeEarly {
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::request_unstake(
+            &instruction::RequestUnstakeAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::UnbondingAction {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3)
+        },
+        staking_program::instruction::RequestUnstake {
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::cancel_unstake_request(
+            &instruction::CancelUnstakeRequestAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3)
+            },
+            2
+        ),
+        staking_program::accounts::UnbondingAction {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3)
+        },
+        staking_program::instruction::CancelUnstakeRequest { request_index: 2 }
+    );
+    assert_instruction!(
+        instruction::withdraw_unstaked(&instruction::WithdrawUnstakedAccounts {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        }),
+        staking_program::accounts::UnStake {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        },
+        staking_program::instruction::WithdrawUnstaked {}
+    );
+    assert_instruction!(
+        instruction::poke(&instruction::PokeAccounts {
+            pool: key(1),
+            user_stake_info: key(2)
+        }),
+        staking_program::accounts::Poke {
+            pool: key(1),
+            user_stake_info: key(2)
+        },
+        staking_program::instruction::Poke {}
+    );
+    assert_instruction!(
+        instruction::get_pending_rewards(
+            &instruction::GetPendingRewardsAccounts {
+                pool: key(1),
+                user_stake_info: key(2)
+            },
+            key(200)
+        ),
+        staking_program::accounts::UserView {
+            pool: key(1),
+            user_stake_info: key(2)
+        },
+        staking_program::instruction::GetPendingRewards { user: key(200) }
+    );
+    assert_instruction!(
+        instruction::get_user_position(
+            &instruction::GetUserPositionAccounts {
+                pool: key(1),
+                user_stake_info: key(2)
+            },
+            key(200)
+        ),
+        staking_program::accounts::UserView {
+            pool: key(1),
+            user_stake_info: key(2)
+        },
+        staking_program::instruction::GetUserPosition { user: key(200) }
+    );
+    assert_instruction!(
+        instruction::get_pool_state(&instruction::GetPoolStateAccounts {
+            pool: key(1),
+            staking_vault: key(2)
+        }),
+        staking_program::accounts::PoolView {
+            pool: key(1),
+            staking_vault: key(2)
+        },
+        staking_program::instruction::GetPoolState {}
+    );
+    assert_instruction!(
+        instruction::stake_liquid(
+            &instruction::StakeLiquidAccounts {
+                user: key(1),
+                pool: key(2),
+                user_staking_wallet: key(3),
+                user_receipt_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                receipt_mint: key(7),
+                token_program: key(8)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::StakeLiquid {
+            user: key(1),
+            pool: key(2),
+            user_staking_wallet: key(3),
+            user_receipt_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            receipt_mint: key(7),
+            token_program: key(8)
+        },
+        staking_program::instruction::StakeLiquid {
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::unstake_liquid(
+            &instruction::UnstakeLiquidAccounts {
+                user: key(1),
+                pool: key(2),
+                user_staking_wallet: key(3),
+                user_receipt_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                receipt_mint: key(7),
+                reward_vault: Some(key(8)),
+                token_program: key(9)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::UnstakeLiquid {
+            user: key(1),
+            pool: key(2),
+            user_staking_wallet: key(3),
+            user_receipt_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            receipt_mint: key(7),
+            reward_vault: Some(key(8)),
+            token_program: key(9)
+        },
+        staking_program::instruction::UnstakeLiquid {
+            shares: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::unstake_liquid(
+            &instruction::UnstakeLiquidAccounts {
+                user: key(1),
+                pool: key(2),
+                user_staking_wallet: key(3),
+                user_receipt_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                receipt_mint: key(7),
+                reward_vault: None,
+                token_program: key(9)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::UnstakeLiquid {
+            user: key(1),
+            pool: key(2),
+            user_staking_wallet: key(3),
+            user_receipt_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            receipt_mint: key(7),
+            reward_vault: None,
+            token_program: key(9)
+        },
+        staking_program::instruction::UnstakeLiquid {
+            shares: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::compound(&instruction::CompoundAccounts {
+            caller: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            staking_vault: key(4),
+            reward_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        }),
+        staking_program::accounts::Compound {
+            caller: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            staking_vault: key(4),
+            reward_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        },
+        staking_program::instruction::Compound {}
+    );
+    assert_instruction!(
+        instruction::claim_rewards(
+            &instruction::ClaimRewardsAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3),
+                user_reward_wallet: key(4),
+                reward_vault: key(5),
+                reward_mint: key(6),
+                token_program: key(7)
+            },
+            2
+        ),
+        staking_program::accounts::ClaimRewards {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_reward_wallet: key(4),
+            reward_vault: key(5),
+            reward_mint: key(6),
+            token_program: key(7)
+        },
+        staking_program::instruction::ClaimRewards { reward_index: 2 }
+    );
+    assert_instruction!(
+        instruction::close_user_stake_info(&instruction::CloseUserStakeInfoAccounts {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3)
+        }),
+        staking_program::accounts::CloseUserStakeInfo {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3)
+        },
+        staking_program::instruction::CloseUserStakeInfo {}
+    );
+    assert_instruction!(
+        instruction::update_reward_rate(
+            &instruction::UpdateRewardRateAccounts {
+                pool: key(1),
+                rate_manager: key(2)
+            },
+            2,
+            123_456_789
+        ),
+        staking_program::accounts::RateManagerAction {
+            pool: key(1),
+            rate_manager: key(2)
+        },
+        staking_program::instruction::UpdateRewardRate {
+            reward_index: 2,
+            new_rate: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::fund_rewards(
+            &instruction::FundRewardsAccounts {
+                pool: key(1),
+                funder: key(2),
+                funder_wallet: key(3),
+                reward_vault: key(4),
+                reward_mint: key(5),
+                token_program: key(6)
+            },
+            2,
+            123_456_789
+        ),
+        staking_program::accounts::FundRewards {
+            pool: key(1),
+            funder: key(2),
+            funder_wallet: key(3),
+            reward_vault: key(4),
+            reward_mint: key(5),
+            token_program: key(6)
+        },
+        staking_program::instruction::FundRewards {
+            reward_index: 2,
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::notify_reward_amount(
+            &instruction::NotifyRewardAmountAccounts {
+                pool: key(1),
+                funder: key(2),
+                funder_wallet: key(3),
+                reward_vault: key(4),
+                reward_mint: key(5),
+                token_program: key(6)
+            },
+            2,
+            123_456_789,
+            86_400
+        ),
+        staking_program::accounts::FundRewards {
+            pool: key(1),
+            funder: key(2),
+            funder_wallet: key(3),
+            reward_vault: key(4),
+            reward_mint: key(5),
+            token_program: key(6)
+        },
+        staking_program::instruction::NotifyRewardAmount {
+            reward_index: 2,
+            amount: 123_456_789,
+            duration: 86_400
+        }
+    );
+    assert_instruction!(
+        instruction::grant_role(
+            &instruction::GrantRoleAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            state::Role::Funder,
+            key(200)
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::GrantRole {
+            role: staking_program::Role::Funder,
+            account: key(200)
+        }
+    );
+    assert_instruction!(
+        instruction::revoke_role(
+            &instruction::RevokeRoleAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            state::Role::Funder
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::RevokeRole {
+            role: staking_program::Role::Funder
+        }
+    );
+    assert_instruction!(
+        instruction::propose_admin(
+            &instruction::ProposeAdminAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            key(200)
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::ProposeAdmin {
+            new_admin: key(200)
+        }
+    );
+    assert_instruction!(
+        instruction::accept_admin(&instruction::AcceptAdminAccounts {
+            pool: key(1),
+            new_admin: key(2)
+        }),
+        staking_program::accounts::AcceptAdmin {
+            pool: key(1),
+            new_admin: key(2)
+        },
+        staking_program::instruction::AcceptAdmin {}
+    );
+    assert_instruction!(
+        instruction::cancel_admin_transfer(&instruction::CancelAdminTransferAccounts {
+            pool: key(1),
+            admin: key(2)
+        }),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::CancelAdminTransfer {}
+    );
+    assert_instruction!(
+        instruction::emergency_withdraw_staked_tokens(
+            &instruction::EmergencyWithdrawStakedTokensAccounts {
+                pool: key(1),
+                guardian: key(2),
+                staking_vault: key(3),
+                staking_mint: key(4),
+                destination_wallet: key(5),
+                token_program: key(6)
+            },
+            123_456_789
+        ),
+        staking_program::accounts::EmergencyWithdrawStaked {
+            pool: key(1),
+            guardian: key(2),
+            staking_vault: key(3),
+            staking_mint: key(4),
+            destination_wallet: key(5),
+            token_program: key(6)
+        },
+        staking_program::instruction::EmergencyWithdrawStakedTokens {
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::emergency_exit(&instruction::EmergencyExitAccounts {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        }),
+        staking_program::accounts::EmergencyExit {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        },
+        staking_program::instruction::EmergencyExit {}
+    );
+    assert_instruction!(
+        instruction::emergency_unstake(
+            &instruction::EmergencyUnstakeAccounts {
+                user: key(1),
+                pool: key(2),
+                user_stake_info: key(3),
+                user_staking_wallet: key(4),
+                staking_vault: key(5),
+                staking_mint: key(6),
+                token_program: key(7)
+            },
+            true
+        ),
+        staking_program::accounts::EmergencyExit {
+            user: key(1),
+            pool: key(2),
+            user_stake_info: key(3),
+            user_staking_wallet: key(4),
+            staking_vault: key(5),
+            staking_mint: key(6),
+            token_program: key(7)
+        },
+        staking_program::instruction::EmergencyUnstake {
+            forfeit_rewards: true
+        }
+    );
+    assert_instruction!(
+        instruction::emergency_withdraw_reward_tokens(
+            &instruction::EmergencyWithdrawRewardTokensAccounts {
+                pool: key(1),
+                guardian: key(2),
+                reward_mint: key(3),
+                reward_vault: key(4),
+                destination_wallet: key(5),
+                token_program: key(6)
+            },
+            2,
+            123_456_789
+        ),
+        staking_program::accounts::EmergencyWithdrawRewards {
+            pool: key(1),
+            guardian: key(2),
+            reward_mint: key(3),
+            reward_vault: key(4),
+            destination_wallet: key(5),
+            token_program: key(6)
+        },
+        staking_program::instruction::EmergencyWithdrawRewardTokens {
+            reward_index: 2,
+            amount: 123_456_789
+        }
+    );
+    assert_instruction!(
+        instruction::add_reward_stream(
+            &instruction::AddRewardStreamAccounts {
+                pool: key(1),
+                admin: key(2),
+                reward_mint: key(3),
+                reward_vault: key(4),
+                system_program: key(5),
+                token_program: key(6)
+            },
+            1_500
+        ),
+        staking_program::accounts::AddRewardStream {
+            pool: key(1),
+            admin: key(2),
+            reward_mint: key(3),
+            reward_vault: key(4),
+            system_program: key(5),
+            token_program: key(6)
+        },
+        staking_program::instruction::AddRewardStream {
+            allowed_mint_extensions: 1_500
+        }
+    );
+    assert_instruction!(
+        instruction::retire_reward_stream(
+            &instruction::RetireRewardStreamAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            2
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::RetireRewardStream { reward_index: 2 }
+    );
+    assert_instruction!(
+        instruction::enable_receipt_token(&instruction::EnableReceiptTokenAccounts {
+            pool: key(1),
+            admin: key(2),
+            staking_mint: key(3),
+            receipt_mint: key(4),
+            system_program: key(5),
+            token_program: key(6)
+        }),
+        staking_program::accounts::EnableReceiptToken {
+            pool: key(1),
+            admin: key(2),
+            staking_mint: key(3),
+            receipt_mint: key(4),
+            system_program: key(5),
+            token_program: key(6)
+        },
+        staking_program::instruction::EnableReceiptToken {}
+    );
+    assert_instruction!(
+        instruction::set_auto_compound(
+            &instruction::SetAutoCompoundAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            true
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::SetAutoCompound { enabled: true }
+    );
+    assert_instruction!(
+        instruction::pause(&instruction::PauseAccounts {
+            pool: key(1),
+            pauser: key(2)
+        }),
+        staking_program::accounts::PauserAction {
+            pool: key(1),
+            pauser: key(2)
+        },
+        staking_program::instruction::Pause {}
+    );
+    assert_instruction!(
+        instruction::unpause(&instruction::UnpauseAccounts {
+            pool: key(1),
+            admin: key(2)
+        }),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::Unpause {}
+    );
+    assert_instruction!(
+        instruction::update_max_pause_duration(
+            &instruction::UpdateMaxPauseDurationAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            86_400
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateMaxPauseDuration {
+            new_duration: 86_400
+        }
+    );
+    assert_instruction!(
+        instruction::update_lockup_duration(
+            &instruction::UpdateLockupDurationAccounts {
+                pool: key(1),
+                rate_manager: key(2)
+            },
+            86_400
+        ),
+        staking_program::accounts::RateManagerAction {
+            pool: key(1),
+            rate_manager: key(2)
+        },
+        staking_program::instruction::UpdateLockupDuration {
+            new_duration: 86_400
+        }
+    );
+    assert_instruction!(
+        instruction::update_timelock_delay(
+            &instruction::UpdateTimelockDelayAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            86_400
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateTimelockDelay { new_delay: 86_400 }
+    );
+    assert_instruction!(
+        instruction::schedule_change(
+            &instruction::ScheduleChangeAccounts {
+                pool: key(1),
+                queued_change: key(2),
+                admin: key(3),
+                system_program: key(4)
+            },
+            state::TimelockAction::UpdateRewardRate {
+                reward_index: 1,
+                new_rate: 42
+            },
+            86_400
+        ),
+        staking_program::accounts::ScheduleChange {
+            pool: key(1),
+            queued_change: key(2),
+            admin: key(3),
+            system_program: key(4)
+        },
+        staking_program::instruction::ScheduleChange {
+            action: staking_program::TimelockAction::UpdateRewardRate {
+                reward_index: 1,
+                new_rate: 42
+            },
+            eta: 86_400
+        }
+    );
+    assert_instruction!(
+        instruction::execute_change(&instruction::ExecuteChangeAccounts {
+            pool: key(1),
+            queued_change: key(2),
+            rent_payer: key(3)
+        }),
+        staking_program::accounts::ExecuteChange {
+            pool: key(1),
+            queued_change: key(2),
+            rent_payer: key(3)
+        },
+        staking_program::instruction::ExecuteChange {}
+    );
+    assert_instruction!(
+        instruction::cancel_change(&instruction::CancelChangeAccounts {
+            pool: key(1),
+            queued_change: key(2),
+            admin: key(3),
+            rent_payer: key(4)
+        }),
+        staking_program::accounts::CancelChange {
+            pool: key(1),
+            queued_change: key(2),
+            admin: key(3),
+            rent_payer: key(4)
+        },
+        staking_program::instruction::CancelChange {}
+    );
+    assert_instruction!(
+        instruction::update_max_boost(
+            &instruction::UpdateMaxBoostAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            1_500
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateMaxBoost {
+            max_boost_bps: 1_500
+        }
+    );
+    assert_instruction!(
+        instruction::update_early_unstake_penalty(
+            &instruction::UpdateEarlyUnstakePenaltyAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            1_500,
+            true
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateEarlyUnstakePenalty {
+            penalty_bps: 1_500,
+            scale_with_lock: true
+        }
+    );
+    assert_instruction!(
+        instruction::update_penalty_destination(
+            &instruction::UpdatePenaltyDestinationAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            state::PenaltyDestination::Treasury,
+            key(200)
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdatePenaltyDestination {
+            destination: staking_program::PenaltyDestination::Treasury,
+            treasury: key(200)
+        }
+    );
+    assert_instruction!(
+        instruction::update_cooldown_duration(
+            &instruction::UpdateCooldownDurationAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            86_400
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateCooldownDuration {
+            new_duration: 86_400
+        }
+    );
+    assert_instruction!(
+        instruction::update_parameter_bounds(
+            &instruction::UpdateParameterBoundsAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            state::ParameterBounds {
+                min_lockup_duration: 1,
+                max_lockup_duration: 2,
+                max_reward_rate: 3,
+                max_cooldown_duration: 4,
+                max_timelock_delay: 5,
+                max_pause_duration: 6,
+                min_solvency_horizon: 7,
+                max_solvency_horizon: 8,
+                min_reward_duration: 9,
+                max_reward_duration: 10
+            }
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateParameterBounds {
+            bounds: staking_program::ParameterBounds {
+                min_lockup_duration: 1,
+                max_lockup_duration: 2,
+                max_reward_rate: 3,
+                max_cooldown_duration: 4,
+                max_timelock_delay: 5,
+                max_pause_duration: 6,
+                min_solvency_horizon: 7,
+                max_solvency_horizon: 8,
+                min_reward_duration: 9,
+                max_reward_duration: 10
+            }
+        }
+    );
+    assert_instruction!(
+        instruction::update_solvency_horizon(
+            &instruction::UpdateSolvencyHorizonAccounts {
+                pool: key(1),
+                admin: key(2)
+            },
+            86_400
+        ),
+        staking_program::accounts::AdminAction {
+            pool: key(1),
+            admin: key(2)
+        },
+        staking_program::instruction::UpdateSolvencyHorizon {
+            new_horizon: 86_400
+        }
+    );
+}
+
+// 客户端编码的 Pool 必须能被程序解码，且程序重新编码后逐字节一致
+#[test]
+fn pool_layout_matches_program() {
+    let account = state::Pool {
+        admin: key(3),
+        pending_admin: key(4),
+        pauser: key(5),
+        rate_manager: key(6),
+        funder: key(7),
+        guardian: key(8),
+        pool_id: 8,
+        staking_mint: key(10),
+        staking_vault: key(11),
+        staking_mint_extensions: 11,
+        reward_streams: [
+            state::RewardStream {
+                mint: key(15),
+                vault: key(16),
+                reward_rate: 16,
+                reward_per_token_stored: 17,
+                period_finish: -18,
+                vault_balance: 19,
+                total_rewards_owed: 20,
+                is_active: true,
+                mint_extensions: 22,
+            },
+            state::RewardStream {
+                mint: key(25),
+                vault: key(26),
+                reward_rate: 26,
+                reward_per_token_stored: 27,
+                period_finish: -28,
+                vault_balance: 29,
+                total_rewards_owed: 30,
+                is_active: true,
+                mint_extensions: 32,
+            },
+            state::RewardStream {
+                mint: key(35),
+                vault: key(36),
+                reward_rate: 36,
+                reward_per_token_stored: 37,
+                period_finish: -38,
+                vault_balance: 39,
+                total_rewards_owed: 40,
+                is_active: true,
+                mint_extensions: 42,
+            },
+        ],
+        last_update_timestamp: -43,
+        total_staked: 44,
+        total_weighted_stake: 45,
+        total_unbonding: 46,
+        pool_bump: 47,
+        lockup_duration: -48,
+        max_boost_bps: 49,
+        cooldown_duration: -50,
+        solvency_horizon: -51,
+        timelock_delay: -52,
+        next_change_id: 53,
+        bounds: state::ParameterBounds {
+            min_lockup_duration: -55,
+            max_lockup_duration: -56,
+            max_reward_rate: 57,
+            max_cooldown_duration: -58,
+            max_timelock_delay: -59,
+            max_pause_duration: -590,
+            min_solvency_horizon: -591,
+            max_solvency_horizon: -592,
+            min_reward_duration: -593,
+            max_reward_duration: -594,
+        },
+        is_shutdown: false,
+        shutdown_claims: 61,
+        shutdown_balance: 62,
+        is_paused: true,
+        paused_at: -64,
+        max_pause_duration: -65,
+        receipt_mint: key(67),
+        liquid_principal: 67,
+        liquid_rewards: 68,
+        liquid_reward_per_token_paid: [70, 71, 72],
+        auto_compound: true,
+        early_unstake_penalty_bps: 74,
+        penalty_scales_with_lock: true,
+        penalty_destination: state::PenaltyDestination::Redistribute,
+        penalty_treasury: key(78),
+        penalties_burned: 78,
+        penalties_to_treasury: 79,
+        penalties_redistributed: 80,
+    };
+    let data = account.encode();
+    let decoded = staking_program::Pool::try_deserialize(&mut data.as_slice()).unwrap();
+    let mut reencoded = Vec::new();
+    decoded.try_serialize(&mut reencoded).unwrap();
+    assert_eq!(reencoded, data);
+    assert_eq!(state::Pool::decode(&data).unwrap(), account);
+}
+
+// 客户端编码的 UserStakeInfo 必须能被程序解码，且程序重新编码后逐字节一致
+#[test]
+fn user_stake_info_layout_matches_program() {
+    let account = state::UserStakeInfo {
+        pool: key(3),
+        user: key(4),
+        stake_amount: 4,
+        weighted_amount: 5,
+        stake_start_timestamp: -6,
+        lots: vec![
+            state::StakeLot {
+                amount: 9,
+                unlock_timestamp: -10,
+                lock_duration: -11,
+            },
+            state::StakeLot {
+                amount: 13,
+                unlock_timestamp: -14,
+                lock_duration: -15,
+            },
+        ],
+        unbonding: vec![
+            state::UnbondingRequest {
+                amount: 18,
+                available_at: -19,
+            },
+            state::UnbondingRequest {
+                amount: 21,
+                available_at: -22,
+            },
+        ],
+        reward_per_token_paid: [24, 25, 26],
+        rewards: [28, 29, 30],
+    };
+    let data = account.encode();
+    let decoded = staking_program::UserStakeInfo::try_deserialize(&mut data.as_slice()).unwrap();
+    let mut reencoded = Vec::new();
+    decoded.try_serialize(&mut reencoded).unwrap();
+    assert_eq!(reencoded, data);
+    assert_eq!(state::UserStakeInfo::decode(&data).unwrap(), account);
+}
+
+// 客户端编码的 QueuedChange 必须能被程序解码，且程序重新编码后逐字节一致
+#[test]
+fn queued_change_layout_matches_program() {
+    let actions = [
+        state::TimelockAction::UpdateRewardRate {
+            reward_index: 1,
+            new_rate: 42,
+        },
+        state::TimelockAction::UpdateLockupDuration { new_duration: 100 },
+        state::TimelockAction::ProposeAdmin { new_admin: key(4) },
+        state::TimelockAction::UpdateTimelockDelay { new_delay: 86_400 },
+        state::TimelockAction::NotifyRewardAmount {
+            reward_index: 2,
+            amount: 1_000_000,
+            duration: 604_800,
+        },
+        state::TimelockAction::RetireRewardStream { reward_index: 3 },
+        state::TimelockAction::UpdateCooldownDuration { new_duration: 7 },
+        state::TimelockAction::UpdateMaxBoost {
+            max_boost_bps: 25_000,
+        },
+        state::TimelockAction::UpdateEarlyUnstakePenalty {
+            penalty_bps: 500,
+            scale_with_lock: true,
+        },
+        state::TimelockAction::UpdateMaxPauseDuration { new_duration: 60 },
+        state::TimelockAction::UpdateParameterBounds {
+            bounds: state::ParameterBounds {
+                min_lockup_duration: 1,
+                max_lockup_duration: 2,
+                max_reward_rate: 3,
+                max_cooldown_duration: 4,
+                max_timelock_delay: 5,
+                max_pause_duration: 6,
+                min_solvency_horizon: 7,
+                max_solvency_horizon: 8,
+                min_reward_duration: 9,
+                max_reward_duration: 10,
+            },
+        },
+        state::TimelockAction::UpdatePenaltyDestination {
+            destination: state::PenaltyDestination::Treasury,
+            treasury: key(8),
+        },
+        state::TimelockAction::SetAutoCompound { enabled: true },
+    ];
+    for action in actions {
+        let account = state::QueuedChange {
+            pool: key(3),
+            change_id: 3,
+            action,
+            eta: -5,
+            rent_payer: key(7),
+            bump: 7,
+        };
+        let data = account.encode();
+        let decoded = staking_program::QueuedChange::try_deserialize(&mut data.as_slice()).unwrap();
+        let mut reencoded = Vec::new();
+        decoded.try_serialize(&mut reencoded).unwrap();
+        assert_eq!(reencoded, data);
+        assert_eq!(state::QueuedChange::decode(&data).unwrap(), account);
+    }
+}
+
+#[test]
+fn discriminators_match_program() {
+    assert_eq!(
+        state::Pool::DISCRIMINATOR,
+        staking_program::Pool::DISCRIMINATOR
+    );
+    assert_eq!(
+        state::UserStakeInfo::DISCRIMINATOR,
+        staking_program::UserStakeInfo::DISCRIMINATOR
+    );
+    assert_eq!(
+        state::QueuedChange::DISCRIMINATOR,
+        staking_program::QueuedChange::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::AddRewardStreamEvent::DISCRIMINATOR,
+        staking_program::AddRewardStreamEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::AdminTransferAcceptedEvent::DISCRIMINATOR,
+        staking_program::AdminTransferAcceptedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::AdminTransferCancelledEvent::DISCRIMINATOR,
+        staking_program::AdminTransferCancelledEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::AdminTransferProposedEvent::DISCRIMINATOR,
+        staking_program::AdminTransferProposedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::ChangeCancelledEvent::DISCRIMINATOR,
+        staking_program::ChangeCancelledEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::ChangeExecutedEvent::DISCRIMINATOR,
+        staking_program::ChangeExecutedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::ChangeScheduledEvent::DISCRIMINATOR,
+        staking_program::ChangeScheduledEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::ClaimEvent::DISCRIMINATOR,
+        staking_program::ClaimEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::CompoundEvent::DISCRIMINATOR,
+        staking_program::CompoundEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::EarlyUnstakeEvent::DISCRIMINATOR,
+        staking_program::EarlyUnstakeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::EmergencyExitEvent::DISCRIMINATOR,
+        staking_program::EmergencyExitEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::EmergencyUnstakeEvent::DISCRIMINATOR,
+        staking_program::EmergencyUnstakeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::EnableReceiptTokenEvent::DISCRIMINATOR,
+        staking_program::EnableReceiptTokenEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::FundRewardsEvent::DISCRIMINATOR,
+        staking_program::FundRewardsEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::InitializePoolEvent::DISCRIMINATOR,
+        staking_program::InitializePoolEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::LiquidStakeEvent::DISCRIMINATOR,
+        staking_program::LiquidStakeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::LiquidUnstakeEvent::DISCRIMINATOR,
+        staking_program::LiquidUnstakeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::NotifyRewardAmountEvent::DISCRIMINATOR,
+        staking_program::NotifyRewardAmountEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::PauseEvent::DISCRIMINATOR,
+        staking_program::PauseEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::PenaltyBurnedEvent::DISCRIMINATOR,
+        staking_program::PenaltyBurnedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::PenaltyRedistributedEvent::DISCRIMINATOR,
+        staking_program::PenaltyRedistributedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::PenaltyToTreasuryEvent::DISCRIMINATOR,
+        staking_program::PenaltyToTreasuryEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::PokeEvent::DISCRIMINATOR,
+        staking_program::PokeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::RetireRewardStreamEvent::DISCRIMINATOR,
+        staking_program::RetireRewardStreamEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::RoleGrantedEvent::DISCRIMINATOR,
+        staking_program::RoleGrantedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::RoleRevokedEvent::DISCRIMINATOR,
+        staking_program::RoleRevokedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::SetAutoCompoundEvent::DISCRIMINATOR,
+        staking_program::SetAutoCompoundEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::ShutdownEvent::DISCRIMINATOR,
+        staking_program::ShutdownEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::StakeEvent::DISCRIMINATOR,
+        staking_program::StakeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UnpauseEvent::DISCRIMINATOR,
+        staking_program::UnpauseEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UnstakeEvent::DISCRIMINATOR,
+        staking_program::UnstakeEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UnstakeRequestCancelledEvent::DISCRIMINATOR,
+        staking_program::UnstakeRequestCancelledEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UnstakeRequestedEvent::DISCRIMINATOR,
+        staking_program::UnstakeRequestedEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UnstakedWithdrawnEvent::DISCRIMINATOR,
+        staking_program::UnstakedWithdrawnEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateCooldownDurationEvent::DISCRIMINATOR,
+        staking_program::UpdateCooldownDurationEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateEarlyUnstakePenaltyEvent::DISCRIMINATOR,
+        staking_program::UpdateEarlyUnstakePenaltyEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateLockupDurationEvent::DISCRIMINATOR,
+        staking_program::UpdateLockupDurationEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateMaxBoostEvent::DISCRIMINATOR,
+        staking_program::UpdateMaxBoostEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateMaxPauseDurationEvent::DISCRIMINATOR,
+        staking_program::UpdateMaxPauseDurationEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateParameterBoundsEvent::DISCRIMINATOR,
+        staking_program::UpdateParameterBoundsEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdatePenaltyDestinationEvent::DISCRIMINATOR,
+        staking_program::UpdatePenaltyDestinationEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateRewardRateEvent::DISCRIMINATOR,
+        staking_program::UpdateRewardRateEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateSolvencyHorizonEvent::DISCRIMINATOR,
+        staking_program::UpdateSolvencyHorizonEvent::DISCRIMINATOR
+    );
+    assert_eq!(
+        events::UpdateTimelockDelayEvent::DISCRIMINATOR,
+        staking_program::UpdateTimelockDelayEvent::DISCRIMINATOR
+    );
+}
+
+// 只解析本程序输出的事件，CPI 调用的其他程序输出的数据被忽略
+#[test]
+fn parses_events_from_logs() {
+    let event = staking_program::StakeEvent {
+        pool: key(1),
+        user: key(2),
+        amount: 1_000,
+        net_amount: 990,
+        unlock_timestamp: 1_700_000_000,
+    };
+    let data = format!("Program data: {}", STANDARD.encode(event.data()));
+    let other = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+    let logs = vec![
+        format!("Program {} invoke [1]", staking_program::ID),
+        "Program log: Instruction: Stake".to_string(),
+        format!("Program {other} invoke [2]"),
+        data.clone(),
+        format!("Program {other} success"),
+        data,
+        format!("Program {} success", staking_program::ID),
+    ];
+    assert_eq!(
+        events::parse_logs(&logs).unwrap(),
+        vec![StakingEvent::Stake(events::StakeEvent {
+            pool: key(1),
+            user: key(2),
+            amount: 1_000,
+            net_amount: 990,
+            unlock_timestamp: 1_700_000_000,
+        })]
+    );
+}
@@ -0,0 +1,310 @@
+// 离线奖励预测与链上程序逐位一致：同一状态、同一操作序列下，客户端和程序的账户编码完全相同
+
+use anchor_lang::{AccountDeserialize, AccountSerialize};
+use solana_program::pubkey::Pubkey;
+use staking_program_client::projection::{
+    apy, Projection, ProjectionError, TransferFee, SECONDS_PER_YEAR,
+};
+use staking_program_client::state::{
+    ParameterBounds, PenaltyDestination, Pool, ProgramAccount, RewardStream, UserStakeInfo,
+    MAX_BOOST_BPS, MAX_REWARD_STREAMS, MIN_BOOST_LOCK,
+};
+
+const START: i64 = 1_700_000_000;
+const DAY: i64 = 24 * 60 * 60;
+
+fn key(n: u8) -> Pubkey {
+    Pubkey::new_from_array([n; 32])
+}
+
+// 测试用的奖励流：有限期、已注资
+fn stream(mint: Pubkey, reward_rate: u64, vault_balance: u64, period_finish: i64) -> RewardStream {
+    RewardStream {
+        mint,
+        vault: key(90),
+        reward_rate,
+        period_finish,
+        vault_balance,
+        is_active: true,
+        ..Default::default()
+    }
+}
+
+// 测试用的池子：第 0 条奖励流发放质押代币，带收据代币份额桶
+fn pool(auto_compound: bool) -> Pool {
+    let staking_mint = key(1);
+    Pool {
+        admin: key(2),
+        pending_admin: Pubkey::default(),
+        pauser: Pubkey::default(),
+        rate_manager: Pubkey::default(),
+        funder: Pubkey::default(),
+        guardian: Pubkey::default(),
+        pool_id: 1,
+        staking_mint,
+        staking_vault: key(3),
+        staking_mint_extensions: 0,
+        reward_streams: [
+            stream(staking_mint, 7, 50_000_000, START + 40 * DAY),
+            stream(key(4), 1_000_003, 2_000_000_000_000, START + 400 * DAY),
+            RewardStream::default(),
+        ],
+        last_update_timestamp: START,
+        total_staked: 5_000,
+        total_weighted_stake: 5_000,
+        total_unbonding: 0,
+        pool_bump: 255,
+        lockup_duration: 3 * DAY,
+        max_boost_bps: MAX_BOOST_BPS,
+        cooldown_duration: 0,
+        solvency_horizon: 7 * DAY,
+        timelock_delay: 0,
+        next_change_id: 0,
+        bounds: ParameterBounds::default(),
+        is_shutdown: false,
+        shutdown_claims: 0,
+        shutdown_balance: 0,
+        is_paused: false,
+        paused_at: 0,
+        max_pause_duration: 0,
+        receipt_mint: key(5),
+        liquid_principal: 5_000,
+        liquid_rewards: 0,
+        liquid_reward_per_token_paid: [0; MAX_REWARD_STREAMS],
+        auto_compound,
+        early_unstake_penalty_bps: 0,
+        penalty_scales_with_lock: false,
+        penalty_destination: PenaltyDestination::Burn,
+        penalty_treasury: Pubkey::default(),
+        penalties_burned: 0,
+        penalties_to_treasury: 0,
+        penalties_redistributed: 0,
+    }
+}
+
+// 转换为程序的账户类型
+fn onchain<A: ProgramAccount, T: AccountDeserialize>(account: &A) -> T {
+    T::try_deserialize(&mut account.encode().as_slice()).unwrap()
+}
+
+// 程序账户的编码必须与客户端账户的编码逐字节一致
+fn assert_same<A: ProgramAccount, T: AccountSerialize>(client: &A, program: &T) {
+    let mut data = Vec::new();
+    program.try_serialize(&mut data).unwrap();
+    assert_eq!(data, client.encode());
+}
+
+// 确定性伪随机数（线性同余）
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) % bound
+    }
+}
+
+// 随机的质押、取消质押、领取、poke 和时间推进序列，每一步后比较池子和所有用户的编码。
+// 程序一侧直接调用各指令处理函数使用的 Pool::record_* 记账方法
+fn run_sequence(seed: u64, auto_compound: bool, fee: TransferFee) {
+    let mut rng = Lcg(seed);
+    let users = [key(10), key(11), key(12)];
+    let mut projection = Projection::new(pool(auto_compound), START).with_transfer_fee(fee);
+    let mut program_pool: staking_program::Pool = onchain(&projection.pool);
+    let mut program_users: Vec<staking_program::UserStakeInfo> = users
+        .iter()
+        .map(|user| staking_program::UserStakeInfo {
+            user: *user,
+            ..Default::default()
+        })
+        .collect();
+
+    for _ in 0..300 {
+        projection
+            .advance_by(rng.next(2 * DAY as u64) as i64)
+            .unwrap();
+        let now = projection.now;
+        let slot = rng.next(users.len() as u64) as usize;
+        let user = users[slot];
+        let info = &mut program_users[slot];
+        match rng.next(4) {
+            0 => {
+                let amount = 1 + rng.next(1_000_000);
+                let lock_duration = match rng.next(3) {
+                    0 => 0,
+                    1 => MIN_BOOST_LOCK,
+                    _ => MIN_BOOST_LOCK + rng.next(200 * DAY as u64) as i64,
+                };
+                match projection.stake(user, amount, lock_duration) {
+                    Ok(net_amount) => {
+                        assert_eq!(net_amount, fee.amount_after_fee(amount).unwrap());
+                        program_pool
+                            .record_stake(info, net_amount, lock_duration, now)
+                            .unwrap();
+                    }
+                    Err(err) => assert!(matches!(
+                        err,
+                        ProjectionError::TooManyStakeLots | ProjectionError::ZeroAmount
+                    )),
+                }
+            }
+            1 => {
+                let unlocked = info.stake_amount - info.locked_amount(now);
+                if unlocked > 0 {
+                    let amount = 1 + rng.next(unlocked);
+                    let remaining_locked = projection.unstake(user, amount).unwrap();
+                    assert_eq!(
+                        remaining_locked,
+                        program_pool.record_unstake(info, amount, now).unwrap()
+                    );
+                }
+            }
+            2 if info.stake_amount > 0 => {
+                let index = rng.next(2) as usize;
+                let claimed = projection.claim(user, index).unwrap();
+                assert_eq!(
+                    claimed,
+                    program_pool.record_claim(info, index, now).unwrap()
+                );
+            }
+            _ if info.stake_amount > 0 => {
+                projection.poke(user).unwrap();
+                program_pool.record_poke(info, now).unwrap();
+            }
+            _ => {}
+        }
+
+        assert_same(&projection.pool, &program_pool);
+        for (user, info) in users.iter().zip(&program_users) {
+            if let Some(client_info) = projection.users.get(user) {
+                assert_same(client_info, info);
+            }
+        }
+    }
+}
+
+#[test]
+fn stake_unstake_sequences_match_program() {
+    for seed in 0..8 {
+        run_sequence(seed, seed % 2 == 1, TransferFee::default());
+    }
+}
+
+// 质押代币带转账手续费时，模拟只记入净额，与程序按净额记账的结果一致
+#[test]
+fn stake_sequences_with_transfer_fee_match_program() {
+    let fee = TransferFee {
+        basis_points: 250,
+        maximum_fee: 5_000,
+    };
+    for seed in 8..12 {
+        run_sequence(seed, seed % 2 == 1, fee);
+    }
+}
+
+// 手续费计算与 Token-2022 一致
+#[test]
+fn transfer_fee_matches_token_2022() {
+    let mut rng = Lcg(42);
+    for _ in 0..1_000 {
+        let fee = TransferFee {
+            basis_points: rng.next(10_001) as u16,
+            maximum_fee: rng.next(u64::MAX),
+        };
+        let token_fee = spl_token_2022::extension::transfer_fee::TransferFee {
+            transfer_fee_basis_points: fee.basis_points.into(),
+            maximum_fee: fee.maximum_fee.into(),
+            ..Default::default()
+        };
+        let amount = match rng.next(3) {
+            0 => rng.next(100),
+            1 => rng.next(u64::MAX),
+            _ => u64::MAX - rng.next(100),
+        };
+        assert_eq!(fee.calculate_fee(amount), token_fee.calculate_fee(amount));
+        assert_eq!(
+            fee.amount_after_fee(amount),
+            token_fee.calculate_post_fee_amount(amount)
+        );
+    }
+}
+
+// 跨越奖励周期结束、金库余额耗尽的长时间推进与程序结果一致
+#[test]
+fn time_travel_matches_program() {
+    let mut client = pool(true);
+    client.reward_streams[0].vault_balance = 1_000;
+    let mut info = UserStakeInfo {
+        user: key(10),
+        stake_amount: 777,
+        weighted_amount: 1_333,
+        ..Default::default()
+    };
+    client.total_weighted_stake += info.weighted_amount;
+    let mut program_pool: staking_program::Pool = onchain(&client);
+    let mut program_info: staking_program::UserStakeInfo = onchain(&info);
+
+    for now in [
+        START,
+        START + 1,
+        START + DAY,
+        START + 41 * DAY,
+        START + 10 * 365 * DAY,
+    ] {
+        client.update_rewards_at(now, Some(&mut info)).unwrap();
+        program_pool
+            .update_rewards_at(now, Some(&mut program_info))
+            .unwrap();
+        assert_same(&client, &program_pool);
+        assert_same(&info, &program_info);
+    }
+    // 奖励负债不超过金库余额
+    assert!(client.reward_streams[0].total_rewards_owed <= 1_000);
+}
+
+// 30 天后的待领奖励：单个用户独占奖励流时等于奖励率乘以时长，且不超过金库余额
+#[test]
+fn projects_pending_rewards_forward() {
+    let mut pool = pool(false);
+    pool.total_staked = 0;
+    pool.total_weighted_stake = 0;
+    pool.liquid_principal = 0;
+    pool.lockup_duration = 0;
+    let mut projection = Projection::new(pool, START);
+    projection.stake(key(10), 1_000, 0).unwrap();
+    projection.advance_by(30 * DAY).unwrap();
+
+    let pending = projection.pending_rewards(key(10)).unwrap();
+    assert_eq!(pending[0], 7 * 30 * DAY as u64);
+    // 第 1 条奖励流的发放以金库余额为上限
+    assert_eq!(pending[1], 2_000_000_000_000);
+    // 查询不修改模拟状态
+    assert_eq!(projection.pool.last_update_timestamp, START);
+    assert_eq!(
+        projection.advance_to(START),
+        Err(ProjectionError::TimeWentBackwards)
+    );
+}
+
+#[test]
+fn computes_apr_and_apy() {
+    let pool = pool(false);
+    let apr = pool.reward_apr(0, START).unwrap();
+    assert_eq!(apr, 7.0 * SECONDS_PER_YEAR as f64 / 5_000.0);
+    assert_eq!(pool.reward_apr(0, START + 40 * DAY), Some(0.0));
+    assert_eq!(pool.reward_apr(MAX_REWARD_STREAMS, START), None);
+
+    // 加权倍数为 2x 的用户获得两倍的年化奖励率
+    let info = UserStakeInfo {
+        stake_amount: 100,
+        weighted_amount: 200,
+        ..Default::default()
+    };
+    assert_eq!(info.reward_apr(&pool, 0, START), Some(apr * 2.0));
+
+    assert_eq!(apy(0.1, 0), 0.1);
+    assert!((apy(0.1, 365) - 0.105_155_781_6).abs() < 1e-9);
+}
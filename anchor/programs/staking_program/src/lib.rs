@@ -3,57 +3,127 @@
 
 // 引入 Anchor 框架核心模块，用于账户管理、上下文处理等
 use anchor_lang::prelude::*;
-// 引入 Anchor SPL token 模块，支持代币相关操作
-use anchor_spl::{
-    token_2022::{transfer_checked, Burn, Transfer, TransferChecked},
-    token_interface::{Mint, Token2022, TokenAccount},
+// 引入 Anchor SPL token 接口模块，同时支持 SPL Token 和 Token-2022
+use anchor_spl::token_interface::{
+    spl_token_2022::{
+        self,
+        extension::{
+            default_account_state::DefaultAccountState, transfer_fee::TransferFeeConfig,
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
+        state::AccountState,
+    },
+    Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
 };
+// 引入与客户端共用的奖励结算和质押批次记账
+use staking_math::{LiquidRewardLedger, Lot, LotError, RewardLedger};
 // 引入 TryInto 用于类型转换
 use std::convert::TryInto;
 
 // 声明程序 ID，需替换为实际部署时的程序 ID
 declare_id!("GjQvMVAgqV8UJmBdMxv2o6B3kNj7fZvw6LBctkQdFK7r");
 
+// 构造池子 PDA 的签名种子：[b"pool", 质押代币 Mint, pool_id, bump]
+macro_rules! pool_signer_seeds {
+    ($pool:expr) => {
+        &[
+            b"pool".as_ref(),
+            $pool.staking_mint.as_ref(),
+            &$pool.pool_id.to_le_bytes(),
+            &[$pool.pool_bump],
+        ]
+    };
+}
+
 // 定义 Anchor 程序模块，包含所有指令实现
 #[program]
 pub mod staking_program {
     use super::*;
-    use anchor_spl::token_2022;
+    use anchor_spl::token_interface;
 
-    // 初始化质押池，仅管理员可调用一次，设置池子参数
+    // 初始化质押池，每个 (质押代币, pool_id) 组合只能初始化一次，设置池子参数；
+    // allowed_mint_extensions 为管理员明确接受的危险 Mint 扩展（MINT_EXT_* 位掩码）
     pub fn initialize(
         ctx: Context<Initialize>,
-        reward_rate: u64,
+        pool_id: u64,
         lockup_duration: i64,
+        allowed_mint_extensions: u16,
     ) -> Result<()> {
+        let bounds = ParameterBounds::default(); // 新池子使用默认参数边界
+        bounds.check_lockup_duration(lockup_duration)?; // 校验锁定期在边界内
+        bounds.check_solvency_horizon(DEFAULT_SOLVENCY_HORIZON)?; // 校验默认偿付能力检查期限
+        bounds.check_max_pause_duration(0)?; // 新池子默认不限制暂停时间
+
+        // 检查质押代币和奖励代币的扩展，未经允许的危险扩展直接拒绝
+        let staking_mint_extensions =
+            validate_mint_extensions(&ctx.accounts.staking_mint, allowed_mint_extensions)?;
+        let reward_mint_extensions =
+            validate_mint_extensions(&ctx.accounts.reward_mint, allowed_mint_extensions)?;
+
         let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
         pool.admin = ctx.accounts.admin.key(); // 设置管理员公钥
+        pool.pool_id = pool_id; // 设置池子编号，参与 PDA 派生
         pool.staking_mint = ctx.accounts.staking_mint.key(); // 设置质押代币 Mint 地址
         pool.staking_vault = ctx.accounts.staking_vault.key(); // 设置质押金库地址
-        pool.reward_mint = ctx.accounts.reward_mint.key(); // 设置奖励代币 Mint 地址
-        pool.reward_vault = ctx.accounts.reward_vault.key(); // 设置奖励金库地址
-        pool.reward_rate = reward_rate; // 设置每秒奖励代币数量
+        pool.staking_mint_extensions = staking_mint_extensions; // 记录质押代币已接受的扩展
+                                                                // 初始奖励流占用第 0 个槽位，奖励周期由 notify_reward_amount 开启
+        pool.reward_streams[0] = RewardStream {
+            mint: ctx.accounts.reward_mint.key(),   // 设置奖励代币 Mint 地址
+            vault: ctx.accounts.reward_vault.key(), // 设置奖励金库地址
+            reward_rate: 0,                         // 注资前不发放奖励
+            reward_per_token_stored: 0,             // 初始化每单位代币累计奖励为 0
+            period_finish: 0,                       // 尚无奖励周期
+            vault_balance: 0,                       // 金库账本余额为 0
+            total_rewards_owed: 0,                  // 尚无已累积未领取的奖励
+            is_active: true,                        // 奖励流处于发放状态
+            mint_extensions: reward_mint_extensions, // 记录奖励代币已接受的扩展
+        };
         pool.last_update_timestamp = Clock::get()?.unix_timestamp; // 设置当前区块链时间戳
         pool.total_staked = 0; // 初始化总质押量为 0
-        pool.reward_per_token_stored = 0; // 初始化每单位代币累计奖励为 0
+        pool.total_weighted_stake = 0; // 初始化总加权质押量为 0
+        pool.max_boost_bps = BOOST_BASE_BPS; // 默认不加速
         pool.pool_bump = ctx.bumps.pool; // 保存池子 PDA bump 值，用于签名验证
         pool.lockup_duration = lockup_duration; // 设置锁定期（秒）
+        pool.solvency_horizon = DEFAULT_SOLVENCY_HORIZON; // 设置默认偿付能力检查期限
+        pool.bounds = bounds; // 设置参数边界
         pool.is_paused = false; // 初始化程序为未暂停状态
+
+        emit!(InitializePoolEvent {
+            pool: pool.key(),
+            pool_id,
+            staking_mint: pool.staking_mint,
+            reward_mint: ctx.accounts.reward_mint.key(),
+            staking_mint_extensions,
+            reward_mint_extensions,
+        }); // 触发池子初始化事件
         Ok(()) // 返回成功
     }
 
-    // 用户质押代币，将代币转入金库并更新状态
-    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
-        require!(!ctx.accounts.pool.is_paused, StakingError::ProgramPaused); // 确保程序未暂停
+    // 用户质押代币，将代币转入金库并更新状态；lock_duration 为可选的加速锁定期（0 表示不加速）
+    pub fn stake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Stake<'info>>,
+        amount: u64,
+        lock_duration: i64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
         require_gt!(amount, 0, StakingError::ZeroStakeAmount); // 确保质押金额大于 0
+        require!(
+            lock_duration == 0 || (MIN_BOOST_LOCK..=MAX_BOOST_LOCK).contains(&lock_duration),
+            StakingError::InvalidLockDuration
+        ); // 加速锁定期需在 1 周到 4 年之间
 
         let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
         let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
 
-        pool.update_rewards(Some(user_stake_info))?; // 更新全局和用户奖励，确保状态同步
+        user_stake_info.pool = pool.key(); // 记录所属池子，便于客户端按池子筛选
+        user_stake_info.user = ctx.accounts.user.key(); // 记录所属用户
 
         // 执行代币转账：从用户钱包到质押金库
-        token_2022::transfer_checked(
+        transfer_checked_with_hook(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(), // 指定 SPL Token 程序
                 TransferChecked {
@@ -62,64 +132,62 @@ pub mod staking_program {
                     to: ctx.accounts.staking_vault.to_account_info(), // 质押金库
                     authority: ctx.accounts.user.to_account_info(),   // 用户签名作为权限
                 },
-            ),
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
             amount,                             // 转账金额
             ctx.accounts.staking_mint.decimals, // 关键：传入代币的小数位数
         )?;
 
-        // 如果用户首次质押，记录开始时间
-        if user_stake_info.stake_amount == 0 {
-            user_stake_info.stake_start_timestamp = Clock::get()?.unix_timestamp;
-        }
-        // 更新用户质押金额，防止溢出
-        user_stake_info.stake_amount = user_stake_info
-            .stake_amount
-            .checked_add(amount)
-            .ok_or(StakingError::ArithmeticOverflow)?;
-        // 更新池子总质押量，防止溢出
-        pool.total_staked = pool
-            .total_staked
-            .checked_add(amount)
-            .ok_or(StakingError::ArithmeticOverflow)?;
+        // 只记入金库实际收到的金额（扣除 Token-2022 转账手续费）
+        let net_amount = amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?;
+        require_gt!(net_amount, 0, StakingError::ZeroStakeAmount);
 
-        // 触发质押事件，记录用户公钥和金额
+        // 结算奖励并记录新的质押批次
+        let current_time = Clock::get()?.unix_timestamp;
+        let unlock_timestamp =
+            pool.record_stake(user_stake_info, net_amount, lock_duration, current_time)?;
+
+        // 触发质押事件，记录用户公钥、转账金额和实际记入的金额
         emit!(StakeEvent {
+            pool: pool.key(),
             user: *ctx.accounts.user.key,
-            amount
+            amount,
+            net_amount,
+            unlock_timestamp
         });
 
         Ok(()) // 返回成功
     }
 
-    // 用户取消质押，提取代币，需满足锁定期
-    pub fn unstake(ctx: Context<UnStake>, amount: u64) -> Result<()> {
-        require!(!ctx.accounts.pool.is_paused, StakingError::ProgramPaused); // 确保程序未暂停
+    // 用户取消质押，按先进先出顺序仅提取已解锁的质押批次
+    pub fn unstake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnStake<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
         require_gt!(amount, 0, StakingError::ZeroUnstakeAmount); // 确保取消质押金额大于 0
+        require_eq!(
+            ctx.accounts.pool.cooldown_duration,
+            0,
+            StakingError::CooldownRequired
+        ); // 设置了冷却期的池子必须走 request_unstake / withdraw_unstaked 两阶段退出
 
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
         let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
-        require_gte!(
-            user_stake_info.stake_amount,
-            amount,
-            StakingError::InsufficientStakeAmount
-        ); // 确保质押金额足够
-
         let current_time = Clock::get()?.unix_timestamp; // 获取当前区块链时间
-                                                         // 确保质押时间超过锁定期
-        require_gte!(
-            current_time,
-            user_stake_info.stake_start_timestamp + ctx.accounts.pool.lockup_duration,
-            StakingError::LockupPeriodNotEnded
-        );
-
-        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
-        pool.update_rewards(Some(user_stake_info))?; // 更新奖励，确保状态同步
+                                                         // 从已解锁的批次中扣减并结算奖励，已解锁总额不足时报错
+        let remaining_locked = pool.record_unstake(user_stake_info, amount, current_time)?;
 
         // 设置 PDA 签名种子，用于金库转账授权
-        let pool_seeds = &[b"pool".as_ref(), &[pool.pool_bump]];
+        let pool_seeds = pool_signer_seeds!(pool);
         let signer = &[&pool_seeds[..]];
 
         // 执行代币转账：从质押金库到用户钱包
-        token_2022::transfer_checked(
+        transfer_checked_with_hook(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 TransferChecked {
@@ -129,525 +197,3861 @@ pub mod staking_program {
                     authority: pool.to_account_info(),                      // 池子 PDA 作为权限
                 },
                 signer,
-            ),
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
             amount,
             ctx.accounts.staking_mint.decimals,
         )?;
 
-        // 更新用户质押金额，防止溢出
+        // 触发取消质押事件，附带用户实际收到的金额和仍处于锁定期的金额
+        emit!(UnstakeEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            amount,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?,
+            remaining_locked
+        });
+
+        Ok(()) // 返回成功
+    }
+
+    // 用户提前取消质押：先提取已解锁批次，不足部分从最早解锁的锁定批次中扣减并支付罚金，
+    // 罚金按池子配置销毁、转入国库或分配给剩余质押者
+    pub fn unstake_early<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnstakeEarly<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
+        require_gt!(amount, 0, StakingError::ZeroUnstakeAmount); // 确保取消质押金额大于 0
+        require_eq!(
+            ctx.accounts.pool.cooldown_duration,
+            0,
+            StakingError::CooldownRequired
+        ); // 设置了冷却期的池子必须走两阶段退出
+        require_gt!(
+            ctx.accounts.pool.early_unstake_penalty_bps,
+            0,
+            StakingError::EarlyUnstakeDisabled
+        ); // 池子未配置罚金时不允许提前退出
+
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+        pool.update_rewards(Some(user_stake_info))?; // 按原质押量结算奖励
+
+        // 扣减质押批次并计算罚金
+        let current_time = pool.last_update_timestamp;
+        let penalty = user_stake_info.consume_lots_early(
+            amount,
+            current_time,
+            pool.early_unstake_penalty_bps,
+            pool.penalty_scales_with_lock,
+        )?;
+        let payout = amount
+            .checked_sub(penalty)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+
+        // 更新用户和池子的质押量
         user_stake_info.stake_amount = user_stake_info
             .stake_amount
             .checked_sub(amount)
             .ok_or(StakingError::ArithmeticOverflow)?;
-        // 更新池子总质押量，防止溢出
         pool.total_staked = pool
             .total_staked
             .checked_sub(amount)
             .ok_or(StakingError::ArithmeticOverflow)?;
-
-        // 如果质押金额为 0，重置开始时间
+        pool.sync_weight(user_stake_info, current_time)?; // 重新计算加权质押量
         if user_stake_info.stake_amount == 0 {
             user_stake_info.stake_start_timestamp = 0;
         }
 
-        // 触发取消质押事件
-        emit!(UnstakeEvent {
+        // 按罚金去向记账；分配给剩余质押者时，罚金通过质押代币奖励流的 reward_per_token_stored 分发
+        let destination = pool.penalty_destination;
+        let mut penalty_vault = None;
+        if penalty > 0 {
+            match destination {
+                PenaltyDestination::Burn => {
+                    pool.penalties_burned = pool
+                        .penalties_burned
+                        .checked_add(penalty)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                }
+                PenaltyDestination::Treasury => {
+                    let treasury = ctx
+                        .accounts
+                        .treasury
+                        .as_ref()
+                        .ok_or(StakingError::TreasuryMismatch)?;
+                    require_keys_eq!(
+                        treasury.key(),
+                        pool.penalty_treasury,
+                        StakingError::TreasuryMismatch
+                    );
+                    penalty_vault = Some(treasury.to_account_info());
+                    pool.penalties_to_treasury = pool
+                        .penalties_to_treasury
+                        .checked_add(penalty)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                }
+                PenaltyDestination::Redistribute => {
+                    let reward_vault = ctx
+                        .accounts
+                        .reward_vault
+                        .as_ref()
+                        .ok_or(StakingError::RewardVaultMismatch)?;
+                    let index = pool.compound_stream_index()?;
+                    pool.reward_stream_for(index as u8, &reward_vault.key(), &pool.staking_mint)?;
+                    penalty_vault = Some(reward_vault.to_account_info());
+                    // 奖励金库只收到扣除转账手续费后的罚金
+                    let net_penalty =
+                        amount_after_transfer_fee(&ctx.accounts.staking_mint, penalty)?;
+                    pool.distribute_penalty(index, net_penalty)?;
+                    pool.penalties_redistributed = pool
+                        .penalties_redistributed
+                        .checked_add(penalty)
+                        .ok_or(StakingError::ArithmeticOverflow)?;
+                }
+            }
+        }
+
+        // 设置 PDA 签名种子，用于金库转账授权
+        let pool_seeds = pool_signer_seeds!(pool);
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：扣除罚金后的金额从质押金库到用户钱包
+        if payout > 0 {
+            transfer_checked_with_hook(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                        mint: ctx.accounts.staking_mint.to_account_info(),
+                        to: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
+                        authority: pool.to_account_info(),                      // 池子 PDA 作为权限
+                    },
+                    signer,
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                payout,
+                ctx.accounts.staking_mint.decimals,
+            )?;
+        }
+
+        // 处理罚金：销毁，或转入国库 / 质押代币奖励金库
+        if let Some(to) = penalty_vault {
+            transfer_checked_with_hook(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                        mint: ctx.accounts.staking_mint.to_account_info(),
+                        to,                                // 国库或奖励金库
+                        authority: pool.to_account_info(), // 池子 PDA 作为权限
+                    },
+                    signer,
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                penalty,
+                ctx.accounts.staking_mint.decimals,
+            )?;
+        } else if penalty > 0 {
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: ctx.accounts.staking_mint.to_account_info(), // 质押代币 Mint
+                        from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                        authority: pool.to_account_info(),                 // 池子 PDA 作为权限
+                    },
+                    signer,
+                ),
+                penalty,
+            )?;
+        }
+
+        if penalty > 0 {
+            match destination {
+                PenaltyDestination::Burn => emit!(PenaltyBurnedEvent {
+                    pool: pool.key(),
+                    amount: penalty
+                }),
+                PenaltyDestination::Treasury => emit!(PenaltyToTreasuryEvent {
+                    pool: pool.key(),
+                    treasury: pool.penalty_treasury,
+                    amount: penalty
+                }),
+                PenaltyDestination::Redistribute => emit!(PenaltyRedistributedEvent {
+                    pool: pool.key(),
+                    amount: penalty
+                }),
+            } // 触发罚金去向事件
+        }
+        emit!(EarlyUnstakeEvent {
+            pool: pool.key(),
             user: *ctx.accounts.user.key,
-            amount
+            amount,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.staking_mint, payout)?,
+            penalty
+        }); // 触发提前取消质押事件
+
+        Ok(()) // 返回成功
+    }
+
+    // 用户发起取消质押请求：金额立即停止计息，冷却期结束后可提取
+    pub fn request_unstake(ctx: Context<UnbondingAction>, amount: u64) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
+        require_gt!(amount, 0, StakingError::ZeroUnstakeAmount); // 确保取消质押金额大于 0
+        require_gt!(
+            ctx.accounts.pool.cooldown_duration,
+            0,
+            StakingError::CooldownNotEnabled
+        ); // 未设置冷却期的池子直接使用 unstake
+
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+        require_gte!(
+            user_stake_info.stake_amount,
+            amount,
+            StakingError::InsufficientStakeAmount
+        ); // 确保质押金额足够
+
+        let current_time = Clock::get()?.unix_timestamp; // 获取当前区块链时间
+        user_stake_info.consume_unlocked_lots(amount, current_time)?; // 锁定期仍然有效
+        pool.update_rewards(Some(user_stake_info))?; // 按原质押量结算奖励，之后该金额停止计息
+
+        // 从计息质押量中移出，计入冷却中的金额
+        user_stake_info.stake_amount = user_stake_info
+            .stake_amount
+            .checked_sub(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_staked = pool
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_unbonding = pool
+            .total_unbonding
+            .checked_add(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.sync_weight(user_stake_info, current_time)?; // 重新计算加权质押量
+        if user_stake_info.stake_amount == 0 {
+            user_stake_info.stake_start_timestamp = 0;
+        }
+
+        let available_at = current_time
+            .checked_add(pool.cooldown_duration)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        require_gt!(
+            MAX_UNBONDING_REQUESTS,
+            user_stake_info.unbonding.len(),
+            StakingError::TooManyUnbondingRequests
+        );
+        user_stake_info.unbonding.push(UnbondingRequest {
+            amount,
+            available_at,
         });
 
+        emit!(UnstakeRequestedEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            amount,
+            available_at
+        }); // 触发取消质押请求事件
         Ok(()) // 返回成功
     }
 
-    // 用户领取累积的奖励代币
-    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
-        require!(!ctx.accounts.pool.is_paused, StakingError::ProgramPaused); // 确保程序未暂停
+    // 用户撤销冷却中的取消质押请求，金额重新计入质押并恢复计息
+    pub fn cancel_unstake_request(ctx: Context<UnbondingAction>, request_index: u8) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
 
         let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
         let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+        let index = request_index as usize;
+        require_gt!(
+            user_stake_info.unbonding.len(),
+            index,
+            StakingError::InvalidUnbondingRequest
+        ); // 确保请求存在
 
-        pool.update_rewards(Some(user_stake_info))?; // 更新奖励，累加待领奖励
+        pool.update_rewards(Some(user_stake_info))?; // 先按当前质押量结算奖励
+        let request = user_stake_info.unbonding.remove(index);
 
-        let rewards_to_claim = user_stake_info.rewards; // 获取待领取奖励金额
-        require_gt!(rewards_to_claim, 0, StakingError::NoRewardsToClaim); // 确保有奖励可领
-        require_gte!(
-            ctx.accounts.reward_vault.amount,
-            rewards_to_claim,
-            StakingError::InsufficientVaultBalance
-        ); // 确保金库余额足够
+        // 重新计入质押量；请求前已通过锁定期检查，作为已解锁批次记录
+        let current_time = pool.last_update_timestamp;
+        if user_stake_info.stake_amount == 0 {
+            user_stake_info.stake_start_timestamp = current_time;
+        }
+        user_stake_info.add_lot(request.amount, current_time, 0, current_time)?;
+        user_stake_info.stake_amount = user_stake_info
+            .stake_amount
+            .checked_add(request.amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_staked = pool
+            .total_staked
+            .checked_add(request.amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_unbonding = pool
+            .total_unbonding
+            .checked_sub(request.amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.sync_weight(user_stake_info, current_time)?; // 重新计算加权质押量
 
-        // 设置 PDA 签名种子
-        let pool_seeds = &[b"pool".as_ref(), &[pool.pool_bump]];
+        emit!(UnstakeRequestCancelledEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            amount: request.amount
+        }); // 触发撤销取消质押请求事件
+        Ok(()) // 返回成功
+    }
+
+    // 用户提取所有冷却期已结束的取消质押请求
+    pub fn withdraw_unstaked<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnStake<'info>>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
+
+        let current_time = Clock::get()?.unix_timestamp; // 获取当前区块链时间
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+                                                                 // 汇总并移除冷却期已结束的请求
+        let amount = user_stake_info
+            .unbonding
+            .iter()
+            .filter(|request| request.available_at <= current_time)
+            .try_fold(0u64, |sum, request| sum.checked_add(request.amount))
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        require_gt!(amount, 0, StakingError::NothingToWithdraw); // 确保有可提取的金额
+        user_stake_info
+            .unbonding
+            .retain(|request| request.available_at > current_time);
+
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        pool.total_unbonding = pool
+            .total_unbonding
+            .checked_sub(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+
+        // 设置 PDA 签名种子，用于金库转账授权
+        let pool_seeds = pool_signer_seeds!(pool);
         let signer = &[&pool_seeds[..]];
 
-        // 执行奖励代币转账：从奖励金库到用户钱包
-        token_2022::transfer_checked(
+        // 执行代币转账：从质押金库到用户钱包
+        transfer_checked_with_hook(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 TransferChecked {
-                    from: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
-                    mint: ctx.accounts.reward_mint.to_account_info(),
-                    to: ctx.accounts.user_reward_wallet.to_account_info(), // 用户奖励钱包
-                    authority: pool.to_account_info(),                     // 池子 PDA 作为权限
+                    from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                    mint: ctx.accounts.staking_mint.to_account_info(),
+                    to: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
+                    authority: pool.to_account_info(),                      // 池子 PDA 作为权限
                 },
                 signer,
-            ),
-            rewards_to_claim,
-            ctx.accounts.reward_mint.decimals,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.staking_mint.decimals,
         )?;
 
-        user_stake_info.rewards = 0; // 重置用户待领奖励
-        emit!(ClaimEvent {
+        emit!(UnstakedWithdrawnEvent {
+            pool: pool.key(),
             user: *ctx.accounts.user.key,
-            amount: rewards_to_claim
-        }); // 触发领取奖励事件
-
+            amount,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?
+        }); // 触发提取事件
         Ok(()) // 返回成功
     }
 
-    // 关闭用户质押信息账户，回收租金
-    pub fn close_user_stake_info(ctx: Context<CloseUserStakeInfo>) -> Result<()> {
-        let user_stake_info = &ctx.accounts.user_stake_info; // 获取用户质押信息引用
-        require_eq!(user_stake_info.stake_amount, 0, StakingError::StakeNotZero); // 确保质押金额为 0
-        require_eq!(user_stake_info.rewards, 0, StakingError::RewardsNotClaimed); // 确保无未领奖励
-                                                                                  // 账户通过 close 约束自动关闭，租金返还用户
-        Ok(())
-    }
-
-    // 管理员更新奖励率
-    pub fn update_reward_rate(ctx: Context<AdminAction>, new_rate: u64) -> Result<()> {
+    // 任何人可调用：按当前时间重新计算某个用户的加权质押量，使已衰减或到期的加速锁定及时生效
+    pub fn poke(ctx: Context<Poke>) -> Result<()> {
         let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
-        pool.update_rewards(None)?; // 更新全局奖励，确保状态同步
-        pool.reward_rate = new_rate; // 设置新奖励率
-        emit!(UpdateRewardRateEvent { new_rate }); // 触发更新奖励率事件
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+
+        let current_time = Clock::get()?.unix_timestamp;
+        pool.record_poke(user_stake_info, current_time)?; // 结算奖励后按当前剩余锁定期重新计算权重
+
+        emit!(PokeEvent {
+            pool: pool.key(),
+            user: user_stake_info.user,
+            weighted_amount: user_stake_info.weighted_amount
+        }); // 触发重新加权事件
         Ok(()) // 返回成功
     }
 
-    // 管理员向奖励金库注资
-    pub fn fund_rewards(ctx: Context<FundRewards>, amount: u64) -> Result<()> {
-        require_gt!(amount, 0, StakingError::ZeroFundAmount); // 确保注资金额大于 0
+    // 只读视图：返回用户按当前时间结算后的各奖励流待领奖励，结果与立即调用 claim_rewards 的金额一致；
+    // 返回值由 Anchor 以 Borsh 编码写入 set_return_data，客户端通过模拟交易读取，不修改任何账户
+    pub fn get_pending_rewards(ctx: Context<UserView>, user: Pubkey) -> Result<PendingRewardsView> {
+        let mut pool = ctx.accounts.pool.clone(); // 在副本上结算奖励，不写回账户
+        let mut user_stake_info = ctx.accounts.user_stake_info.clone();
+        pool.update_rewards(Some(&mut user_stake_info))?;
 
-        // 执行代币转账：从管理员钱包到奖励金库
-        token_2022::transfer_checked(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.funder_wallet.to_account_info(), // 管理员钱包
-                    mint: ctx.accounts.reward_mint.to_account_info(),
-                    to: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
-                    authority: ctx.accounts.admin.to_account_info(), // 管理员签名
-                },
-            ),
-            amount,
-            ctx.accounts.reward_mint.decimals,
-        )?;
+        Ok(PendingRewardsView {
+            pool: pool.key(),
+            user,
+            timestamp: pool.last_update_timestamp,
+            reward_mints: pool.reward_streams.map(|stream| stream.mint),
+            rewards: user_stake_info.rewards,
+        })
+    }
 
-        emit!(FundRewardsEvent { amount }); // 触发注资事件
-        Ok(()) // 返回成功
+    // 只读视图：返回用户仓位，包括锁定与解锁金额、解锁时间、当前加权质押量、冷却中的请求和待领奖励
+    pub fn get_user_position(ctx: Context<UserView>, user: Pubkey) -> Result<UserPositionView> {
+        let mut pool = ctx.accounts.pool.clone(); // 在副本上结算奖励，不写回账户
+        let mut user_stake_info = ctx.accounts.user_stake_info.clone();
+        pool.update_rewards(Some(&mut user_stake_info))?;
+
+        let now = pool.last_update_timestamp;
+        let locked_amount = user_stake_info.locked_amount(now);
+        // 仍锁定批次的解锁时间，0 表示没有锁定中的批次
+        let unlocks = user_stake_info
+            .lots
+            .iter()
+            .map(|lot| lot.unlock_timestamp)
+            .filter(|unlock| *unlock > now);
+        Ok(UserPositionView {
+            pool: pool.key(),
+            user,
+            timestamp: now,
+            stake_amount: user_stake_info.stake_amount,
+            locked_amount,
+            unlocked_amount: user_stake_info.stake_amount.saturating_sub(locked_amount),
+            next_unlock_timestamp: unlocks.clone().min().unwrap_or(0),
+            final_unlock_timestamp: unlocks.max().unwrap_or(0),
+            stake_start_timestamp: user_stake_info.stake_start_timestamp,
+            weighted_amount: user_stake_info.weighted_amount,
+            current_weight: user_stake_info.boosted_weight(now, pool.max_boost_bps)?,
+            unbonding_amount: user_stake_info.unbonding.iter().map(|r| r.amount).sum(),
+            withdrawable_amount: user_stake_info
+                .unbonding
+                .iter()
+                .filter(|request| request.available_at <= now)
+                .map(|request| request.amount)
+                .sum(),
+            rewards: user_stake_info.rewards,
+        })
     }
 
-    // 管理员更改管理员权限
-    pub fn change_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
-        ctx.accounts.pool.admin = new_admin; // 更新管理员公钥
-        emit!(ChangeAdminEvent { new_admin }); // 触发更改管理员事件
-        Ok(()) // 返回成功
+    // 只读视图：返回池子按当前时间结算后的总量、金库余额和各奖励流的年化收益率计算输入；
+    // remaining_accounts 按槽位顺序传入已配置奖励流的金库，用于读取实际代币余额
+    pub fn get_pool_state<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PoolView<'info>>,
+    ) -> Result<PoolStateView> {
+        let mut pool = ctx.accounts.pool.clone(); // 在副本上结算奖励，不写回账户
+        pool.update_rewards(None)?;
+
+        // 读取各奖励金库的实际余额，金库地址必须与奖励流记录的 PDA 一致
+        let mut vault_amounts = [0u64; MAX_REWARD_STREAMS];
+        let mut vaults = ctx.remaining_accounts.iter();
+        for (stream, amount) in pool.reward_streams.iter().zip(vault_amounts.iter_mut()) {
+            if stream.mint == Pubkey::default() {
+                continue; // 空闲槽位没有金库
+            }
+            let vault = vaults.next().ok_or(StakingError::RewardVaultMismatch)?;
+            require_keys_eq!(vault.key(), stream.vault, StakingError::RewardVaultMismatch);
+            *amount = InterfaceAccount::<TokenAccount>::try_from(vault)?.amount;
+        }
+
+        let now = pool.last_update_timestamp;
+        Ok(PoolStateView {
+            pool: pool.key(),
+            timestamp: now,
+            is_paused: pool.is_paused_now()?,
+            is_shutdown: pool.is_shutdown,
+            total_staked: pool.total_staked,
+            total_weighted_stake: pool.total_weighted_stake,
+            total_unbonding: pool.total_unbonding,
+            staking_vault_balance: ctx.accounts.staking_vault.amount,
+            liquid_principal: pool.liquid_principal,
+            liquid_rewards: pool.liquid_rewards,
+            lockup_duration: pool.lockup_duration,
+            cooldown_duration: pool.cooldown_duration,
+            max_boost_bps: pool.max_boost_bps,
+            reward_streams: std::array::from_fn(|index| {
+                pool.reward_streams[index].view(now, vault_amounts[index])
+            }),
+        })
     }
 
-    // 管理员紧急提取质押代币
-    pub fn emergency_withdraw_staked_tokens(
-        ctx: Context<EmergencyWithdrawStaked>,
+    // 用户以流动性方式质押：代币计入池子的份额桶，按份额价格铸造收据代币（xTOKEN）给用户
+    pub fn stake_liquid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StakeLiquid<'info>>,
         amount: u64,
     ) -> Result<()> {
-        let pool = &ctx.accounts.pool; // 获取池子账户引用
-        let pool_seeds = &[b"pool".as_ref(), &[pool.pool_bump]]; // 设置 PDA 签名种子
-        let signer = &[&pool_seeds[..]];
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
+        require_gt!(amount, 0, StakingError::ZeroStakeAmount); // 确保质押金额大于 0
+        require_eq!(
+            ctx.accounts.pool.lockup_duration,
+            0,
+            StakingError::LiquidStakingRequiresNoLockup
+        ); // 收据代币可自由转让，无法跟踪锁定期
+        require_eq!(
+            ctx.accounts.pool.cooldown_duration,
+            0,
+            StakingError::LiquidStakingRequiresNoCooldown
+        ); // 收据代币可自由转让，无法走两阶段退出
 
-        // 执行代币转账：从质押金库到目标钱包
-        token_2022::transfer_checked(
-            CpiContext::new_with_signer(
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        pool.update_rewards(None)?; // 结算份额桶的奖励，确保份额价格为最新
+        let net_amount = amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?; // 金库实际收到的金额
+
+        // 按份额价格计算铸造数量：net_amount * 总份额 / 份额桶总价值，首次存入按 1:1 铸造
+        let supply = ctx.accounts.receipt_mint.supply;
+        let liquid_value = pool.liquid_value()?;
+        let shares: u64 = if supply == 0 || liquid_value == 0 {
+            net_amount
+        } else {
+            ((net_amount as u128)
+                .checked_mul(supply as u128)
+                .ok_or(StakingError::ArithmeticOverflow)?
+                / liquid_value as u128)
+                .try_into()
+                .map_err(|_| StakingError::ArithmeticOverflow)?
+        };
+        require_gt!(shares, 0, StakingError::ZeroStakeAmount); // 金额过小，无法铸造份额
+
+        // 执行代币转账：从用户钱包到质押金库
+        transfer_checked_with_hook(
+            CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 TransferChecked {
-                    from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                    from: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
                     mint: ctx.accounts.staking_mint.to_account_info(),
-                    to: ctx.accounts.destination_wallet.to_account_info(), // 目标钱包
-                    authority: pool.to_account_info(),                     // 池子 PDA 作为权限
+                    to: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                    authority: ctx.accounts.user.to_account_info(),   // 用户签名作为权限
                 },
-                signer,
-            ),
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
             amount,
             ctx.accounts.staking_mint.decimals,
         )?;
 
-        Ok(()) // 返回成功
-    }
-
-    // 管理员紧急提取奖励代币
-    pub fn emergency_withdraw_reward_tokens(
-        ctx: Context<EmergencyWithdrawRewards>,
-        amount: u64,
-    ) -> Result<()> {
-        let pool = &ctx.accounts.pool; // 获取池子账户引用
-        let pool_seeds = &[b"pool".as_ref(), &[pool.pool_bump]]; // 设置 PDA 签名种子
+        // 铸造收据代币：池子 PDA 作为 Mint 权限
+        let pool_seeds = pool_signer_seeds!(pool);
         let signer = &[&pool_seeds[..]];
-
-        // 执行代币转账：从奖励金库到目标钱包
-        token_2022::transfer_checked(
+        token_interface::mint_to(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
-                    mint: ctx.accounts.reward_mint.to_account_info(),
-                    to: ctx.accounts.destination_wallet.to_account_info(), // 目标钱包
-                    authority: pool.to_account_info(),                     // 池子 PDA 作为权限
+                MintTo {
+                    mint: ctx.accounts.receipt_mint.to_account_info(), // 收据代币 Mint
+                    to: ctx.accounts.user_receipt_wallet.to_account_info(), // 用户收据代币钱包
+                    authority: pool.to_account_info(),                 // 池子 PDA 作为权限
                 },
                 signer,
             ),
-            amount,
-            ctx.accounts.reward_mint.decimals, // 关键：传入代币的小数位数
+            shares,
         )?;
 
-        Ok(()) // 返回成功
-    }
+        // 份额桶按 1x 权重参与奖励分配
+        let old_weight = pool.liquid_weight()?;
+        pool.liquid_principal = pool
+            .liquid_principal
+            .checked_add(net_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_staked = pool
+            .total_staked
+            .checked_add(net_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.replace_liquid_weight(old_weight)?; // 同步总加权质押量
 
-    // 管理员暂停程序，阻止用户操作
-    pub fn pause(ctx: Context<AdminAction>) -> Result<()> {
-        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
-        require!(!pool.is_paused, StakingError::AlreadyPaused); // 确保程序未暂停
-        pool.is_paused = true; // 设置暂停状态
-        emit!(PauseEvent {}); // 触发暂停事件
+        emit!(LiquidStakeEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            amount,
+            net_amount,
+            shares
+        }); // 触发流动性质押事件
         Ok(()) // 返回成功
     }
 
-    // 管理员恢复程序，允许用户操作
-    pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
-        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
-        require!(pool.is_paused, StakingError::NotPaused); // 确保程序已暂停
-        pool.is_paused = false; // 取消暂停状态
-        emit!(UnpauseEvent {}); // 触发恢复事件
-        Ok(()) // 返回成功
-    }
+    // 收据代币持有人销毁份额，按份额价格取回本金及份额桶累积的质押代币奖励；
+    // 开启收据代币的池子冷却期恒为 0。池子关停后不受暂停限制，本金按关停时的剩余比例折算
+    pub fn unstake_liquid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnstakeLiquid<'info>>,
+        shares: u64,
+    ) -> Result<()> {
+        let is_shutdown = ctx.accounts.pool.is_shutdown;
+        require!(
+            !ctx.accounts.pool.is_paused_now()? || is_shutdown,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require_gt!(shares, 0, StakingError::ZeroUnstakeAmount); // 确保销毁份额大于 0
 
-    // 管理员更新锁定期
-    pub fn update_lockup_duration(ctx: Context<AdminAction>, new_duration: i64) -> Result<()> {
         let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
-        pool.lockup_duration = new_duration; // 设置新锁定期
-        emit!(UpdateLockupDurationEvent { new_duration }); // 触发更新锁定期事件
-        Ok(()) // 返回成功
-    }
-}
+        pool.update_rewards(None)?; // 结算份额桶的奖励，确保份额价格为最新
 
-// 定义账户结构和约束
+        // 按份额比例计算可取回的本金和奖励
+        let supply = ctx.accounts.receipt_mint.supply;
+        require_gte!(supply, shares, StakingError::InsufficientStakeAmount);
+        let principal = pool.liquid_share_of(pool.liquid_principal, shares, supply)?;
+        let rewards = pool.liquid_share_of(pool.liquid_rewards, shares, supply)?;
+        let old_weight = pool.liquid_weight()?;
+        let payout = if is_shutdown {
+            pool.shutdown_payout(principal)? // 关停后本金按比例折算
+        } else {
+            principal
+        };
 
-// 初始化指令的账户结构
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    // 初始化池子账户，分配空间并设置 PDA 种子
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + Pool::INIT_SPACE,
-        seeds = [b"pool"],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-    #[account(mut)] // 管理员签名者，支付初始化费用
-    pub admin: Signer<'info>,
-    pub staking_mint: InterfaceAccount<'info, Mint>, // 质押代币 Mint 账户
-    // 初始化质押金库，权限归池子
-    #[account(init, payer = admin, token::mint = staking_mint, token::authority = pool, seeds = [b"staking_vault"], bump
-    )]
-    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
-    pub reward_mint: InterfaceAccount<'info, Mint>, // 奖励代币 Mint 账户
-    // 初始化奖励金库，权限归池子
-    #[account(init, payer = admin, token::mint = reward_mint, token::authority = pool, seeds = [b"reward_vault"], bump
-    )]
+        // 销毁收据代币：持有人签名
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.receipt_mint.to_account_info(), // 收据代币 Mint
+                    from: ctx.accounts.user_receipt_wallet.to_account_info(), // 用户收据代币钱包
+                    authority: ctx.accounts.user.to_account_info(),    // 用户签名作为权限
+                },
+            ),
+            shares,
+        )?;
+
+        let pool_seeds = pool_signer_seeds!(pool); // 设置 PDA 签名种子
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：从质押金库到用户钱包
+        if payout > 0 {
+            transfer_checked_with_hook(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                        mint: ctx.accounts.staking_mint.to_account_info(),
+                        to: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
+                        authority: pool.to_account_info(),                      // 池子 PDA 作为权限
+                    },
+                    signer,
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                payout,
+                ctx.accounts.staking_mint.decimals,
+            )?;
+        }
+
+        // 份额桶的奖励来自奖励代币与质押代币相同的奖励流，从其奖励金库支付
+        if rewards > 0 {
+            let reward_vault = ctx
+                .accounts
+                .reward_vault
+                .as_ref()
+                .ok_or(StakingError::RewardVaultMismatch)?;
+            let index = pool.compound_stream_index()?;
+            pool.reward_stream_for(index as u8, &reward_vault.key(), &pool.staking_mint)?;
+
+            transfer_checked_with_hook(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: reward_vault.to_account_info(), // 奖励金库
+                        mint: ctx.accounts.staking_mint.to_account_info(),
+                        to: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
+                        authority: pool.to_account_info(),                      // 池子 PDA 作为权限
+                    },
+                    signer,
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                rewards,
+                ctx.accounts.staking_mint.decimals,
+            )?;
+
+            // 同步奖励流账本：已支付的奖励不再是负债
+            let stream = &mut pool.reward_streams[index];
+            stream.total_rewards_owed = stream.total_rewards_owed.saturating_sub(rewards);
+            stream.vault_balance = stream
+                .vault_balance
+                .checked_sub(rewards)
+                .ok_or(StakingError::InsufficientVaultBalance)?;
+            pool.liquid_rewards = pool
+                .liquid_rewards
+                .checked_sub(rewards)
+                .ok_or(StakingError::ArithmeticOverflow)?;
+        }
+
+        pool.liquid_principal = pool
+            .liquid_principal
+            .checked_sub(principal)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_staked = pool
+            .total_staked
+            .checked_sub(principal)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.replace_liquid_weight(old_weight)?; // 同步总加权质押量
+
+        emit!(LiquidUnstakeEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            shares,
+            amount: payout,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.staking_mint, payout)?,
+            rewards,
+            net_rewards: amount_after_transfer_fee(&ctx.accounts.staking_mint, rewards)?
+        }); // 触发流动性取消质押事件
+        Ok(()) // 返回成功
+    }
+
+    // 将用户在质押代币奖励流中累积的奖励直接转入质押金库并计入质押量，不影响已有批次的锁定期；
+    // 池子开启自动复利时任何人都可以为用户调用
+    pub fn compound<'info>(ctx: Context<'_, '_, 'info, 'info, Compound<'info>>) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 确保池子未关停
+        require!(
+            ctx.accounts.pool.auto_compound
+                || ctx.accounts.caller.key() == ctx.accounts.user_stake_info.user,
+            StakingError::CompoundNotPermitted
+        ); // 未开启自动复利时仅用户本人可复利
+
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+                                                                 // 校验奖励代币为质押代币的奖励流及其金库
+        let index = pool.compound_stream_index()?;
+        pool.reward_stream_for(
+            index as u8,
+            &ctx.accounts.reward_vault.key(),
+            &ctx.accounts.staking_mint.key(),
+        )?;
+
+        pool.update_rewards(Some(user_stake_info))?; // 更新所有奖励流，累加待领奖励
+        let amount = user_stake_info.rewards[index]; // 获取可复利的奖励金额
+        require_gt!(amount, 0, StakingError::NoRewardsToClaim); // 确保有奖励可复利
+
+        // 设置 PDA 签名种子
+        let pool_seeds = pool_signer_seeds!(pool);
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：从奖励金库到质押金库
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
+                    mint: ctx.accounts.staking_mint.to_account_info(),
+                    to: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                    authority: pool.to_account_info(),                // 池子 PDA 作为权限
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.staking_mint.decimals,
+        )?;
+
+        // 同步奖励流账本：复利的奖励不再是负债
+        user_stake_info.rewards[index] = 0;
+        let stream = &mut pool.reward_streams[index];
+        stream.total_rewards_owed = stream.total_rewards_owed.saturating_sub(amount);
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_sub(amount)
+            .ok_or(StakingError::InsufficientVaultBalance)?;
+
+        // 复利部分（扣除转账手续费后质押金库实际收到的金额）作为已解锁批次计入质押量，
+        // 并入已有的已解锁批次，他人反复复利也不会占满批次上限
+        let net_amount = amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?;
+        let current_time = pool.last_update_timestamp;
+        if user_stake_info.stake_amount == 0 {
+            user_stake_info.stake_start_timestamp = current_time;
+        }
+        user_stake_info.add_lot(net_amount, current_time, 0, current_time)?;
+        user_stake_info.stake_amount = user_stake_info
+            .stake_amount
+            .checked_add(net_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.total_staked = pool
+            .total_staked
+            .checked_add(net_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        pool.sync_weight(user_stake_info, current_time)?; // 重新计算加权质押量
+
+        emit!(CompoundEvent {
+            pool: pool.key(),
+            user: user_stake_info.user,
+            amount,
+            net_amount
+        }); // 触发复利事件
+        Ok(()) // 返回成功
+    }
+
+    // 用户领取指定奖励流中累积的奖励代币
+    pub fn claim_rewards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>,
+        reward_index: u8,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.pool.is_paused_now()?,
+            StakingError::ProgramPaused
+        ); // 确保程序未暂停
+
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+                                                                 // 校验奖励流的金库和 Mint 与传入账户一致
+        pool.reward_stream_for(
+            reward_index,
+            &ctx.accounts.reward_vault.key(),
+            &ctx.accounts.reward_mint.key(),
+        )?;
+
+        // 结算奖励并清零该奖励流的待领奖励，获取领取金额
+        let current_time = Clock::get()?.unix_timestamp;
+        let rewards_to_claim =
+            pool.record_claim(user_stake_info, reward_index as usize, current_time)?;
+        require_gt!(rewards_to_claim, 0, StakingError::NoRewardsToClaim); // 确保有奖励可领
+        require_gte!(
+            ctx.accounts.reward_vault.amount,
+            rewards_to_claim,
+            StakingError::InsufficientVaultBalance
+        ); // 确保金库余额足够
+
+        // 设置 PDA 签名种子
+        let pool_seeds = pool_signer_seeds!(pool);
+        let signer = &[&pool_seeds[..]];
+
+        // 执行奖励代币转账：从奖励金库到用户钱包
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.user_reward_wallet.to_account_info(), // 用户奖励钱包
+                    authority: pool.to_account_info(),                     // 池子 PDA 作为权限
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            rewards_to_claim,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        emit!(ClaimEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            reward_mint: ctx.accounts.reward_mint.key(),
+            amount: rewards_to_claim,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.reward_mint, rewards_to_claim)?
+        }); // 触发领取奖励事件
+
+        Ok(()) // 返回成功
+    }
+
+    // 关闭用户质押信息账户，回收租金
+    pub fn close_user_stake_info(ctx: Context<CloseUserStakeInfo>) -> Result<()> {
+        let user_stake_info = &ctx.accounts.user_stake_info; // 获取用户质押信息引用
+        require_eq!(user_stake_info.stake_amount, 0, StakingError::StakeNotZero); // 确保质押金额为 0
+        require!(
+            user_stake_info.unbonding.is_empty(),
+            StakingError::UnbondingPending
+        ); // 确保没有冷却中的取消质押请求
+        require!(
+            user_stake_info.rewards.iter().all(|r| *r == 0),
+            StakingError::RewardsNotClaimed
+        ); // 确保所有奖励流均无未领奖励
+           // 账户通过 close 约束自动关闭，租金返还用户
+        Ok(())
+    }
+
+    // 奖励率管理者更新指定奖励流在当前奖励周期剩余时间内的奖励率
+    pub fn update_reward_rate(
+        ctx: Context<RateManagerAction>,
+        reward_index: u8,
+        new_rate: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        let reward_mint = pool.set_reward_rate(reward_index, new_rate)?; // 校验并设置新奖励率
+        emit!(UpdateRewardRateEvent {
+            pool: pool.key(),
+            reward_mint,
+            new_rate
+        }); // 触发更新奖励率事件
+        Ok(()) // 返回成功
+    }
+
+    // 注资者从任意钱包向指定奖励流的金库注资
+    pub fn fund_rewards<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FundRewards<'info>>,
+        reward_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        require_gt!(amount, 0, StakingError::ZeroFundAmount); // 确保注资金额大于 0
+
+        // 校验奖励流的金库和 Mint 与传入账户一致
+        ctx.accounts.pool.reward_stream_for(
+            reward_index,
+            &ctx.accounts.reward_vault.key(),
+            &ctx.accounts.reward_mint.key(),
+        )?;
+
+        // 执行代币转账：从注资者钱包到奖励金库
+        transfer_checked_with_hook(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.funder_wallet.to_account_info(), // 注资者钱包
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
+                    authority: ctx.accounts.funder.to_account_info(), // 注资者签名
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        // 记入奖励流账本余额：只计入金库实际收到的金额
+        let net_amount = amount_after_transfer_fee(&ctx.accounts.reward_mint, amount)?;
+        let stream = &mut ctx.accounts.pool.reward_streams[reward_index as usize];
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_add(net_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+
+        emit!(FundRewardsEvent {
+            pool: ctx.accounts.pool.key(),
+            reward_mint: ctx.accounts.reward_mint.key(),
+            amount,
+            net_amount
+        }); // 触发注资事件
+        Ok(()) // 返回成功
+    }
+
+    // 注资并开启新的奖励周期：reward_rate = (实际到账金额 + 上一周期剩余奖励) / duration
+    pub fn notify_reward_amount<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FundRewards<'info>>,
+        reward_index: u8,
+        amount: u64,
+        duration: i64,
+    ) -> Result<()> {
+        require_gt!(amount, 0, StakingError::ZeroFundAmount); // 确保注资金额大于 0
+        require_eq!(
+            ctx.accounts.pool.timelock_delay,
+            0,
+            StakingError::TimelockRequired
+        ); // 开启时间锁后先 fund_rewards 注资，再通过 schedule_change 开启周期
+
+        // 开启新周期会改变奖励率，注资者还需持有奖励率管理者角色
+        require!(
+            ctx.accounts
+                .pool
+                .has_role(Role::RateManager, &ctx.accounts.funder.key()),
+            StakingError::MissingRole
+        );
+
+        // 校验奖励流的金库和 Mint 与传入账户一致
+        ctx.accounts.pool.reward_stream_for(
+            reward_index,
+            &ctx.accounts.reward_vault.key(),
+            &ctx.accounts.reward_mint.key(),
+        )?;
+
+        ctx.accounts.pool.update_rewards(None)?; // 按旧奖励率结算至当前时间，新注资不参与此前的累积
+
+        // 执行代币转账：从注资者钱包到奖励金库
+        transfer_checked_with_hook(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.funder_wallet.to_account_info(), // 注资者钱包
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
+                    authority: ctx.accounts.funder.to_account_info(), // 注资者签名
+                },
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+
+        let net_amount = amount_after_transfer_fee(&ctx.accounts.reward_mint, amount)?; // 金库实际收到的金额
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let stream = &mut pool.reward_streams[reward_index as usize];
+        // 记入奖励流账本余额
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_add(net_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        let stream = pool.start_reward_period(reward_index, net_amount, duration)?; // 以到账金额开启新周期
+
+        emit!(NotifyRewardAmountEvent {
+            pool: pool.key(),
+            reward_mint: stream.mint,
+            amount,
+            net_amount,
+            duration,
+            reward_rate: stream.reward_rate,
+            period_finish: stream.period_finish
+        }); // 触发开启奖励周期事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员将角色授予指定地址，覆盖该角色原持有者；管理员本身始终拥有全部角色
+    pub fn grant_role(ctx: Context<AdminAction>, role: Role, account: Pubkey) -> Result<()> {
+        require_keys_neq!(account, Pubkey::default(), StakingError::InvalidRoleHolder); // 不能授予空地址
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let previous = std::mem::replace(pool.role_holder_mut(role), account); // 设置新的角色持有者
+        emit!(RoleGrantedEvent {
+            pool: pool.key(),
+            role,
+            account,
+            previous
+        }); // 触发授予角色事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员撤销角色，撤销后该角色仅由管理员持有
+    pub fn revoke_role(ctx: Context<AdminAction>, role: Role) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let account = std::mem::take(pool.role_holder_mut(role)); // 清空角色持有者
+        require_keys_neq!(account, Pubkey::default(), StakingError::RoleNotAssigned); // 确保角色已被授予
+        emit!(RoleRevokedEvent {
+            pool: pool.key(),
+            role,
+            account
+        }); // 触发撤销角色事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员提名新管理员，需由新管理员调用 accept_admin 签名确认后才生效
+    pub fn propose_admin(ctx: Context<AdminAction>, new_admin: Pubkey) -> Result<()> {
+        require_keys_neq!(
+            new_admin,
+            Pubkey::default(),
+            StakingError::InvalidPendingAdmin
+        ); // 不能提名空地址
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 提名
+        pool.pending_admin = new_admin; // 记录待确认的新管理员，覆盖之前的提名
+        emit!(AdminTransferProposedEvent {
+            pool: pool.key(),
+            current_admin: pool.admin,
+            pending_admin: new_admin
+        }); // 触发提名管理员事件
+        Ok(()) // 返回成功
+    }
+
+    // 被提名的新管理员签名接受管理员权限
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let previous_admin = pool.admin;
+        pool.admin = pool.pending_admin; // 转移管理员权限
+        pool.pending_admin = Pubkey::default(); // 清空待确认的提名
+        emit!(AdminTransferAcceptedEvent {
+            pool: pool.key(),
+            previous_admin,
+            new_admin: pool.admin
+        }); // 触发接受管理员事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员撤销尚未被接受的管理员提名
+    pub fn cancel_admin_transfer(ctx: Context<AdminAction>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let cancelled_admin = pool.pending_admin;
+        require_keys_neq!(
+            cancelled_admin,
+            Pubkey::default(),
+            StakingError::NoPendingAdmin
+        ); // 确保存在待确认的提名
+        pool.pending_admin = Pubkey::default(); // 清空提名
+        emit!(AdminTransferCancelledEvent {
+            pool: pool.key(),
+            cancelled_admin
+        }); // 触发撤销提名事件
+        Ok(()) // 返回成功
+    }
+
+    // 守护者紧急提取质押代币，池子随之进入不可逆的关停状态：
+    // 奖励停止累积，用户通过 emergency_exit 按比例取回金库剩余的质押代币
+    pub fn emergency_withdraw_staked_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyWithdrawStaked<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 只能关停一次
+        ctx.accounts.pool.update_rewards(None)?; // 结算关停前的奖励
+
+        let pool = &ctx.accounts.pool; // 获取池子账户引用
+        let pool_seeds = pool_signer_seeds!(pool); // 设置 PDA 签名种子
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：从质押金库到目标钱包
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                    mint: ctx.accounts.staking_mint.to_account_info(),
+                    to: ctx.accounts.destination_wallet.to_account_info(), // 目标钱包
+                    authority: pool.to_account_info(),                     // 池子 PDA 作为权限
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.staking_mint.decimals,
+        )?;
+
+        // 以提取后的金库余额进入关停状态
+        ctx.accounts.staking_vault.reload()?;
+        let remaining_balance = ctx.accounts.staking_vault.amount;
+        let pool = &mut ctx.accounts.pool;
+        pool.shutdown(remaining_balance)?;
+
+        emit!(ShutdownEvent {
+            pool: pool.key(),
+            guardian: ctx.accounts.guardian.key(),
+            amount,
+            destination: ctx.accounts.destination_wallet.key(),
+            remaining_balance,
+            outstanding_claims: pool.shutdown_claims
+        }); // 触发关停事件
+        Ok(()) // 返回成功
+    }
+
+    // 池子关停后，用户取回质押本金（含冷却中的请求）按比例对应的剩余代币；
+    // 关停前累积的奖励保留在用户账户中，仍可通过 claim_rewards 领取。不受暂停限制
+    pub fn emergency_exit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyExit<'info>>,
+    ) -> Result<()> {
+        require!(ctx.accounts.pool.is_shutdown, StakingError::PoolNotShutdown); // 仅关停后可用
+
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        pool.update_rewards(Some(user_stake_info))?; // 结算关停前的奖励
+
+        let claim = pool.close_position(user_stake_info)?; // 清空用户仓位，得到本金债权
+        let amount = pool.shutdown_payout(claim)?; // 按比例计算可取回的金额
+
+        let pool_seeds = pool_signer_seeds!(pool); // 设置 PDA 签名种子
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：从质押金库到用户钱包
+        if amount > 0 {
+            transfer_checked_with_hook(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                        mint: ctx.accounts.staking_mint.to_account_info(),
+                        to: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
+                        authority: pool.to_account_info(),                      // 池子 PDA 作为权限
+                    },
+                    signer,
+                )
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                amount,
+                ctx.accounts.staking_mint.decimals,
+            )?;
+        }
+
+        emit!(EmergencyExitEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            claim,
+            amount,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?
+        }); // 触发紧急退出事件
+        Ok(()) // 返回成功
+    }
+
+    // 池子暂停期间，用户无视锁定期和冷却期取回全部质押本金（含冷却中的请求）。
+    // forfeit_rewards 为 true 时放弃未领取的奖励：只结算全局奖励累加器，不计入用户奖励
+    pub fn emergency_unstake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyExit<'info>>,
+        forfeit_rewards: bool,
+    ) -> Result<()> {
+        require!(ctx.accounts.pool.is_paused_now()?, StakingError::NotPaused); // 仅暂停期间可用，超过最长暂停时间后视为未暂停
+        require!(!ctx.accounts.pool.is_shutdown, StakingError::PoolShutdown); // 关停后使用 emergency_exit
+
+        let user_stake_info = &mut ctx.accounts.user_stake_info; // 获取用户质押信息可变引用
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        if forfeit_rewards {
+            // 先按变化前的总加权质押量结算全局累加器，其他质押者的奖励不受本次退出影响
+            pool.update_rewards(None)?;
+            // 放弃已记录和未同步的奖励，释放回各奖励流的未占用余额
+            for index in 0..MAX_REWARD_STREAMS {
+                let forfeited = user_stake_info.rewards[index]
+                    .saturating_add(user_stake_info.calculate_pending_rewards(pool, index));
+                let stream = &mut pool.reward_streams[index];
+                stream.total_rewards_owed = stream.total_rewards_owed.saturating_sub(forfeited);
+                user_stake_info.rewards[index] = 0;
+                user_stake_info.reward_per_token_paid[index] = stream.reward_per_token_stored;
+            }
+        } else {
+            pool.update_rewards(Some(user_stake_info))?; // 结算奖励，退出后仍可领取
+        }
+
+        let amount = pool.close_position(user_stake_info)?; // 清空用户仓位，得到可取回的本金
+
+        let pool_seeds = pool_signer_seeds!(pool); // 设置 PDA 签名种子
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：从质押金库到用户钱包
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.staking_vault.to_account_info(), // 质押金库
+                    mint: ctx.accounts.staking_mint.to_account_info(),
+                    to: ctx.accounts.user_staking_wallet.to_account_info(), // 用户钱包
+                    authority: pool.to_account_info(),                      // 池子 PDA 作为权限
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.staking_mint.decimals,
+        )?;
+
+        emit!(EmergencyUnstakeEvent {
+            pool: pool.key(),
+            user: *ctx.accounts.user.key,
+            amount,
+            net_amount: amount_after_transfer_fee(&ctx.accounts.staking_mint, amount)?,
+            forfeit_rewards
+        }); // 触发暂停期间紧急取消质押事件
+        Ok(()) // 返回成功
+    }
+
+    // 守护者紧急提取指定奖励流的奖励代币
+    pub fn emergency_withdraw_reward_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyWithdrawRewards<'info>>,
+        reward_index: u8,
+        amount: u64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+
+        // 校验奖励流的金库和 Mint 与传入账户一致
+        pool.reward_stream_for(
+            reward_index,
+            &ctx.accounts.reward_vault.key(),
+            &ctx.accounts.reward_mint.key(),
+        )?;
+
+        // 偿付能力检查：提取后金库仍需覆盖已累积奖励和当前周期剩余的承诺奖励
+        pool.update_rewards(None)?;
+        let current_time = pool.last_update_timestamp;
+        let stream = &pool.reward_streams[reward_index as usize];
+        let committed = stream.emissions_within(stream.reward_rate, current_time, i64::MAX)?;
+        let withdrawable = (stream.unreserved_balance() as u128).saturating_sub(committed);
+        require_gte!(
+            withdrawable,
+            amount as u128,
+            StakingError::WithdrawExceedsUnreservedRewards
+        );
+
+        let pool_seeds = pool_signer_seeds!(pool); // 设置 PDA 签名种子
+        let signer = &[&pool_seeds[..]];
+
+        // 执行代币转账：从奖励金库到目标钱包
+        transfer_checked_with_hook(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.reward_vault.to_account_info(), // 奖励金库
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.destination_wallet.to_account_info(), // 目标钱包
+                    authority: pool.to_account_info(),                     // 池子 PDA 作为权限
+                },
+                signer,
+            )
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.reward_mint.decimals, // 关键：传入代币的小数位数
+        )?;
+
+        // 同步奖励流账本余额
+        let stream = &mut pool.reward_streams[reward_index as usize];
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_sub(amount)
+            .ok_or(StakingError::InsufficientVaultBalance)?;
+
+        Ok(()) // 返回成功
+    }
+
+    // 管理员新增一条奖励流，占用第一个空闲槽位并创建对应奖励金库；
+    // allowed_mint_extensions 含义与 initialize 相同
+    pub fn add_reward_stream(
+        ctx: Context<AddRewardStream>,
+        allowed_mint_extensions: u16,
+    ) -> Result<()> {
+        let mint_extensions =
+            validate_mint_extensions(&ctx.accounts.reward_mint, allowed_mint_extensions)?;
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let reward_mint = ctx.accounts.reward_mint.key();
+        // 同一奖励代币只能对应一条奖励流
+        require!(
+            pool.reward_streams.iter().all(|s| s.mint != reward_mint),
+            StakingError::DuplicateRewardMint
+        );
+        // 查找空闲槽位（退役的奖励流仍保留，以便用户领取已累积的奖励）
+        let index = pool
+            .reward_streams
+            .iter()
+            .position(|s| s.mint == Pubkey::default())
+            .ok_or(StakingError::RewardStreamsFull)?;
+
+        pool.update_rewards(None)?; // 先结算已有奖励流，新奖励流从当前时间开始计算
+        pool.reward_streams[index] = RewardStream {
+            mint: reward_mint,
+            vault: ctx.accounts.reward_vault.key(),
+            reward_rate: 0,
+            reward_per_token_stored: 0,
+            period_finish: 0,
+            vault_balance: 0,
+            total_rewards_owed: 0,
+            is_active: true,
+            mint_extensions,
+        };
+
+        emit!(AddRewardStreamEvent {
+            pool: pool.key(),
+            reward_index: index as u8,
+            reward_mint,
+            reward_vault: ctx.accounts.reward_vault.key(),
+            mint_extensions
+        }); // 触发新增奖励流事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员退役奖励流：停止发放新奖励，用户仍可领取已累积的奖励
+    pub fn retire_reward_stream(ctx: Context<AdminAction>, reward_index: u8) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 退役
+        let reward_mint = pool.retire_stream(reward_index)?; // 结算后停止发放
+
+        emit!(RetireRewardStreamEvent {
+            pool: pool.key(),
+            reward_index,
+            reward_mint
+        }); // 触发退役奖励流事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员为池子开启收据代币，创建由池子 PDA 控制的份额 Mint；
+    // 份额桶即时赎回，只能在无锁定期、无冷却期的池子上开启
+    pub fn enable_receipt_token(ctx: Context<EnableReceiptToken>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(
+            pool.lockup_duration,
+            0,
+            StakingError::LiquidStakingRequiresNoLockup
+        ); // 收据代币可自由转让，无法跟踪锁定期
+        require_eq!(
+            pool.cooldown_duration,
+            0,
+            StakingError::LiquidStakingRequiresNoCooldown
+        ); // 收据代币可自由转让，无法走两阶段退出
+        pool.receipt_mint = ctx.accounts.receipt_mint.key(); // 记录收据代币 Mint 地址
+        emit!(EnableReceiptTokenEvent {
+            pool: pool.key(),
+            receipt_mint: pool.receipt_mint
+        }); // 触发开启收据代币事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员开启或关闭自动复利：开启后收据代币份额桶累积的质押代币奖励按份额价格继续计息，
+    // 且任何人都可以为用户调用 compound
+    pub fn set_auto_compound(ctx: Context<AdminAction>, enabled: bool) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_auto_compound(enabled)?; // 结算奖励后切换开关，份额桶的权重随开关变化
+        emit!(SetAutoCompoundEvent {
+            pool: pool.key(),
+            enabled
+        }); // 触发设置自动复利事件
+        Ok(()) // 返回成功
+    }
+
+    // 暂停者（或管理员）暂停程序，阻止用户操作
+    pub fn pause(ctx: Context<PauserAction>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require!(!pool.is_paused, StakingError::AlreadyPaused); // 确保程序未暂停
+        pool.is_paused = true; // 设置暂停状态
+        pool.paused_at = Clock::get()?.unix_timestamp; // 记录暂停时间，用于最长暂停时间判断
+        emit!(PauseEvent { pool: pool.key() }); // 触发暂停事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员恢复程序，允许用户操作
+    pub fn unpause(ctx: Context<AdminAction>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require!(pool.is_paused, StakingError::NotPaused); // 确保程序已暂停
+        pool.is_paused = false; // 取消暂停状态
+        emit!(UnpauseEvent { pool: pool.key() }); // 触发恢复事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员设置最长暂停时间（秒），超过后池子自动按未暂停处理；0 表示不限制
+    pub fn update_max_pause_duration(ctx: Context<AdminAction>, new_duration: i64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_max_pause_duration(new_duration)?; // 校验并设置新最长暂停时间
+        emit!(UpdateMaxPauseDurationEvent {
+            pool: pool.key(),
+            new_duration
+        }); // 触发更新最长暂停时间事件
+        Ok(()) // 返回成功
+    }
+
+    // 奖励率管理者更新锁定期
+    pub fn update_lockup_duration(
+        ctx: Context<RateManagerAction>,
+        new_duration: i64,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.bounds.check_lockup_duration(new_duration)?; // 校验锁定期在边界内
+        pool.lockup_duration = new_duration; // 设置新锁定期
+        emit!(UpdateLockupDurationEvent {
+            pool: pool.key(),
+            new_duration
+        }); // 触发更新锁定期事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员延长时间锁延迟；缩短延迟本身也必须经过时间锁排队
+    pub fn update_timelock_delay(ctx: Context<AdminAction>, new_delay: i64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_gte!(
+            new_delay,
+            pool.timelock_delay,
+            StakingError::InvalidTimelockDelay
+        ); // 只能直接延长
+        pool.bounds.check_timelock_delay(new_delay)?; // 校验延迟在边界内
+        pool.timelock_delay = new_delay; // 设置新延迟
+        emit!(UpdateTimelockDelayEvent {
+            pool: pool.key(),
+            new_delay
+        }); // 触发更新时间锁延迟事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员排队一项参数变更，eta 至少为当前时间加 timelock_delay；变更存放在独立的 PDA 中
+    pub fn schedule_change(
+        ctx: Context<ScheduleChange>,
+        action: TimelockAction,
+        eta: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp; // 获取当前时间戳
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        let earliest = now
+            .checked_add(pool.timelock_delay)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        require_gte!(eta, earliest, StakingError::EtaTooEarly); // 确保给出足够的提前通知
+        action.validate(pool)?; // 排队前按当前参数边界检查
+
+        let change_id = pool.next_change_id;
+        pool.next_change_id = change_id
+            .checked_add(1)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+
+        let queued_change = &mut ctx.accounts.queued_change; // 初始化排队变更账户
+        queued_change.pool = pool.key();
+        queued_change.change_id = change_id;
+        queued_change.action = action;
+        queued_change.eta = eta;
+        queued_change.rent_payer = ctx.accounts.admin.key();
+        queued_change.bump = ctx.bumps.queued_change;
+
+        emit!(ChangeScheduledEvent {
+            pool: pool.key(),
+            change_id,
+            action,
+            eta
+        }); // 触发排队变更事件
+        Ok(()) // 返回成功
+    }
+
+    // 任何人都可以在 eta 之后执行已排队的变更，执行后关闭变更账户并返还租金
+    pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp; // 获取当前时间戳
+        let queued_change = &ctx.accounts.queued_change;
+        require_gte!(now, queued_change.eta, StakingError::TimelockNotReady); // 确保已到执行时间
+        let (change_id, action) = (queued_change.change_id, queued_change.action);
+
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        match action {
+            TimelockAction::UpdateRewardRate {
+                reward_index,
+                new_rate,
+            } => {
+                let reward_mint = pool.set_reward_rate(reward_index, new_rate)?; // 按执行时的状态重新校验
+                emit!(UpdateRewardRateEvent {
+                    pool: pool.key(),
+                    reward_mint,
+                    new_rate
+                }); // 触发更新奖励率事件
+            }
+            TimelockAction::UpdateLockupDuration { new_duration } => {
+                pool.bounds.check_lockup_duration(new_duration)?; // 按执行时的边界重新校验
+                pool.lockup_duration = new_duration; // 设置新锁定期
+                emit!(UpdateLockupDurationEvent {
+                    pool: pool.key(),
+                    new_duration
+                }); // 触发更新锁定期事件
+            }
+            TimelockAction::ProposeAdmin { new_admin } => {
+                pool.pending_admin = new_admin; // 提名仍需新管理员调用 accept_admin 接受
+                emit!(AdminTransferProposedEvent {
+                    pool: pool.key(),
+                    current_admin: pool.admin,
+                    pending_admin: new_admin
+                }); // 触发提名管理员事件
+            }
+            TimelockAction::UpdateTimelockDelay { new_delay } => {
+                pool.bounds.check_timelock_delay(new_delay)?; // 按执行时的边界重新校验
+                pool.timelock_delay = new_delay; // 设置新延迟
+                emit!(UpdateTimelockDelayEvent {
+                    pool: pool.key(),
+                    new_delay
+                }); // 触发更新时间锁延迟事件
+            }
+            TimelockAction::NotifyRewardAmount {
+                reward_index,
+                amount,
+                duration,
+            } => {
+                // 奖励已通过 fund_rewards 到账，这里只从未占用余额中划出 amount 开启新周期
+                let stream = pool.start_reward_period(reward_index, amount, duration)?;
+                emit!(NotifyRewardAmountEvent {
+                    pool: pool.key(),
+                    reward_mint: stream.mint,
+                    amount,
+                    net_amount: amount,
+                    duration,
+                    reward_rate: stream.reward_rate,
+                    period_finish: stream.period_finish
+                }); // 触发开启奖励周期事件
+            }
+            TimelockAction::RetireRewardStream { reward_index } => {
+                let reward_mint = pool.retire_stream(reward_index)?;
+                emit!(RetireRewardStreamEvent {
+                    pool: pool.key(),
+                    reward_index,
+                    reward_mint
+                }); // 触发退役奖励流事件
+            }
+            TimelockAction::UpdateCooldownDuration { new_duration } => {
+                pool.set_cooldown_duration(new_duration)?; // 按执行时的边界重新校验
+                emit!(UpdateCooldownDurationEvent {
+                    pool: pool.key(),
+                    new_duration
+                }); // 触发更新冷却期事件
+            }
+            TimelockAction::UpdateMaxBoost { max_boost_bps } => {
+                pool.set_max_boost(max_boost_bps)?;
+                emit!(UpdateMaxBoostEvent {
+                    pool: pool.key(),
+                    max_boost_bps
+                }); // 触发更新加速倍数事件
+            }
+            TimelockAction::UpdateEarlyUnstakePenalty {
+                penalty_bps,
+                scale_with_lock,
+            } => {
+                pool.set_early_unstake_penalty(penalty_bps, scale_with_lock)?;
+                emit!(UpdateEarlyUnstakePenaltyEvent {
+                    pool: pool.key(),
+                    penalty_bps,
+                    scale_with_lock
+                }); // 触发更新罚金事件
+            }
+            TimelockAction::UpdateMaxPauseDuration { new_duration } => {
+                pool.set_max_pause_duration(new_duration)?; // 按执行时的边界重新校验
+                emit!(UpdateMaxPauseDurationEvent {
+                    pool: pool.key(),
+                    new_duration
+                }); // 触发更新最长暂停时间事件
+            }
+            TimelockAction::UpdateParameterBounds { bounds } => {
+                pool.set_parameter_bounds(bounds)?; // 新边界必须容纳执行时生效的参数
+                emit!(UpdateParameterBoundsEvent {
+                    pool: pool.key(),
+                    bounds
+                }); // 触发更新参数边界事件
+            }
+            TimelockAction::UpdatePenaltyDestination {
+                destination,
+                treasury,
+            } => {
+                pool.set_penalty_destination(destination, treasury)?;
+                emit!(UpdatePenaltyDestinationEvent {
+                    pool: pool.key(),
+                    destination,
+                    treasury
+                }); // 触发更新罚金去向事件
+            }
+            TimelockAction::SetAutoCompound { enabled } => {
+                pool.set_auto_compound(enabled)?;
+                emit!(SetAutoCompoundEvent {
+                    pool: pool.key(),
+                    enabled
+                }); // 触发设置自动复利事件
+            }
+        }
+
+        emit!(ChangeExecutedEvent {
+            pool: pool.key(),
+            change_id,
+            action
+        }); // 触发执行变更事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员在 eta 之前撤销已排队的变更
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp; // 获取当前时间戳
+        let queued_change = &ctx.accounts.queued_change;
+        require_gt!(
+            queued_change.eta,
+            now,
+            StakingError::ChangeAlreadyExecutable
+        ); // 到期后只能执行
+        emit!(ChangeCancelledEvent {
+            pool: ctx.accounts.pool.key(),
+            change_id: queued_change.change_id,
+            action: queued_change.action
+        }); // 触发撤销变更事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员更新最长锁定期对应的最大加速倍数（基点），10000 表示不加速
+    pub fn update_max_boost(ctx: Context<AdminAction>, max_boost_bps: u16) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_max_boost(max_boost_bps)?; // 设置新倍数，用户的权重在下一次交互或 poke 时更新
+        emit!(UpdateMaxBoostEvent {
+            pool: pool.key(),
+            max_boost_bps
+        }); // 触发更新加速倍数事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员更新提前取消质押的罚金（基点），0 表示不允许提前退出；
+    // scale_with_lock 为 true 时罚金按批次剩余锁定期占总锁定期的比例线性递减
+    pub fn update_early_unstake_penalty(
+        ctx: Context<AdminAction>,
+        penalty_bps: u16,
+        scale_with_lock: bool,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_early_unstake_penalty(penalty_bps, scale_with_lock)?; // 设置罚金基点及是否按剩余锁定期递减
+        emit!(UpdateEarlyUnstakePenaltyEvent {
+            pool: pool.key(),
+            penalty_bps,
+            scale_with_lock
+        }); // 触发更新罚金事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员更新罚金去向；转入国库时 treasury 为接收罚金的质押代币账户，其他去向忽略该参数
+    pub fn update_penalty_destination(
+        ctx: Context<AdminAction>,
+        destination: PenaltyDestination,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_penalty_destination(destination, treasury)?; // 校验并设置罚金去向和国库地址
+        emit!(UpdatePenaltyDestinationEvent {
+            pool: pool.key(),
+            destination,
+            treasury
+        }); // 触发更新罚金去向事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员更新取消质押冷却期，0 表示关闭两阶段退出
+    pub fn update_cooldown_duration(ctx: Context<AdminAction>, new_duration: i64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_cooldown_duration(new_duration)?; // 校验并设置新冷却期
+        emit!(UpdateCooldownDurationEvent {
+            pool: pool.key(),
+            new_duration
+        }); // 触发更新冷却期事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员更新参数边界；新边界不能超过程序硬上限，且必须容纳当前生效的参数
+    pub fn update_parameter_bounds(
+        ctx: Context<AdminAction>,
+        bounds: ParameterBounds,
+    ) -> Result<()> {
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        require_eq!(pool.timelock_delay, 0, StakingError::TimelockRequired); // 开启时间锁后只能通过 schedule_change 修改
+        pool.set_parameter_bounds(bounds)?; // 校验并设置新边界
+        emit!(UpdateParameterBoundsEvent {
+            pool: pool.key(),
+            bounds
+        }); // 触发更新参数边界事件
+        Ok(()) // 返回成功
+    }
+
+    // 管理员更新偿付能力检查期限：调整奖励率时要求金库能覆盖该期限内的发放
+    pub fn update_solvency_horizon(ctx: Context<AdminAction>, new_horizon: i64) -> Result<()> {
+        require_gt!(new_horizon, 0, StakingError::InvalidSolvencyHorizon); // 确保期限大于 0
+        let pool = &mut ctx.accounts.pool; // 获取池子账户可变引用
+        pool.bounds.check_solvency_horizon(new_horizon)?; // 校验期限在边界内
+        pool.solvency_horizon = new_horizon; // 设置新期限
+        emit!(UpdateSolvencyHorizonEvent {
+            pool: pool.key(),
+            new_horizon
+        }); // 触发更新偿付能力检查期限事件
+        Ok(()) // 返回成功
+    }
+}
+
+// 定义账户结构和约束
+
+// 初始化指令的账户结构
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+pub struct Initialize<'info> {
+    // 初始化池子账户，分配空间并设置 PDA 种子（质押代币 + 池子编号）
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Pool::INIT_SPACE,
+        seeds = [b"pool", staking_mint.key().as_ref(), &pool_id.to_le_bytes()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)] // 管理员签名者，支付初始化费用
+    pub admin: Signer<'info>,
+    #[account(mint::token_program = token_program)] // 质押代币 Mint 账户，需属于 token_program
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    // 初始化质押金库，权限归池子
+    #[account(init, payer = admin, token::mint = staking_mint, token::authority = pool, token::token_program = token_program, seeds = [b"staking_vault", pool.key().as_ref()], bump
+    )]
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    // 初始奖励代币 Mint 账户，需属于 reward_token_program，可与质押代币使用不同的 Token 程序
+    #[account(mint::token_program = reward_token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    // 初始化奖励金库，权限归池子
+    #[account(init, payer = admin, token::mint = reward_mint, token::authority = pool, token::token_program = reward_token_program, seeds = [b"reward_vault", pool.key().as_ref(), reward_mint.key().as_ref()], bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>, // 系统程序，用于账户创建
+    pub token_program: Interface<'info, TokenInterface>, // 质押代币所属的 Token 程序
+    pub reward_token_program: Interface<'info, TokenInterface>, // 奖励代币所属的 Token 程序
+    pub rent: Sysvar<'info, Rent>,              // 租金 Sysvar，用于账户空间计算
+}
+
+// 质押指令的账户结构
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)] // 用户签名者
+    pub user: Signer<'info>,
+    // 池子账户，验证 PDA 和质押金库
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户，按需初始化
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserStakeInfo::INIT_SPACE,
+        seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+    // 用户质押代币钱包，验证 Mint 匹配
+    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
+    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)] // 质押金库，可变
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    // 质押代币的 Mint 账户，用于 transfer_checked
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>, // 系统程序
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 取消质押指令的账户结构
+#[derive(Accounts)]
+pub struct UnStake<'info> {
+    #[account(mut)] // 用户签名者
+    pub user: Signer<'info>,
+    // 池子账户，验证 PDA、质押金库和质押代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+    // 用户质押代币钱包
+    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
+    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 提前取消质押指令的账户结构
+#[derive(Accounts)]
+pub struct UnstakeEarly<'info> {
+    #[account(mut)] // 用户签名者
+    pub user: Signer<'info>,
+    // 池子账户，验证 PDA、质押金库和质押代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+    // 用户质押代币钱包
+    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
+    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, mint::token_program = token_program)] // 质押代币 Mint，销毁罚金时供应量减少
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    // 罚金转入国库时的国库账户，在指令中校验
+    #[account(mut)]
+    pub treasury: Option<InterfaceAccount<'info, TokenAccount>>,
+    // 罚金分配给剩余质押者时的质押代币奖励金库，在指令中校验
+    #[account(mut)]
+    pub reward_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 发起或撤销取消质押请求的账户结构
+#[derive(Accounts)]
+pub struct UnbondingAction<'info> {
+    pub user: Signer<'info>, // 用户签名者
+    // 池子账户，验证 PDA
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+}
+
+// 重新加权指令的账户结构，无需签名
+#[derive(Accounts)]
+pub struct Poke<'info> {
+    // 池子账户，验证 PDA
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump)]
+    pub pool: Account<'info, Pool>,
+    // 被重新加权的用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user_stake_info.user.as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+}
+
+// 池子只读视图指令的账户结构，无需签名
+#[derive(Accounts)]
+pub struct PoolView<'info> {
+    // 池子账户，验证 PDA 和质押金库
+    #[account(seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault)]
+    pub pool: Account<'info, Pool>,
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>, // 质押金库
+}
+
+// 用户只读视图指令的账户结构，无需签名
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct UserView<'info> {
+    // 池子账户，验证 PDA
+    #[account(seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump)]
+    pub pool: Account<'info, Pool>,
+    // 被查询用户的质押信息账户
+    #[account(seeds = [b"stake_info", pool.key().as_ref(), user.as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+}
+
+// 领取奖励指令的账户结构
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)] // 用户签名者
+    pub user: Signer<'info>,
+    // 池子账户，验证 PDA（奖励金库在指令中按奖励流校验）
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+    // 用户奖励代币钱包
+    #[account(mut, constraint = user_reward_wallet.mint == reward_mint.key())]
+    pub user_reward_wallet: InterfaceAccount<'info, TokenAccount>,
+    // 奖励金库，按池子和奖励代币重新派生，奖励流索引在指令中校验
+    #[account(mut, seeds = [b"reward_vault", pool.key().as_ref(), reward_mint.key().as_ref()], bump, token::mint = reward_mint, token::authority = pool, token::token_program = token_program)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 关闭用户质押信息账户的结构
+#[derive(Accounts)]
+pub struct CloseUserStakeInfo<'info> {
+    #[account(mut)] // 用户签名者
+    pub user: Signer<'info>,
+    // 池子账户，用于派生用户质押信息 PDA
+    #[account(seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户，关闭后租金返还
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()], bump, close = user)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+}
+
+// 管理员操作指令的账户结构
+#[derive(Accounts)]
+pub struct AdminAction<'info> {
+    // 池子账户，验证管理员权限
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = admin)]
+    pub pool: Account<'info, Pool>,
+    pub admin: Signer<'info>, // 管理员签名者
+}
+
+// 排队参数变更的账户结构
+#[derive(Accounts)]
+pub struct ScheduleChange<'info> {
+    // 池子账户，验证管理员权限
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = admin)]
+    pub pool: Account<'info, Pool>,
+    // 排队变更账户，按池子的变更序号派生
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + QueuedChange::INIT_SPACE,
+        seeds = [b"queued_change", pool.key().as_ref(), &pool.next_change_id.to_le_bytes()],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedChange>,
+    #[account(mut)] // 管理员签名者，支付变更账户租金
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>, // 系统程序
+}
+
+// 执行排队变更的账户结构，任何人都可以调用
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump)]
+    pub pool: Account<'info, Pool>,
+    // 排队变更账户，执行后关闭并把租金返还给支付者
+    #[account(mut, seeds = [b"queued_change", pool.key().as_ref(), &queued_change.change_id.to_le_bytes()], bump = queued_change.bump, has_one = pool, has_one = rent_payer, close = rent_payer)]
+    pub queued_change: Account<'info, QueuedChange>,
+    #[account(mut)] // 排队时支付租金的账户
+    pub rent_payer: SystemAccount<'info>,
+}
+
+// 撤销排队变更的账户结构
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    // 池子账户，验证管理员权限
+    #[account(seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = admin)]
+    pub pool: Account<'info, Pool>,
+    // 排队变更账户，撤销后关闭并把租金返还给支付者
+    #[account(mut, seeds = [b"queued_change", pool.key().as_ref(), &queued_change.change_id.to_le_bytes()], bump = queued_change.bump, has_one = pool, has_one = rent_payer, close = rent_payer)]
+    pub queued_change: Account<'info, QueuedChange>,
+    pub admin: Signer<'info>, // 管理员签名者
+    #[account(mut)] // 排队时支付租金的账户
+    pub rent_payer: SystemAccount<'info>,
+}
+
+// 接受管理员权限的账户结构
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    // 池子账户，验证签名者是被提名的新管理员
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, constraint = pool.pending_admin == new_admin.key() @ StakingError::NotPendingAdmin)]
+    pub pool: Account<'info, Pool>,
+    pub new_admin: Signer<'info>, // 被提名的新管理员签名者
+}
+
+// 暂停操作的账户结构
+#[derive(Accounts)]
+pub struct PauserAction<'info> {
+    // 池子账户，验证签名者持有暂停者角色
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, constraint = pool.has_role(Role::Pauser, &pauser.key()) @ StakingError::MissingRole)]
+    pub pool: Account<'info, Pool>,
+    pub pauser: Signer<'info>, // 暂停者签名者
+}
+
+// 奖励率和锁定期管理操作的账户结构
+#[derive(Accounts)]
+pub struct RateManagerAction<'info> {
+    // 池子账户，验证签名者持有奖励率管理者角色
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, constraint = pool.has_role(Role::RateManager, &rate_manager.key()) @ StakingError::MissingRole)]
+    pub pool: Account<'info, Pool>,
+    pub rate_manager: Signer<'info>, // 奖励率管理者签名者
+}
+
+// 注资奖励金库的账户结构
+#[derive(Accounts)]
+pub struct FundRewards<'info> {
+    // 池子账户，验证 PDA 和注资者角色（开启奖励周期时需更新奖励状态）
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, constraint = pool.has_role(Role::Funder, &funder.key()) @ StakingError::MissingRole)]
+    pub pool: Account<'info, Pool>,
+    pub funder: Signer<'info>, // 注资者签名者
+    // 注资者的奖励代币钱包
+    #[account(mut, constraint = funder_wallet.mint == reward_mint.key())]
+    pub funder_wallet: InterfaceAccount<'info, TokenAccount>,
+    // 奖励金库，按池子和奖励代币重新派生，奖励流索引在指令中校验
+    #[account(mut, seeds = [b"reward_vault", pool.key().as_ref(), reward_mint.key().as_ref()], bump, token::mint = reward_mint, token::authority = pool, token::token_program = token_program)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    // 奖励代币的 Mint 账户，用于 transfer_checked
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 紧急提取质押代币的账户结构
+#[derive(Accounts)]
+pub struct EmergencyWithdrawStaked<'info> {
+    // 池子账户，验证 PDA、守护者角色、质押金库和质押代币（关停时需要更新状态）
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint, constraint = pool.has_role(Role::Guardian, &guardian.key()) @ StakingError::MissingRole)]
+    pub pool: Account<'info, Pool>,
+    pub guardian: Signer<'info>, // 守护者签名者
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = staking_mint, token::token_program = token_program)] // 目标钱包
+    pub destination_wallet: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 紧急退出（关停后的 emergency_exit 和暂停期间的 emergency_unstake）的账户结构
+#[derive(Accounts)]
+pub struct EmergencyExit<'info> {
+    pub user: Signer<'info>, // 用户签名者
+    // 池子账户，验证 PDA、质押金库和质押代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user.key().as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+    // 用户质押代币钱包
+    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
+    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 紧急提取奖励代币的账户结构
+#[derive(Accounts)]
+pub struct EmergencyWithdrawRewards<'info> {
+    // 池子账户，验证 PDA 和守护者角色（奖励流索引在指令中校验）
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, constraint = pool.has_role(Role::Guardian, &guardian.key()) @ StakingError::MissingRole)]
+    pub pool: Account<'info, Pool>,
+    pub guardian: Signer<'info>, // 守护者签名者
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    // 奖励金库，按池子和奖励代币重新派生
+    #[account(mut, seeds = [b"reward_vault", pool.key().as_ref(), reward_mint.key().as_ref()], bump, token::mint = reward_mint, token::authority = pool, token::token_program = token_program)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = reward_mint, token::token_program = token_program)] // 目标钱包
+    pub destination_wallet: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 新增奖励流的账户结构
+#[derive(Accounts)]
+pub struct AddRewardStream<'info> {
+    // 池子账户，验证管理员权限
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = admin)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)] // 管理员签名者，支付奖励金库租金
+    pub admin: Signer<'info>,
+    #[account(mint::token_program = token_program)]
+    pub reward_mint: InterfaceAccount<'info, Mint>, // 新奖励代币 Mint 账户
+    // 初始化新奖励金库，权限归池子
+    #[account(init, payer = admin, token::mint = reward_mint, token::authority = pool, token::token_program = token_program, seeds = [b"reward_vault", pool.key().as_ref(), reward_mint.key().as_ref()], bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    pub system_program: Program<'info, System>, // 系统程序，用于账户创建
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 复利指令的账户结构
+#[derive(Accounts)]
+pub struct Compound<'info> {
+    pub caller: Signer<'info>, // 调用者，未开启自动复利时必须是用户本人
+    // 池子账户，验证 PDA、质押金库和质押代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint)]
+    pub pool: Account<'info, Pool>,
+    // 被复利的用户质押信息账户
+    #[account(mut, seeds = [b"stake_info", pool.key().as_ref(), user_stake_info.user.as_ref()], bump)]
+    pub user_stake_info: Account<'info, UserStakeInfo>,
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    // 奖励代币为质押代币的奖励金库，按池子和质押代币重新派生，奖励流在指令中校验
+    #[account(mut, seeds = [b"reward_vault", pool.key().as_ref(), staking_mint.key().as_ref()], bump, token::mint = staking_mint, token::authority = pool, token::token_program = token_program)]
     pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 开启收据代币的账户结构
+#[derive(Accounts)]
+pub struct EnableReceiptToken<'info> {
+    // 池子账户，验证管理员权限和质押代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = admin, has_one = staking_mint)]
+    pub pool: Account<'info, Pool>,
+    #[account(mut)] // 管理员签名者，支付收据代币 Mint 租金
+    pub admin: Signer<'info>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>, // 质押代币 Mint 账户，收据代币沿用其小数位数
+    // 初始化收据代币 Mint，权限归池子，每个池子只能创建一次
+    #[account(init, payer = admin, mint::decimals = staking_mint.decimals, mint::authority = pool, mint::token_program = token_program, seeds = [b"receipt_mint", pool.key().as_ref()], bump
+    )]
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
     pub system_program: Program<'info, System>, // 系统程序，用于账户创建
-    pub token_program: Program<'info, Token2022>, // Token 程序，用于代币操作
-    pub rent: Sysvar<'info, Rent>,              // 租金 Sysvar，用于账户空间计算
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 流动性质押指令的账户结构
+#[derive(Accounts)]
+pub struct StakeLiquid<'info> {
+    pub user: Signer<'info>, // 用户签名者
+    // 池子账户，验证 PDA、质押金库和收据代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint, has_one = receipt_mint)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押代币钱包，验证 Mint 匹配
+    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
+    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
+    // 用户收据代币钱包，验证 Mint 匹配
+    #[account(mut, constraint = user_receipt_wallet.mint == pool.receipt_mint)]
+    pub user_receipt_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, mint::token_program = token_program)] // 收据代币 Mint
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 流动性取消质押指令的账户结构
+#[derive(Accounts)]
+pub struct UnstakeLiquid<'info> {
+    pub user: Signer<'info>, // 收据代币持有人签名者
+    // 池子账户，验证 PDA、质押金库和收据代币
+    #[account(mut, seeds = [b"pool", pool.staking_mint.as_ref(), &pool.pool_id.to_le_bytes()], bump = pool.pool_bump, has_one = staking_vault, has_one = staking_mint, has_one = receipt_mint)]
+    pub pool: Account<'info, Pool>,
+    // 用户质押代币钱包，接收本金和奖励
+    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
+    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
+    // 用户收据代币钱包，份额从中销毁
+    #[account(mut, constraint = user_receipt_wallet.mint == pool.receipt_mint)]
+    pub user_receipt_wallet: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)] // 质押金库
+    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mint::token_program = token_program)]
+    pub staking_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, mint::token_program = token_program)] // 收据代币 Mint
+    pub receipt_mint: InterfaceAccount<'info, Mint>,
+    // 奖励代币为质押代币的奖励流金库，份额桶有累积奖励时必须提供，在指令中校验
+    #[account(mut)]
+    pub reward_vault: Option<InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Interface<'info, TokenInterface>, // Token 程序
+}
+
+// 定义状态账户结构
+
+// 每个池子最多同时维护的奖励流数量
+pub const MAX_REWARD_STREAMS: usize = 3;
+
+// 单条奖励流，拥有独立的奖励代币、金库、奖励率和累计指数
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct RewardStream {
+    pub mint: Pubkey,                  // 奖励代币 Mint 地址，默认值表示空闲槽位
+    pub vault: Pubkey,                 // 奖励金库地址
+    pub reward_rate: u64,              // 每秒奖励代币数量
+    pub reward_per_token_stored: u128, // 每单位代币的累计奖励（高精度）
+    pub period_finish: i64,            // 当前奖励周期结束时间戳，之后停止累积
+    pub vault_balance: u64,            // 奖励金库账本余额（注资减去已支付）
+    pub total_rewards_owed: u64,       // 已累积但尚未领取的奖励（负债）
+    pub is_active: bool,               // 是否仍在发放，退役后停止累积但可领取
+    pub mint_extensions: u16,          // 奖励代币 Mint 的扩展（MINT_EXT_* 位掩码）
+}
+
+// 池子状态账户，存储全局质押信息
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub admin: Pubkey,                                            // 管理员公钥
+    pub pending_admin: Pubkey, // 已提名、待接受的新管理员，默认值表示没有提名
+    pub pauser: Pubkey,        // 暂停者，只能暂停程序，默认值表示未授予
+    pub rate_manager: Pubkey,  // 奖励率管理者，可调整奖励率和锁定期
+    pub funder: Pubkey,        // 注资者，可从任意钱包注资奖励
+    pub guardian: Pubkey,      // 守护者，可执行紧急提取
+    pub pool_id: u64,          // 池子编号，由管理员选择，参与 PDA 派生
+    pub staking_mint: Pubkey,  // 质押代币 Mint 地址
+    pub staking_vault: Pubkey, // 质押金库地址
+    pub staking_mint_extensions: u16, // 质押代币 Mint 的扩展（MINT_EXT_* 位掩码）
+    pub reward_streams: [RewardStream; MAX_REWARD_STREAMS], // 奖励流列表
+    pub last_update_timestamp: i64, // 最后更新时间戳
+    pub total_staked: u64,     // 总质押量
+    pub total_weighted_stake: u64, // 按锁定期加权后的总质押量，用于分配奖励
+    pub total_unbonding: u64,  // 冷却中、已停止计息但尚未提取的质押量
+    pub pool_bump: u8,         // 池子 PDA bump 值
+    pub lockup_duration: i64,  // 锁定期（秒）
+    pub max_boost_bps: u16,    // 锁定 4 年对应的最大加速倍数（基点）
+    pub cooldown_duration: i64, // 取消质押冷却期（秒），0 表示可直接 unstake
+    pub solvency_horizon: i64, // 调整奖励率时的偿付能力检查期限（秒）
+    pub timelock_delay: i64,   // 参数变更的最短排队时间（秒），0 表示不启用时间锁
+    pub next_change_id: u64,   // 下一个排队变更的序号，参与变更 PDA 派生
+    pub bounds: ParameterBounds, // 管理员可设置参数的上下限
+    pub is_shutdown: bool,     // 紧急提取质押代币后进入的不可逆关停状态
+    pub shutdown_claims: u64,  // 关停后尚未退出的本金债权总额
+    pub shutdown_balance: u64, // 关停后尚未分配的质押金库余额
+    pub is_paused: bool,       // 程序暂停状态
+    pub paused_at: i64,        // 最近一次暂停的时间戳
+    pub max_pause_duration: i64, // 最长暂停时间（秒），超过后自动按未暂停处理，0 表示不限制
+    pub receipt_mint: Pubkey,  // 收据代币 Mint 地址，默认值表示未开启
+    pub liquid_principal: u64, // 收据代币份额桶中的质押本金，按 1x 权重计入总质押量
+    pub liquid_rewards: u64,   // 份额桶累积的质押代币奖励，计入份额价格
+    pub liquid_reward_per_token_paid: [u128; MAX_REWARD_STREAMS], // 份额桶在每条奖励流的奖励基准
+    pub auto_compound: bool,   // 是否开启自动复利
+    pub early_unstake_penalty_bps: u16, // 提前取消质押的罚金（基点），0 表示不允许
+    pub penalty_scales_with_lock: bool, // 罚金是否按剩余锁定期线性递减
+    pub penalty_destination: PenaltyDestination, // 罚金去向
+    pub penalty_treasury: Pubkey, // 接收罚金的国库账户
+    pub penalties_burned: u64, // 累计销毁的罚金
+    pub penalties_to_treasury: u64, // 累计转入国库的罚金
+    pub penalties_redistributed: u64, // 累计分配给剩余质押者的罚金
+}
+
+// 可由管理员授予的权限角色，管理员本身始终拥有全部角色
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    Pauser,      // 暂停程序
+    RateManager, // 调整奖励率和锁定期
+    Funder,      // 注资奖励
+    Guardian,    // 紧急提取
+}
+
+// 管理员可设置参数的上下限，每个 setter 都会按此校验
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub struct ParameterBounds {
+    pub min_lockup_duration: i64,   // 最短锁定期（秒）
+    pub max_lockup_duration: i64,   // 最长锁定期（秒）
+    pub max_reward_rate: u64,       // 每条奖励流的最大奖励率
+    pub max_cooldown_duration: i64, // 最长取消质押冷却期（秒）
+    pub max_timelock_delay: i64,    // 最长时间锁延迟（秒）
+    pub max_pause_duration: i64,    // 最长暂停时间的上限（秒），0 表示允许不限制暂停时间
+    pub min_solvency_horizon: i64,  // 最短偿付能力检查期限（秒）
+    pub max_solvency_horizon: i64,  // 最长偿付能力检查期限（秒）
+    pub min_reward_duration: i64,   // 最短奖励周期（秒）
+    pub max_reward_duration: i64,   // 最长奖励周期（秒）
+}
+
+impl Default for ParameterBounds {
+    // 默认边界即程序硬上限
+    fn default() -> Self {
+        ParameterBounds {
+            min_lockup_duration: 0,
+            max_lockup_duration: MAX_LOCKUP_DURATION,
+            max_reward_rate: MAX_REWARD_RATE,
+            max_cooldown_duration: MAX_COOLDOWN_DURATION,
+            max_timelock_delay: MAX_TIMELOCK_DELAY,
+            max_pause_duration: 0,
+            min_solvency_horizon: 1,
+            max_solvency_horizon: MAX_SOLVENCY_HORIZON,
+            min_reward_duration: 1,
+            max_reward_duration: MAX_REWARD_DURATION,
+        }
+    }
+}
+
+impl ParameterBounds {
+    // 校验边界本身：下限不大于上限，且上限不超过程序硬上限
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            0 <= self.min_lockup_duration
+                && self.min_lockup_duration <= self.max_lockup_duration
+                && self.max_lockup_duration <= MAX_LOCKUP_DURATION,
+            StakingError::InvalidParameterBounds
+        );
+        require!(
+            self.max_reward_rate <= MAX_REWARD_RATE,
+            StakingError::InvalidParameterBounds
+        );
+        require!(
+            (0..=MAX_COOLDOWN_DURATION).contains(&self.max_cooldown_duration),
+            StakingError::InvalidParameterBounds
+        );
+        require!(
+            (0..=MAX_TIMELOCK_DELAY).contains(&self.max_timelock_delay),
+            StakingError::InvalidParameterBounds
+        );
+        require!(
+            (0..=MAX_PAUSE_DURATION).contains(&self.max_pause_duration),
+            StakingError::InvalidParameterBounds
+        );
+        require!(
+            1 <= self.min_solvency_horizon
+                && self.min_solvency_horizon <= self.max_solvency_horizon
+                && self.max_solvency_horizon <= MAX_SOLVENCY_HORIZON,
+            StakingError::InvalidParameterBounds
+        );
+        require!(
+            1 <= self.min_reward_duration
+                && self.min_reward_duration <= self.max_reward_duration
+                && self.max_reward_duration <= MAX_REWARD_DURATION,
+            StakingError::InvalidParameterBounds
+        );
+        Ok(())
+    }
+
+    pub fn check_lockup_duration(&self, duration: i64) -> Result<()> {
+        require!(
+            (self.min_lockup_duration..=self.max_lockup_duration).contains(&duration),
+            StakingError::LockupDurationOutOfBounds
+        );
+        Ok(())
+    }
+
+    pub fn check_cooldown_duration(&self, duration: i64) -> Result<()> {
+        require!(
+            (0..=self.max_cooldown_duration).contains(&duration),
+            StakingError::CooldownDurationOutOfBounds
+        );
+        Ok(())
+    }
+
+    pub fn check_timelock_delay(&self, delay: i64) -> Result<()> {
+        require!(
+            (0..=self.max_timelock_delay).contains(&delay),
+            StakingError::TimelockDelayOutOfBounds
+        );
+        Ok(())
+    }
+
+    // 设置了上限时暂停时间必须有限（不能为 0），且不超过上限；未设置上限时不超过程序硬上限
+    pub fn check_max_pause_duration(&self, duration: i64) -> Result<()> {
+        let allowed = if self.max_pause_duration == 0 {
+            (0..=MAX_PAUSE_DURATION).contains(&duration)
+        } else {
+            (1..=self.max_pause_duration).contains(&duration)
+        };
+        require!(allowed, StakingError::PauseDurationOutOfBounds);
+        Ok(())
+    }
+
+    pub fn check_solvency_horizon(&self, horizon: i64) -> Result<()> {
+        require!(
+            (self.min_solvency_horizon..=self.max_solvency_horizon).contains(&horizon),
+            StakingError::SolvencyHorizonOutOfBounds
+        );
+        Ok(())
+    }
+
+    pub fn check_reward_duration(&self, duration: i64) -> Result<()> {
+        require!(
+            (self.min_reward_duration..=self.max_reward_duration).contains(&duration),
+            StakingError::RewardDurationOutOfBounds
+        );
+        Ok(())
+    }
+
+    // 校验奖励率不超过上限，并模拟最坏情况（总加权质押量为 1）下
+    // 持续 REWARD_RATE_SIMULATION_HORIZON 后累加器和奖励负债都不会溢出
+    pub fn check_reward_rate(&self, reward_rate: u64, reward_per_token_stored: u128) -> Result<()> {
+        require_gte!(
+            self.max_reward_rate,
+            reward_rate,
+            StakingError::RewardRateAboveMax
+        );
+        let emissions = (reward_rate as u128)
+            .checked_mul(REWARD_RATE_SIMULATION_HORIZON as u128)
+            .filter(|e| *e <= u64::MAX as u128) // 奖励负债以 u64 记账
+            .ok_or(StakingError::RewardRateOverflow)?;
+        emissions
+            .checked_mul(PRECISION)
+            .and_then(|increment| reward_per_token_stored.checked_add(increment))
+            .ok_or(StakingError::RewardRateOverflow)?;
+        Ok(())
+    }
+}
+
+// 可通过时间锁排队的参数变更
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, InitSpace)]
+pub enum TimelockAction {
+    // 更新奖励率
+    UpdateRewardRate {
+        reward_index: u8,
+        new_rate: u64,
+    },
+    // 更新锁定期
+    UpdateLockupDuration {
+        new_duration: i64,
+    },
+    // 提名新管理员
+    ProposeAdmin {
+        new_admin: Pubkey,
+    },
+    // 修改时间锁延迟
+    UpdateTimelockDelay {
+        new_delay: i64,
+    },
+    // 从已注资的未占用余额中划出 amount 开启新奖励周期
+    NotifyRewardAmount {
+        reward_index: u8,
+        amount: u64,
+        duration: i64,
+    },
+    // 退役奖励流
+    RetireRewardStream {
+        reward_index: u8,
+    },
+    // 更新冷却期
+    UpdateCooldownDuration {
+        new_duration: i64,
+    },
+    // 更新最大加速倍数
+    UpdateMaxBoost {
+        max_boost_bps: u16,
+    },
+    // 更新提前取消质押罚金
+    UpdateEarlyUnstakePenalty {
+        penalty_bps: u16,
+        scale_with_lock: bool,
+    },
+    // 更新最长暂停时间
+    UpdateMaxPauseDuration {
+        new_duration: i64,
+    },
+    // 更新参数边界
+    UpdateParameterBounds {
+        bounds: ParameterBounds,
+    },
+    // 更新罚金去向
+    UpdatePenaltyDestination {
+        destination: PenaltyDestination,
+        treasury: Pubkey,
+    },
+    // 开启或关闭自动复利
+    SetAutoCompound {
+        enabled: bool,
+    },
+}
+
+impl TimelockAction {
+    // 排队时按当前参数边界检查，依赖奖励周期等状态的检查在执行时进行
+    pub fn validate(&self, pool: &Pool) -> Result<()> {
+        match *self {
+            TimelockAction::UpdateRewardRate {
+                reward_index,
+                new_rate,
+            } => {
+                let stream = pool.reward_stream(reward_index)?;
+                pool.bounds
+                    .check_reward_rate(new_rate, stream.reward_per_token_stored)?;
+            }
+            TimelockAction::ProposeAdmin { new_admin } => {
+                require_keys_neq!(
+                    new_admin,
+                    Pubkey::default(),
+                    StakingError::InvalidPendingAdmin
+                );
+            }
+            TimelockAction::UpdateTimelockDelay { new_delay } => {
+                pool.bounds.check_timelock_delay(new_delay)?;
+            }
+            TimelockAction::UpdateLockupDuration { new_duration } => {
+                pool.bounds.check_lockup_duration(new_duration)?;
+            }
+            TimelockAction::NotifyRewardAmount {
+                reward_index,
+                amount,
+                duration,
+            } => {
+                require_gt!(amount, 0, StakingError::ZeroFundAmount);
+                require_gt!(duration, 0, StakingError::InvalidRewardDuration);
+                pool.bounds.check_reward_duration(duration)?;
+                require!(
+                    pool.reward_stream(reward_index)?.is_active,
+                    StakingError::RewardStreamInactive
+                );
+            }
+            TimelockAction::RetireRewardStream { reward_index } => {
+                require!(
+                    pool.reward_stream(reward_index)?.is_active,
+                    StakingError::RewardStreamInactive
+                );
+            }
+            TimelockAction::UpdateCooldownDuration { new_duration } => {
+                pool.bounds.check_cooldown_duration(new_duration)?;
+            }
+            TimelockAction::UpdateMaxBoost { max_boost_bps } => {
+                check_max_boost(max_boost_bps)?;
+            }
+            TimelockAction::UpdateEarlyUnstakePenalty { penalty_bps, .. } => {
+                check_early_unstake_penalty(penalty_bps)?;
+            }
+            TimelockAction::UpdateMaxPauseDuration { new_duration } => {
+                require_gte!(new_duration, 0, StakingError::InvalidPauseDuration);
+                pool.bounds.check_max_pause_duration(new_duration)?;
+            }
+            TimelockAction::UpdateParameterBounds { bounds } => {
+                bounds.validate()?; // 是否容纳当前参数在执行时检查
+            }
+            TimelockAction::UpdatePenaltyDestination {
+                destination,
+                treasury,
+            } => {
+                pool.check_penalty_destination(destination, treasury)?;
+            }
+            TimelockAction::SetAutoCompound { enabled } => {
+                if enabled {
+                    pool.compound_stream_index()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 排队中的参数变更，每项变更一个 PDA，便于前端展示即将生效的变更
+#[account]
+#[derive(InitSpace)]
+pub struct QueuedChange {
+    pub pool: Pubkey,           // 所属池子
+    pub change_id: u64,         // 变更序号
+    pub action: TimelockAction, // 变更内容
+    pub eta: i64,               // 最早可执行时间
+    pub rent_payer: Pubkey,     // 支付租金的账户，变更关闭时返还
+    pub bump: u8,               // PDA bump 值
+}
+
+// 提前取消质押罚金的去向
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, Debug, InitSpace,
+)]
+pub enum PenaltyDestination {
+    #[default]
+    Burn, // 从质押金库销毁
+    Treasury,     // 转入国库
+    Redistribute, // 通过质押代币奖励流分配给剩余质押者
+}
+
+// 每个用户在每个池子中最多同时持有的质押批次数量
+pub const MAX_STAKE_LOTS: usize = 16;
+
+// 单笔质押批次，拥有独立的金额和解锁时间
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct StakeLot {
+    pub amount: u64,           // 批次剩余金额
+    pub unlock_timestamp: i64, // 批次解锁时间戳
+    pub lock_duration: i64,    // 批次存入时的锁定期（秒），用于按比例计算提前退出罚金
+}
+
+// 与 staking_math 的纯数值批次互相转换，批次记账由 staking_math 完成
+impl From<Lot> for StakeLot {
+    fn from(lot: Lot) -> Self {
+        Self {
+            amount: lot.amount,
+            unlock_timestamp: lot.unlock_timestamp,
+            lock_duration: lot.lock_duration,
+        }
+    }
+}
+
+impl From<StakeLot> for Lot {
+    fn from(lot: StakeLot) -> Self {
+        Self {
+            amount: lot.amount,
+            unlock_timestamp: lot.unlock_timestamp,
+            lock_duration: lot.lock_duration,
+        }
+    }
+}
+
+impl From<LotError> for StakingError {
+    fn from(err: LotError) -> Self {
+        match err {
+            LotError::ArithmeticOverflow => StakingError::ArithmeticOverflow,
+            LotError::TooManyStakeLots => StakingError::TooManyStakeLots,
+            LotError::LockupPeriodNotEnded => StakingError::LockupPeriodNotEnded,
+        }
+    }
+}
+
+// 每个用户在每个池子中最多同时存在的取消质押请求数量
+pub const MAX_UNBONDING_REQUESTS: usize = 8;
+
+// 冷却中的取消质押请求
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct UnbondingRequest {
+    pub amount: u64,       // 请求提取的金额
+    pub available_at: i64, // 冷却期结束、可提取的时间戳
+}
+
+// 用户质押信息账户，存储用户特定数据
+#[account]
+#[derive(Default, InitSpace)]
+pub struct UserStakeInfo {
+    pub pool: Pubkey,               // 所属池子地址
+    pub user: Pubkey,               // 所属用户公钥
+    pub stake_amount: u64,          // 用户质押金额
+    pub weighted_amount: u64,       // 上次同步时按锁定期加权后的质押量
+    pub stake_start_timestamp: i64, // 质押开始时间戳
+    #[max_len(MAX_STAKE_LOTS)]
+    pub lots: Vec<StakeLot>, // 质押批次，按存入顺序排列，金额之和等于 stake_amount
+    #[max_len(MAX_UNBONDING_REQUESTS)]
+    pub unbonding: Vec<UnbondingRequest>, // 冷却中的取消质押请求
+    pub reward_per_token_paid: [u128; MAX_REWARD_STREAMS], // 用户在每条奖励流上次同步的奖励基准
+    pub rewards: [u64; MAX_REWARD_STREAMS], // 每条奖励流已计算但未领取的奖励
+}
+
+// 只读视图的返回值，均按调用时的区块链时间结算
+
+// get_pending_rewards 的返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PendingRewardsView {
+    pub pool: Pubkey,                               // 池子地址
+    pub user: Pubkey,                               // 用户公钥
+    pub timestamp: i64,                             // 结算时间戳
+    pub reward_mints: [Pubkey; MAX_REWARD_STREAMS], // 每条奖励流的奖励代币 Mint，默认值表示空闲槽位
+    pub rewards: [u64; MAX_REWARD_STREAMS],         // 每条奖励流的待领奖励
+}
+
+// get_user_position 的返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct UserPositionView {
+    pub pool: Pubkey,                       // 池子地址
+    pub user: Pubkey,                       // 用户公钥
+    pub timestamp: i64,                     // 结算时间戳
+    pub stake_amount: u64,                  // 质押金额
+    pub locked_amount: u64,                 // 仍处于锁定期的金额
+    pub unlocked_amount: u64,               // 已解锁、可取消质押的金额
+    pub next_unlock_timestamp: i64,         // 最近一个锁定批次的解锁时间，0 表示没有锁定中的批次
+    pub final_unlock_timestamp: i64,        // 最后一个锁定批次的解锁时间，0 表示没有锁定中的批次
+    pub stake_start_timestamp: i64,         // 质押开始时间戳
+    pub weighted_amount: u64,               // 上次同步的加权质押量，决定当前的奖励份额
+    pub current_weight: u64,                // 按当前剩余锁定期计算的加权质押量，poke 后生效
+    pub unbonding_amount: u64,              // 冷却中的取消质押总额
+    pub withdrawable_amount: u64,           // 冷却期已结束、可提取的金额
+    pub rewards: [u64; MAX_REWARD_STREAMS], // 每条奖励流的待领奖励
+}
+
+// 奖励流的只读视图，包含计算年化收益率所需的输入
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct RewardStreamView {
+    pub mint: Pubkey,                  // 奖励代币 Mint 地址，默认值表示空闲槽位
+    pub vault: Pubkey,                 // 奖励金库地址
+    pub reward_rate: u64,              // 配置的每秒奖励代币数量
+    pub current_reward_rate: u64,      // 当前实际发放速率，周期结束后为 0
+    pub period_finish: i64,            // 当前奖励周期结束时间戳
+    pub reward_per_token_stored: u128, // 每单位加权质押的累计奖励（高精度）
+    pub vault_balance: u64,            // 奖励金库账本余额
+    pub vault_amount: u64,             // 奖励金库的实际代币余额，可能因直接转入而高于账本余额
+    pub total_rewards_owed: u64,       // 已累积但尚未领取的奖励
+    pub unreserved_balance: u64,       // 可继续发放的余额
+    pub is_active: bool,               // 是否仍在发放
+}
+
+// get_pool_state 的返回值
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PoolStateView {
+    pub pool: Pubkey,                                           // 池子地址
+    pub timestamp: i64,                                         // 结算时间戳
+    pub is_paused: bool,            // 当前是否暂停（已考虑最长暂停时间）
+    pub is_shutdown: bool,          // 是否已关停
+    pub total_staked: u64,          // 总质押量
+    pub total_weighted_stake: u64,  // 总加权质押量，年化收益率的分母
+    pub total_unbonding: u64,       // 冷却中的质押量
+    pub staking_vault_balance: u64, // 质押金库的实际代币余额
+    pub liquid_principal: u64,      // 收据代币份额桶中的质押本金
+    pub liquid_rewards: u64,        // 份额桶累积的质押代币奖励
+    pub lockup_duration: i64,       // 锁定期（秒）
+    pub cooldown_duration: i64,     // 取消质押冷却期（秒）
+    pub max_boost_bps: u16,         // 最大加速倍数（基点）
+    pub reward_streams: [RewardStreamView; MAX_REWARD_STREAMS], // 奖励流视图
 }
 
-// 质押指令的账户结构
-#[derive(Accounts)]
-pub struct Stake<'info> {
-    #[account(mut)] // 用户签名者
-    pub user: Signer<'info>,
-    // 池子账户，验证 PDA 和质押金库
-    #[account(mut, seeds = [b"pool"], bump = pool.pool_bump, has_one = staking_vault,has_one = staking_mint)]
-    pub pool: Account<'info, Pool>,
-    // 用户质押信息账户，按需初始化
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + UserStakeInfo::INIT_SPACE,
-        seeds = [b"stake_info", user.key().as_ref()],
-        bump
-    )]
-    pub user_stake_info: Account<'info, UserStakeInfo>,
-    // 用户质押代币钱包，验证 Mint 匹配
-    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
-    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)] // 质押金库，可变
-    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
-    // 质押代币的 Mint 账户，用于 transfer_checked
-    pub staking_mint: InterfaceAccount<'info, Mint>,
-    pub system_program: Program<'info, System>, // 系统程序
-    pub token_program: Program<'info, Token2022>, // Token 程序
-}
+// 定义精度因子，10^12 提供足够精度，适配 u128 和 u64
+const PRECISION: u128 = staking_math::PRECISION;
+
+// 加速倍数的基准（基点），10000 表示 1x
+pub const BOOST_BASE_BPS: u16 = staking_math::BOOST_BASE_BPS;
+// 最大加速倍数上限：4x
+pub const MAX_BOOST_BPS: u16 = 40_000;
+// 加速锁定期下限：1 周
+pub const MIN_BOOST_LOCK: i64 = 7 * 24 * 60 * 60;
+// 加速锁定期上限：4 年，锁定满 4 年获得最大倍数
+pub const MAX_BOOST_LOCK: i64 = staking_math::MAX_BOOST_LOCK;
+
+// 罚金上限：100%
+pub const MAX_PENALTY_BPS: u16 = 10_000;
+// 基点分母
+const BPS_DENOMINATOR: u128 = 10_000;
+
+// Mint 扩展位掩码，记录在池子上供客户端提示风险
+// 危险扩展：可能冻结、转走或锁死金库中的代币，需管理员在初始化时明确允许
+pub const MINT_EXT_FREEZE_AUTHORITY: u16 = 1 << 0; // 设置了冻结权限
+pub const MINT_EXT_PERMANENT_DELEGATE: u16 = 1 << 1; // 永久委托人可转走任意账户的代币
+pub const MINT_EXT_NON_TRANSFERABLE: u16 = 1 << 2; // 不可转让
+pub const MINT_EXT_CONFIDENTIAL_TRANSFER: u16 = 1 << 3; // 机密转账，余额可能不可见
+pub const MINT_EXT_CLOSE_AUTHORITY: u16 = 1 << 4; // Mint 可被关闭
+pub const MINT_EXT_DEFAULT_FROZEN: u16 = 1 << 5; // 新账户默认冻结
+                                                 // 程序已正确处理的扩展，始终允许
+pub const MINT_EXT_TRANSFER_FEE: u16 = 1 << 8; // 转账手续费
+pub const MINT_EXT_TRANSFER_HOOK: u16 = 1 << 9; // 转账钩子
+                                                // 需要管理员明确允许的扩展集合
+pub const DANGEROUS_MINT_EXTENSIONS: u16 = MINT_EXT_FREEZE_AUTHORITY
+    | MINT_EXT_PERMANENT_DELEGATE
+    | MINT_EXT_NON_TRANSFERABLE
+    | MINT_EXT_CONFIDENTIAL_TRANSFER
+    | MINT_EXT_CLOSE_AUTHORITY
+    | MINT_EXT_DEFAULT_FROZEN;
+
+// 默认偿付能力检查期限：7 天
+pub const DEFAULT_SOLVENCY_HORIZON: i64 = 7 * 24 * 60 * 60;
+
+// 锁定期硬上限：4 年
+pub const MAX_LOCKUP_DURATION: i64 = MAX_BOOST_LOCK;
+// 冷却期硬上限：1 年
+pub const MAX_COOLDOWN_DURATION: i64 = 365 * 24 * 60 * 60;
+// 时间锁延迟硬上限：30 天
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 24 * 60 * 60;
+// 最长暂停时间硬上限：1 年
+pub const MAX_PAUSE_DURATION: i64 = 365 * 24 * 60 * 60;
+// 偿付能力检查期限硬上限：1 年
+pub const MAX_SOLVENCY_HORIZON: i64 = 365 * 24 * 60 * 60;
+// 奖励率溢出模拟的时间范围：10 年
+pub const REWARD_RATE_SIMULATION_HORIZON: i64 = 10 * 365 * 24 * 60 * 60;
+// 持续发放 REWARD_RATE_SIMULATION_HORIZON 的总奖励仍能以 u64 记账的最大奖励率
+pub const MAX_REWARD_RATE: u64 = u64::MAX / REWARD_RATE_SIMULATION_HORIZON as u64;
+// 奖励周期硬上限：与奖励率溢出模拟的时间范围一致
+pub const MAX_REWARD_DURATION: i64 = REWARD_RATE_SIMULATION_HORIZON;
+
+// 实现池子奖励更新逻辑
+impl Pool {
+    // 更新全局和用户奖励，在用户交互或管理员操作前调用
+    pub fn update_rewards(
+        &mut self,
+        user_stake_info: Option<&mut Account<UserStakeInfo>>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp; // 获取当前区块链时间
+        self.update_rewards_at(current_time, user_stake_info.map(|info| &mut **info))
+    }
+
+    // 按给定时间更新全局和用户奖励，累加器算术来自与客户端共用的 staking_math
+    pub fn update_rewards_at(
+        &mut self,
+        current_time: i64,
+        user_stake_info: Option<&mut UserStakeInfo>,
+    ) -> Result<()> {
+        // 奖励只累积到周期结束时间，且不超过金库中未被占用的余额
+        staking_math::accrue_streams(
+            self.reward_streams.iter_mut().map(|stream| RewardLedger {
+                period_finish: stream.period_finish,
+                reward_rate: stream.reward_rate,
+                vault_balance: stream.vault_balance,
+                reward_per_token_stored: &mut stream.reward_per_token_stored,
+                total_rewards_owed: &mut stream.total_rewards_owed,
+            }),
+            self.last_update_timestamp,
+            current_time,
+            self.total_weighted_stake,
+        )
+        .ok_or(StakingError::ArithmeticOverflow)?;
+
+        self.last_update_timestamp = current_time; // 更新最后时间戳
+        self.settle_liquid_rewards()?; // 结算收据代币份额桶的奖励
+
+        // 如果提供用户质押信息，逐条奖励流计算并更新用户奖励
+        if let Some(info) = user_stake_info {
+            for index in 0..MAX_REWARD_STREAMS {
+                // 计算用户在该奖励流的待领奖励
+                let pending_rewards = info.calculate_pending_rewards(self, index);
+                // 累加到用户奖励字段
+                info.rewards[index] = info.rewards[index]
+                    .checked_add(pending_rewards)
+                    .ok_or(StakingError::ArithmeticOverflow)?;
+                // 同步用户奖励基准到当前全局值
+                info.reward_per_token_paid[index] =
+                    self.reward_streams[index].reward_per_token_stored;
+            }
+        }
+
+        Ok(()) // 返回成功
+    }
+
+    // 结算收据代币份额桶的奖励：质押代币计价的奖励计入份额价格，
+    // 其他奖励代币无法按份额价格分配，释放回奖励流的未占用余额
+    // 自动复利时新增奖励计入份额桶权重
+    fn settle_liquid_rewards(&mut self) -> Result<()> {
+        let staking_mint = self.staking_mint;
+        staking_math::settle_liquid_rewards(
+            self.reward_streams
+                .iter_mut()
+                .zip(self.liquid_reward_per_token_paid.iter_mut())
+                .map(|(stream, paid)| LiquidRewardLedger {
+                    reward_per_token_stored: stream.reward_per_token_stored,
+                    compounds: stream.mint == staking_mint,
+                    reward_per_token_paid: paid,
+                    total_rewards_owed: &mut stream.total_rewards_owed,
+                }),
+            self.auto_compound,
+            self.liquid_principal,
+            &mut self.liquid_rewards,
+            &mut self.total_weighted_stake,
+        )
+        .ok_or(StakingError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // 份额桶参与奖励分配的权重：自动复利时包含已累积的质押代币奖励
+    pub fn liquid_weight(&self) -> Result<u64> {
+        Ok(staking_math::liquid_weight(
+            self.auto_compound,
+            self.liquid_principal,
+            self.liquid_rewards,
+        )
+        .ok_or(StakingError::ArithmeticOverflow)?)
+    }
+
+    // 用份额桶的当前权重替换 old_weight，同步池子总加权质押量
+    pub fn replace_liquid_weight(&mut self, old_weight: u64) -> Result<()> {
+        self.total_weighted_stake = staking_math::replace_weight(
+            self.total_weighted_stake,
+            old_weight,
+            self.liquid_weight()?,
+        )
+        .ok_or(StakingError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // 将罚金计入质押代币奖励流并立即按加权质押量分配；无人质押时作为未占用余额留待后续发放
+    pub fn distribute_penalty(&mut self, index: usize, amount: u64) -> Result<()> {
+        let total_weighted_stake = self.total_weighted_stake;
+        let stream = &mut self.reward_streams[index];
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_add(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        if total_weighted_stake == 0 {
+            return Ok(());
+        }
+        stream.reward_per_token_stored = stream
+            .reward_per_token_stored
+            .checked_add(
+                (amount as u128)
+                    .checked_mul(PRECISION)
+                    .ok_or(StakingError::ArithmeticOverflow)?
+                    / total_weighted_stake as u128,
+            )
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        stream.total_rewards_owed = stream
+            .total_rewards_owed
+            .checked_add(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // 奖励代币与质押代币相同的奖励流索引，复利只能使用该奖励流
+    pub fn compound_stream_index(&self) -> Result<usize> {
+        Ok(self
+            .reward_streams
+            .iter()
+            .position(|s| s.mint == self.staking_mint)
+            .ok_or(StakingError::NoCompoundableStream)?)
+    }
+
+    // 收据代币份额桶的总价值（本金 + 已累积的质押代币奖励）
+    pub fn liquid_value(&self) -> Result<u64> {
+        Ok(self
+            .liquid_principal
+            .checked_add(self.liquid_rewards)
+            .ok_or(StakingError::ArithmeticOverflow)?)
+    }
+
+    // 计算 shares 份额在总份额 supply 中对应的 total 部分，向下取整
+    pub fn liquid_share_of(&self, total: u64, shares: u64, supply: u64) -> Result<u64> {
+        Ok(((total as u128)
+            .checked_mul(shares as u128)
+            .ok_or(StakingError::ArithmeticOverflow)?
+            .checked_div(supply as u128)
+            .ok_or(StakingError::ArithmeticOverflow)?)
+        .try_into()
+        .map_err(|_| StakingError::ArithmeticOverflow)?)
+    }
+
+    // 当前是否处于暂停状态：超过最长暂停时间后视为未暂停
+    pub fn is_paused_now(&self) -> Result<bool> {
+        if !self.is_paused || self.max_pause_duration == 0 {
+            return Ok(self.is_paused);
+        }
+        let now = Clock::get()?.unix_timestamp;
+        Ok(now < self.paused_at.saturating_add(self.max_pause_duration))
+    }
+
+    // 清空用户仓位（质押批次和冷却中的请求）并同步池子总量，返回用户对质押金库的本金债权
+    pub fn close_position(&mut self, info: &mut UserStakeInfo) -> Result<u64> {
+        let unbonding = info
+            .unbonding
+            .iter()
+            .try_fold(0u64, |sum, request| sum.checked_add(request.amount))
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        let stake_amount = info.stake_amount;
+        let claim = stake_amount
+            .checked_add(unbonding)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        require_gt!(claim, 0, StakingError::InsufficientStakeAmount); // 确保有可取回的本金
+
+        self.total_staked = self
+            .total_staked
+            .checked_sub(stake_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.total_unbonding = self
+            .total_unbonding
+            .checked_sub(unbonding)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.total_weighted_stake = self
+            .total_weighted_stake
+            .checked_sub(info.weighted_amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        info.stake_amount = 0;
+        info.weighted_amount = 0;
+        info.stake_start_timestamp = 0;
+        info.lots.clear();
+        info.unbonding.clear();
+        Ok(claim)
+    }
+
+    // 进入关停状态：所有奖励流在当前时间结束，记录待分配的金库余额和本金债权总额。
+    // 调用前需先 update_rewards 结算至当前时间
+    pub fn shutdown(&mut self, remaining_balance: u64) -> Result<()> {
+        let now = self.last_update_timestamp;
+        for stream in self.reward_streams.iter_mut() {
+            stream.period_finish = stream.period_finish.min(now); // 停止累积奖励
+            stream.reward_rate = 0;
+        }
+        self.is_shutdown = true;
+        self.shutdown_claims = self
+            .total_staked
+            .checked_add(self.total_unbonding)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.shutdown_balance = remaining_balance;
+        Ok(())
+    }
+
+    // 关停后按 债权 * 剩余余额 / 剩余债权 计算退出金额并扣减，结果与退出顺序无关（向下取整）
+    pub fn shutdown_payout(&mut self, claim: u64) -> Result<u64> {
+        if claim == 0 {
+            return Ok(0);
+        }
+        let payout: u64 = (claim as u128)
+            .checked_mul(self.shutdown_balance as u128)
+            .ok_or(StakingError::ArithmeticOverflow)?
+            .checked_div(self.shutdown_claims as u128)
+            .ok_or(StakingError::ArithmeticOverflow)?
+            .try_into()
+            .map_err(|_| StakingError::ArithmeticOverflow)?;
+        self.shutdown_claims = self
+            .shutdown_claims
+            .checked_sub(claim)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.shutdown_balance = self
+            .shutdown_balance
+            .checked_sub(payout)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        Ok(payout)
+    }
+
+    // 校验并设置指定奖励流的奖励率，返回奖励代币 Mint
+    pub fn set_reward_rate(&mut self, reward_index: u8, new_rate: u64) -> Result<Pubkey> {
+        require!(!self.is_shutdown, StakingError::PoolShutdown); // 关停后不再调整奖励率
+        let stream = self.reward_stream(reward_index)?;
+        require!(stream.is_active, StakingError::RewardStreamInactive); // 已退役的奖励流不能再设置奖励率
+        require_gt!(
+            stream.period_finish,
+            Clock::get()?.unix_timestamp,
+            StakingError::RewardPeriodFinished
+        ); // 奖励率只作用于进行中的奖励周期
+        self.update_rewards(None)?; // 更新全局奖励，确保状态同步
+        let current_time = self.last_update_timestamp;
+        let horizon = self.solvency_horizon;
+        let bounds = self.bounds;
+        let stream = &mut self.reward_streams[reward_index as usize];
+        bounds.check_reward_rate(new_rate, stream.reward_per_token_stored)?; // 校验奖励率边界和累加器溢出
+                                                                             // 确保新奖励率在偿付期限内（不超过周期结束）不会让奖励流资不抵债
+        require_gte!(
+            stream.unreserved_balance() as u128,
+            stream.emissions_within(new_rate, current_time, horizon)?,
+            StakingError::RewardRateUnsustainable
+        );
+        stream.reward_rate = new_rate; // 设置新奖励率
+        Ok(stream.mint)
+    }
+
+    // 从奖励流的未占用余额中划出 amount 开启新奖励周期：
+    // reward_rate = (amount + 上一周期剩余奖励) / duration，返回更新后的奖励流
+    pub fn start_reward_period(
+        &mut self,
+        reward_index: u8,
+        amount: u64,
+        duration: i64,
+    ) -> Result<RewardStream> {
+        require_gt!(duration, 0, StakingError::InvalidRewardDuration); // 确保奖励周期大于 0
+        self.bounds.check_reward_duration(duration)?; // 校验奖励周期在边界内
+        require!(!self.is_shutdown, StakingError::PoolShutdown); // 关停后不再开启奖励周期
+        require!(
+            self.reward_stream(reward_index)?.is_active,
+            StakingError::RewardStreamInactive
+        ); // 已退役的奖励流不能开启新周期
+        self.update_rewards(None)?; // 按旧奖励率结算至当前时间
+        let current_time = self.last_update_timestamp;
+        let bounds = self.bounds;
+        let stream = &mut self.reward_streams[reward_index as usize];
+        // 上一周期未发放完的奖励滚入新周期
+        let leftover = if current_time < stream.period_finish {
+            ((stream.period_finish - current_time) as u128)
+                .checked_mul(stream.reward_rate as u128)
+                .ok_or(StakingError::ArithmeticOverflow)?
+        } else {
+            0
+        };
+        let total_reward = (amount as u128)
+            .checked_add(leftover)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        let new_rate: u64 = (total_reward / duration as u128)
+            .try_into()
+            .map_err(|_| StakingError::ArithmeticOverflow)?;
+        require_gt!(new_rate, 0, StakingError::RewardRateTooLow); // 金额不足以按秒发放
+        bounds.check_reward_rate(new_rate, stream.reward_per_token_stored)?; // 校验奖励率边界和累加器溢出
+
+        // 确保整个周期承诺的奖励不超过金库中未被已累积奖励占用的余额
+        let promised = (new_rate as u128)
+            .checked_mul(duration as u128)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        require_gte!(
+            stream.unreserved_balance() as u128,
+            promised,
+            StakingError::InsufficientVaultBalance
+        );
+
+        stream.reward_rate = new_rate; // 设置新奖励率
+        stream.period_finish = current_time
+            .checked_add(duration)
+            .ok_or(StakingError::ArithmeticOverflow)?; // 记录周期结束时间
+        Ok(*stream)
+    }
+
+    // 退役奖励流：结算截至当前的奖励后停止发放，用户仍可领取已累积的奖励
+    pub fn retire_stream(&mut self, reward_index: u8) -> Result<Pubkey> {
+        require!(
+            self.reward_stream(reward_index)?.is_active,
+            StakingError::RewardStreamInactive
+        ); // 确保奖励流仍在发放
+        self.update_rewards(None)?; // 结算截至当前的奖励
+        let current_time = self.last_update_timestamp;
+        let stream = &mut self.reward_streams[reward_index as usize];
+        stream.reward_rate = 0; // 停止发放
+        stream.period_finish = stream.period_finish.min(current_time); // 结束当前奖励周期
+        stream.is_active = false; // 标记为已退役
+        Ok(stream.mint)
+    }
+
+    pub fn set_cooldown_duration(&mut self, new_duration: i64) -> Result<()> {
+        require_gte!(new_duration, 0, StakingError::InvalidCooldownDuration); // 冷却期不能为负
+        self.bounds.check_cooldown_duration(new_duration)?; // 校验冷却期在边界内
+        require!(
+            new_duration == 0 || self.receipt_mint == Pubkey::default(),
+            StakingError::LiquidStakingRequiresNoCooldown
+        ); // 份额桶即时赎回，开启收据代币后不能再设置冷却期
+        self.cooldown_duration = new_duration;
+        Ok(())
+    }
+
+    pub fn set_max_boost(&mut self, max_boost_bps: u16) -> Result<()> {
+        check_max_boost(max_boost_bps)?;
+        self.max_boost_bps = max_boost_bps;
+        Ok(())
+    }
+
+    pub fn set_early_unstake_penalty(
+        &mut self,
+        penalty_bps: u16,
+        scale_with_lock: bool,
+    ) -> Result<()> {
+        check_early_unstake_penalty(penalty_bps)?;
+        self.early_unstake_penalty_bps = penalty_bps;
+        self.penalty_scales_with_lock = scale_with_lock;
+        Ok(())
+    }
+
+    pub fn set_max_pause_duration(&mut self, new_duration: i64) -> Result<()> {
+        require_gte!(new_duration, 0, StakingError::InvalidPauseDuration); // 不能为负
+        self.bounds.check_max_pause_duration(new_duration)?; // 校验最长暂停时间在边界内
+        self.max_pause_duration = new_duration;
+        Ok(())
+    }
+
+    // 新边界不能超过程序硬上限，且必须容纳当前生效的参数
+    pub fn set_parameter_bounds(&mut self, bounds: ParameterBounds) -> Result<()> {
+        bounds.validate()?; // 校验边界本身合法
+        bounds.check_lockup_duration(self.lockup_duration)?;
+        bounds.check_cooldown_duration(self.cooldown_duration)?;
+        bounds.check_timelock_delay(self.timelock_delay)?;
+        bounds.check_max_pause_duration(self.max_pause_duration)?;
+        bounds.check_solvency_horizon(self.solvency_horizon)?;
+        for stream in self.reward_streams.iter().filter(|s| s.is_active) {
+            bounds.check_reward_rate(stream.reward_rate, stream.reward_per_token_stored)?;
+        }
+        self.bounds = bounds;
+        Ok(())
+    }
+
+    pub fn check_penalty_destination(
+        &self,
+        destination: PenaltyDestination,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        match destination {
+            PenaltyDestination::Treasury => {
+                require_keys_neq!(treasury, Pubkey::default(), StakingError::InvalidTreasury);
+            } // 国库地址不能为空
+            PenaltyDestination::Redistribute => {
+                self.compound_stream_index()?;
+            } // 需要存在以质押代币发放奖励的奖励流
+            PenaltyDestination::Burn => {}
+        }
+        Ok(())
+    }
+
+    pub fn set_penalty_destination(
+        &mut self,
+        destination: PenaltyDestination,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        self.check_penalty_destination(destination, treasury)?;
+        self.penalty_destination = destination;
+        self.penalty_treasury = treasury;
+        Ok(())
+    }
+
+    // 先按旧权重结算奖励，份额桶的权重随开关变化
+    pub fn set_auto_compound(&mut self, enabled: bool) -> Result<()> {
+        if enabled {
+            self.compound_stream_index()?; // 需要存在以质押代币发放奖励的奖励流
+        }
+        self.update_rewards(None)?;
+        let old_weight = self.liquid_weight()?;
+        self.auto_compound = enabled;
+        self.replace_liquid_weight(old_weight)
+    }
+
+    // 判断地址是否持有指定角色：管理员始终拥有全部角色
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        let holder = match role {
+            Role::Pauser => self.pauser,
+            Role::RateManager => self.rate_manager,
+            Role::Funder => self.funder,
+            Role::Guardian => self.guardian,
+        };
+        *key == self.admin || (holder != Pubkey::default() && *key == holder)
+    }
 
-// 取消质押指令的账户结构
-#[derive(Accounts)]
-pub struct UnStake<'info> {
-    #[account(mut)] // 用户签名者
-    pub user: Signer<'info>,
-    // 池子账户，验证 PDA 和质押金库
-    #[account(mut, seeds = [b"pool"], bump = pool.pool_bump, has_one = staking_vault)]
-    pub pool: Account<'info, Pool>,
-    // 用户质押信息账户
-    #[account(mut, seeds = [b"stake_info", user.key().as_ref()], bump)]
-    pub user_stake_info: Account<'info, UserStakeInfo>,
-    // 用户质押代币钱包
-    #[account(mut, constraint = user_staking_wallet.mint == pool.staking_mint)]
-    pub user_staking_wallet: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)] // 质押金库
-    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
-    pub staking_mint: InterfaceAccount<'info, Mint>,
-    pub token_program: Program<'info, Token2022>, // Token 程序
-}
+    // 获取角色持有者字段的可变引用
+    pub fn role_holder_mut(&mut self, role: Role) -> &mut Pubkey {
+        match role {
+            Role::Pauser => &mut self.pauser,
+            Role::RateManager => &mut self.rate_manager,
+            Role::Funder => &mut self.funder,
+            Role::Guardian => &mut self.guardian,
+        }
+    }
 
-// 领取奖励指令的账户结构
-#[derive(Accounts)]
-pub struct ClaimRewards<'info> {
-    #[account(mut)] // 用户签名者
-    pub user: Signer<'info>,
-    // 池子账户，验证 PDA 和奖励金库
-    #[account(mut, seeds = [b"pool"], bump = pool.pool_bump, has_one = reward_vault)]
-    pub pool: Account<'info, Pool>,
-    // 用户质押信息账户
-    #[account(mut, seeds = [b"stake_info", user.key().as_ref()], bump)]
-    pub user_stake_info: Account<'info, UserStakeInfo>,
-    // 用户奖励代币钱包
-    #[account(mut, constraint = user_reward_wallet.mint == pool.reward_mint)]
-    pub user_reward_wallet: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)] // 奖励金库
-    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
-    pub reward_mint: InterfaceAccount<'info, Mint>,
-    pub token_program: Program<'info, Token2022>, // Token 程序
-}
+    // 按当前时间重新计算用户加权质押量，并同步池子总加权质押量
+    pub fn sync_weight(&mut self, info: &mut UserStakeInfo, now: i64) -> Result<()> {
+        staking_math::sync_weight(
+            info.lots
+                .iter()
+                .map(|lot| (lot.amount, lot.unlock_timestamp)),
+            now,
+            self.max_boost_bps,
+            &mut info.weighted_amount,
+            &mut self.total_weighted_stake,
+        )
+        .ok_or(StakingError::ArithmeticOverflow)?;
+        Ok(())
+    }
 
-// 关闭用户质押信息账户的结构
-#[derive(Accounts)]
-pub struct CloseUserStakeInfo<'info> {
-    #[account(mut)] // 用户签名者
-    pub user: Signer<'info>,
-    // 用户质押信息账户，关闭后租金返还
-    #[account(mut, seeds = [b"stake_info", user.key().as_ref()], bump, close = user)]
-    pub user_stake_info: Account<'info, UserStakeInfo>,
-}
+    // stake 指令的记账：结算奖励后记录新的质押批次并同步加权质押量，返回批次解锁时间。
+    // amount 为质押金库实际收到的金额（已扣除转账手续费）
+    pub fn record_stake(
+        &mut self,
+        info: &mut UserStakeInfo,
+        amount: u64,
+        lock_duration: i64,
+        now: i64,
+    ) -> Result<i64> {
+        self.update_rewards_at(now, Some(info))?; // 更新全局和用户奖励，确保状态同步
+        if info.stake_amount == 0 {
+            info.stake_start_timestamp = now; // 首次质押记录开始时间
+        }
+        // 每笔存入记录为独立的质押批次，锁定期取池子锁定期与用户选择的加速锁定期中较长者
+        let lot_lock = self.lockup_duration.max(lock_duration);
+        let unlock_timestamp = now
+            .checked_add(lot_lock)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        info.add_lot(amount, unlock_timestamp, lot_lock, now)?;
+        info.stake_amount = info
+            .stake_amount
+            .checked_add(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.total_staked = self
+            .total_staked
+            .checked_add(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.sync_weight(info, now)?; // 按新批次重新计算加权质押量
+        Ok(unlock_timestamp)
+    }
 
-// 管理员操作指令的账户结构
-#[derive(Accounts)]
-pub struct AdminAction<'info> {
-    // 池子账户，验证管理员权限
-    #[account(mut, seeds = [b"pool"], bump = pool.pool_bump, has_one = admin)]
-    pub pool: Account<'info, Pool>,
-    pub admin: Signer<'info>, // 管理员签名者
+    // unstake 指令的记账：从已解锁的批次中扣减，结算奖励后同步质押量和加权质押量，返回仍锁定的金额
+    pub fn record_unstake(
+        &mut self,
+        info: &mut UserStakeInfo,
+        amount: u64,
+        now: i64,
+    ) -> Result<u64> {
+        require_gte!(
+            info.stake_amount,
+            amount,
+            StakingError::InsufficientStakeAmount
+        ); // 确保质押金额足够
+        let remaining_locked = info.consume_unlocked_lots(amount, now)?; // 已解锁总额不足时报错
+        self.update_rewards_at(now, Some(info))?;
+        info.stake_amount = info
+            .stake_amount
+            .checked_sub(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.total_staked = self
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(StakingError::ArithmeticOverflow)?;
+        self.sync_weight(info, now)?; // 重新计算加权质押量
+        if info.stake_amount == 0 {
+            info.stake_start_timestamp = 0; // 质押金额为 0 时重置开始时间
+        }
+        Ok(remaining_locked)
+    }
+
+    // claim_rewards 指令的记账：结算奖励后清零用户该奖励流的待领奖励，并同步奖励流账本，返回领取金额
+    pub fn record_claim(
+        &mut self,
+        info: &mut UserStakeInfo,
+        index: usize,
+        now: i64,
+    ) -> Result<u64> {
+        self.update_rewards_at(now, Some(info))?; // 更新所有奖励流，累加待领奖励
+        let amount = info.rewards[index];
+        info.rewards[index] = 0;
+        // 已领取的奖励不再是负债，金库余额相应减少
+        let stream = &mut self.reward_streams[index];
+        stream.total_rewards_owed = stream.total_rewards_owed.saturating_sub(amount);
+        stream.vault_balance = stream
+            .vault_balance
+            .checked_sub(amount)
+            .ok_or(StakingError::InsufficientVaultBalance)?;
+        Ok(amount)
+    }
+
+    // poke 指令的记账：先按旧权重结算奖励，再按当前剩余锁定期重新计算权重
+    pub fn record_poke(&mut self, info: &mut UserStakeInfo, now: i64) -> Result<()> {
+        self.update_rewards_at(now, Some(info))?;
+        self.sync_weight(info, now)
+    }
+
+    // 按索引获取奖励流，并校验传入的金库和 Mint 与之匹配
+    pub fn reward_stream_for(
+        &self,
+        reward_index: u8,
+        vault: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<&RewardStream> {
+        let stream = self.reward_stream(reward_index)?;
+        require_keys_eq!(stream.vault, *vault, StakingError::RewardVaultMismatch);
+        require_keys_eq!(stream.mint, *mint, StakingError::RewardMintMismatch);
+        Ok(stream)
+    }
+
+    // 按索引获取已配置的奖励流，索引越界或槽位为空时报错
+    pub fn reward_stream(&self, reward_index: u8) -> Result<&RewardStream> {
+        let stream = self
+            .reward_streams
+            .get(reward_index as usize)
+            .ok_or(StakingError::InvalidRewardIndex)?;
+        require_keys_neq!(
+            stream.mint,
+            Pubkey::default(),
+            StakingError::InvalidRewardIndex
+        );
+        Ok(stream)
+    }
 }
 
-// 注资奖励金库的账户结构
-#[derive(Accounts)]
-pub struct FundRewards<'info> {
-    // 池子账户，验证管理员和奖励金库
-    #[account(has_one = admin)]
-    pub pool: Account<'info, Pool>,
-    pub admin: Signer<'info>, // 管理员签名者
-    // 管理员的奖励代币钱包
-    #[account(mut, constraint = funder_wallet.mint == pool.reward_mint)]
-    pub funder_wallet: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut, address = pool.reward_vault)] // 奖励金库
-    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
-    // 奖励代币的 Mint 账户，用于 transfer_checked
-    pub reward_mint: InterfaceAccount<'info, Mint>,
-    pub token_program: Program<'info, Token2022>, // Token 程序
+// 实现奖励流的偿付能力计算
+impl RewardStream {
+    // 金库中未被已累积奖励占用的余额
+    pub fn unreserved_balance(&self) -> u64 {
+        self.vault_balance.saturating_sub(self.total_rewards_owed)
+    }
+
+    // 按给定奖励率，从 now 起 horizon 秒内（不超过周期结束）将发放的奖励总额
+    pub fn emissions_within(&self, reward_rate: u64, now: i64, horizon: i64) -> Result<u128> {
+        let until = now.saturating_add(horizon).min(self.period_finish);
+        let seconds = until.saturating_sub(now).max(0) as u128;
+        Ok(seconds
+            .checked_mul(reward_rate as u128)
+            .ok_or(StakingError::ArithmeticOverflow)?)
+    }
+
+    // 生成 now 时刻的只读视图，vault_amount 为奖励金库的实际代币余额
+    pub fn view(&self, now: i64, vault_amount: u64) -> RewardStreamView {
+        RewardStreamView {
+            mint: self.mint,
+            vault: self.vault,
+            reward_rate: self.reward_rate,
+            current_reward_rate: if now < self.period_finish {
+                self.reward_rate
+            } else {
+                0
+            },
+            period_finish: self.period_finish,
+            reward_per_token_stored: self.reward_per_token_stored,
+            vault_balance: self.vault_balance,
+            vault_amount,
+            total_rewards_owed: self.total_rewards_owed,
+            unreserved_balance: self.unreserved_balance(),
+            is_active: self.is_active,
+        }
+    }
 }
 
-// 紧急提取质押代币的账户结构
-#[derive(Accounts)]
-pub struct EmergencyWithdrawStaked<'info> {
-    // 池子账户，验证管理员和质押金库
-    #[account(has_one = admin)]
-    pub pool: Account<'info, Pool>,
-    pub admin: Signer<'info>, // 管理员签名者
-    #[account(mut, address = pool.staking_vault)] // 质押金库
-    pub staking_vault: InterfaceAccount<'info, TokenAccount>,
-    pub staking_mint: InterfaceAccount<'info, Mint>,
-    #[account(mut)] // 目标钱包
-    pub destination_wallet: InterfaceAccount<'info, TokenAccount>,
-    pub token_program: Program<'info, Token2022>, // Token 程序
+// 读取 Mint 的冻结权限和 Token-2022 扩展，返回 MINT_EXT_* 位掩码
+pub fn mint_extension_flags(mint: &InterfaceAccount<Mint>) -> Result<u16> {
+    let mut flags = 0;
+    if mint.freeze_authority.is_some() {
+        flags |= MINT_EXT_FREEZE_AUTHORITY;
+    }
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(flags); // 经典 SPL Token 没有扩展
+    }
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        flags |= match extension {
+            ExtensionType::PermanentDelegate => MINT_EXT_PERMANENT_DELEGATE,
+            ExtensionType::NonTransferable => MINT_EXT_NON_TRANSFERABLE,
+            ExtensionType::ConfidentialTransferMint => MINT_EXT_CONFIDENTIAL_TRANSFER,
+            ExtensionType::MintCloseAuthority => MINT_EXT_CLOSE_AUTHORITY,
+            // 只有默认状态为冻结时才有风险，默认 Initialized 的 Mint 与普通 Mint 无异
+            ExtensionType::DefaultAccountState => {
+                let default_state = state.get_extension::<DefaultAccountState>()?;
+                if default_state.state == AccountState::Frozen as u8 {
+                    MINT_EXT_DEFAULT_FROZEN
+                } else {
+                    0
+                }
+            }
+            ExtensionType::TransferFeeConfig => MINT_EXT_TRANSFER_FEE,
+            ExtensionType::TransferHook => MINT_EXT_TRANSFER_HOOK,
+            _ => 0,
+        };
+    }
+    Ok(flags)
 }
 
-// 紧急提取奖励代币的账户结构
-#[derive(Accounts)]
-pub struct EmergencyWithdrawRewards<'info> {
-    // 池子账户，验证管理员和奖励金库
-    #[account(
-        has_one = admin,
-        has_one = reward_mint,
-        has_one = reward_vault
-    )]
-    pub pool: Account<'info, Pool>,
-    pub admin: Signer<'info>, // 管理员签名者
-    #[account(mut)]
-    pub reward_mint: InterfaceAccount<'info, Mint>,
-    #[account(mut, address = pool.reward_vault)] // 奖励金库
-    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut)] // 目标钱包
-    pub destination_wallet: InterfaceAccount<'info, TokenAccount>,
-    pub token_program: Program<'info, Token2022>, // Token 程序
+// 加速倍数需在 1x 到 4x 之间
+pub fn check_max_boost(max_boost_bps: u16) -> Result<()> {
+    require!(
+        (BOOST_BASE_BPS..=MAX_BOOST_BPS).contains(&max_boost_bps),
+        StakingError::InvalidBoost
+    );
+    Ok(())
 }
 
-// 定义状态账户结构
+// 罚金不超过 100%
+pub fn check_early_unstake_penalty(penalty_bps: u16) -> Result<()> {
+    require_gte!(MAX_PENALTY_BPS, penalty_bps, StakingError::InvalidPenalty);
+    Ok(())
+}
 
-// 池子状态账户，存储全局质押信息
-#[account]
-#[derive(InitSpace)]
-pub struct Pool {
-    pub admin: Pubkey,                 // 管理员公钥
-    pub staking_mint: Pubkey,          // 质押代币 Mint 地址
-    pub staking_vault: Pubkey,         // 质押金库地址
-    pub reward_mint: Pubkey,           // 奖励代币 Mint 地址
-    pub reward_vault: Pubkey,          // 奖励金库地址
-    pub reward_rate: u64,              // 每秒奖励代币数量
-    pub last_update_timestamp: i64,    // 最后更新时间戳
-    pub total_staked: u64,             // 总质押量
-    pub reward_per_token_stored: u128, // 每单位代币的累计奖励（高精度）
-    pub pool_bump: u8,                 // 池子 PDA bump 值
-    pub lockup_duration: i64,          // 锁定期（秒）
-    pub is_paused: bool,               // 程序暂停状态
+// 校验 Mint 的危险扩展均在 allowed 中，返回该 Mint 的扩展位掩码
+pub fn validate_mint_extensions(mint: &InterfaceAccount<Mint>, allowed: u16) -> Result<u16> {
+    let flags = mint_extension_flags(mint)?;
+    require!(
+        flags & DANGEROUS_MINT_EXTENSIONS & !allowed == 0,
+        StakingError::UnsupportedMintExtension
+    );
+    Ok(flags)
 }
 
-// 用户质押信息账户，存储用户特定数据
-#[account]
-#[derive(Default, InitSpace)]
-pub struct UserStakeInfo {
-    pub stake_amount: u64,           // 用户质押金额
-    pub stake_start_timestamp: i64,  // 质押开始时间戳
-    pub reward_per_token_paid: u128, // 用户上次同步的奖励基准
-    pub rewards: u64,                // 已计算但未领取的奖励
+// 执行 transfer_checked；Mint 启用 TransferHook 扩展时，从 CPI 的 remaining_accounts 中
+// 解析 ExtraAccountMetaList 及其声明的额外账户并一并传给 Token 程序
+pub fn transfer_checked_with_hook<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    spl_token_2022::onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )?;
+    Ok(())
 }
 
-// 定义精度因子，10^12 提供足够精度，适配 u128 和 u64
-const PRECISION: u128 = 1_000_000_000_000;
+// 扣除 Token-2022 转账手续费后，转账目标账户实际收到的金额；
+// 经典 SPL Token 或未启用 TransferFee 扩展的 Mint 手续费为 0
+pub fn amount_after_transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(amount);
+    }
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let fee = match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(StakingError::ArithmeticOverflow)?,
+        Err(_) => 0,
+    };
+    Ok(amount
+        .checked_sub(fee)
+        .ok_or(StakingError::ArithmeticOverflow)?)
+}
 
-// 实现池子奖励更新逻辑
-impl Pool {
-    // 更新全局和用户奖励，在用户交互或管理员操作前调用
-    pub fn update_rewards(
+// 实现用户待领奖励计算逻辑
+impl UserStakeInfo {
+    // 记录一笔新的质押批次：now 时刻已解锁的批次合并为一个，解锁时间相同的相邻批次合并
+    pub fn add_lot(
         &mut self,
-        user_stake_info: Option<&mut Account<UserStakeInfo>>,
+        amount: u64,
+        unlock_timestamp: i64,
+        lock_duration: i64,
+        now: i64,
     ) -> Result<()> {
-        let current_time = Clock::get()?.unix_timestamp; // 获取当前区块链时间
-                                                         // 计算时间差，saturating_sub 防止时间回退导致负数
-        let time_elapsed = current_time.saturating_sub(self.last_update_timestamp) as u128;
-
-        // 仅当时间流逝且有质押代币时，更新全局奖励
-        if time_elapsed > 0 && self.total_staked > 0 {
-            // 计算总奖励：时间 * 奖励率
-            let rewards_accrued = time_elapsed
-                .checked_mul(self.reward_rate as u128)
-                .ok_or(StakingError::ArithmeticOverflow)?;
-            // 计算每单位代币奖励增量：(总奖励 * 精度) / 总质押量
-            let reward_per_token_increment = rewards_accrued
-                .checked_mul(PRECISION)
-                .ok_or(StakingError::ArithmeticOverflow)?
-                .checked_div(self.total_staked as u128)
-                .ok_or(StakingError::ArithmeticOverflow)?;
-            // 更新全局奖励指数
-            self.reward_per_token_stored = self
-                .reward_per_token_stored
-                .checked_add(reward_per_token_increment)
-                .ok_or(StakingError::ArithmeticOverflow)?;
-        }
+        let lot = Lot {
+            amount,
+            unlock_timestamp,
+            lock_duration,
+        };
+        Ok(
+            staking_math::add_lot(&mut self.lots, lot, MAX_STAKE_LOTS, now)
+                .map_err(StakingError::from)?,
+        )
+    }
 
-        self.last_update_timestamp = current_time; // 更新最后时间戳
+    // 计算在 now 时刻的加权质押量：每个批次的倍数随剩余锁定期线性衰减至 1x
+    pub fn boosted_weight(&self, now: i64, max_boost_bps: u16) -> Result<u64> {
+        Ok(staking_math::boosted_weight(
+            self.lots
+                .iter()
+                .map(|lot| (lot.amount, lot.unlock_timestamp)),
+            now,
+            max_boost_bps,
+        )
+        .ok_or(StakingError::ArithmeticOverflow)?)
+    }
 
-        // 如果提供用户质押信息，计算并更新用户奖励
-        if let Some(info) = user_stake_info {
-            // 计算用户待领奖励
-            let pending_rewards = info.calculate_pending_rewards(self);
-            // 累加到用户奖励字段
-            info.rewards = info
-                .rewards
-                .checked_add(pending_rewards)
-                .ok_or(StakingError::ArithmeticOverflow)?;
-            // 同步用户奖励基准到当前全局值
-            info.reward_per_token_paid = self.reward_per_token_stored;
+    // 计算在 now 时刻仍处于锁定期的质押金额
+    pub fn locked_amount(&self, now: i64) -> u64 {
+        staking_math::locked_amount(
+            self.lots
+                .iter()
+                .map(|lot| (lot.amount, lot.unlock_timestamp)),
+            now,
+        )
+    }
+
+    // 按先进先出顺序从已解锁批次中扣减 amount，返回扣减后仍锁定的金额
+    pub fn consume_unlocked_lots(&mut self, amount: u64, now: i64) -> Result<u64> {
+        Ok(
+            staking_math::consume_unlocked_lots(&mut self.lots, self.stake_amount, amount, now)
+                .map_err(StakingError::from)?,
+        )
+    }
+
+    // 提前取消质押：先按先进先出扣减已解锁批次，再按解锁时间从早到晚扣减锁定批次，返回应付罚金
+    pub fn consume_lots_early(
+        &mut self,
+        amount: u64,
+        now: i64,
+        penalty_bps: u16,
+        scale_with_lock: bool,
+    ) -> Result<u64> {
+        require_gte!(
+            self.stake_amount,
+            amount,
+            StakingError::InsufficientStakeAmount
+        );
+        let unlocked = self.stake_amount.saturating_sub(self.locked_amount(now));
+        let from_unlocked = unlocked.min(amount);
+        if from_unlocked > 0 {
+            self.consume_unlocked_lots(from_unlocked, now)?;
         }
 
-        Ok(()) // 返回成功
+        // 优先扣减最早解锁的批次，使罚金最低
+        let mut order: Vec<usize> = (0..self.lots.len()).collect();
+        order.sort_by_key(|&i| self.lots[i].unlock_timestamp);
+        let mut remaining = amount - from_unlocked;
+        let mut penalty: u128 = 0;
+        for i in order {
+            if remaining == 0 {
+                break;
+            }
+            let lot = &mut self.lots[i];
+            if lot.unlock_timestamp <= now {
+                continue; // 已解锁的批次在上面已经扣减
+            }
+            let taken = lot.amount.min(remaining);
+            lot.amount -= taken;
+            remaining -= taken;
+            // 罚金 = 扣减金额 * 罚金基点，按比例时再乘以 剩余锁定期 / 批次锁定期
+            let mut lot_penalty = taken as u128 * penalty_bps as u128 / BPS_DENOMINATOR;
+            if scale_with_lock {
+                let left = (lot.unlock_timestamp - now) as u128;
+                lot_penalty = lot_penalty * left / (lot.lock_duration.max(1) as u128).max(left);
+            }
+            penalty += lot_penalty;
+        }
+        self.lots.retain(|lot| lot.amount > 0); // 移除已清空的批次
+        Ok(penalty
+            .try_into()
+            .map_err(|_| StakingError::ArithmeticOverflow)?)
     }
-}
 
-// 实现用户待领奖励计算逻辑
-impl UserStakeInfo {
-    // 计算用户自上次同步以来的待领奖励
-    pub fn calculate_pending_rewards(&self, pool: &Pool) -> u64 {
-        // 计算未同步的奖励基准差：全局奖励指数 - 用户上次同步值
-        let reward_per_token_pending = pool
-            .reward_per_token_stored
-            .checked_sub(self.reward_per_token_paid)
-            .unwrap_or(0);
-        // 计算待领奖励：(质押量 * 奖励差) / 精度
-        let pending_rewards = (self.stake_amount as u128)
-            .checked_mul(reward_per_token_pending)
-            .unwrap_or(0)
-            .checked_div(PRECISION)
-            .unwrap_or(0);
-        // 转换为 u64，异常情况返回 0
-        pending_rewards.try_into().unwrap_or(0)
+    // 计算用户在指定奖励流上自上次同步以来的待领奖励
+    pub fn calculate_pending_rewards(&self, pool: &Pool, index: usize) -> u64 {
+        // 待领奖励 = (加权质押量 * (全局奖励指数 - 用户上次同步值)) / 精度，异常情况返回 0
+        staking_math::pending_rewards(
+            self.weighted_amount,
+            pool.reward_streams[index].reward_per_token_stored,
+            self.reward_per_token_paid[index],
+        )
     }
 }
 
 // 定义事件，用于记录操作日志
 
+#[event]
+pub struct InitializePoolEvent {
+    pub pool: Pubkey,                 // 池子地址
+    pub pool_id: u64,                 // 池子编号
+    pub staking_mint: Pubkey,         // 质押代币 Mint 地址
+    pub reward_mint: Pubkey,          // 奖励代币 Mint 地址
+    pub staking_mint_extensions: u16, // 质押代币已接受的扩展
+    pub reward_mint_extensions: u16,  // 奖励代币已接受的扩展
+}
+
 #[event]
 pub struct StakeEvent {
-    pub user: Pubkey, // 质押用户公钥
-    pub amount: u64,  // 质押金额
+    pub pool: Pubkey,          // 池子地址
+    pub user: Pubkey,          // 质押用户公钥
+    pub amount: u64,           // 转出的质押金额
+    pub net_amount: u64,       // 扣除转账手续费后实际记入的质押金额
+    pub unlock_timestamp: i64, // 本批次解锁时间戳
 }
 
 #[event]
 pub struct UnstakeEvent {
-    pub user: Pubkey, // 取消质押用户公钥
-    pub amount: u64,  // 取消质押金额
+    pub pool: Pubkey,          // 池子地址
+    pub user: Pubkey,          // 取消质押用户公钥
+    pub amount: u64,           // 取消质押金额
+    pub net_amount: u64,       // 扣除转账手续费后用户实际收到的金额
+    pub remaining_locked: u64, // 仍处于锁定期的质押金额
 }
 
 #[event]
 pub struct ClaimEvent {
-    pub user: Pubkey, // 领取奖励用户公钥
-    pub amount: u64,  // 领取奖励金额
+    pub pool: Pubkey,        // 池子地址
+    pub user: Pubkey,        // 领取奖励用户公钥
+    pub reward_mint: Pubkey, // 奖励代币 Mint 地址
+    pub amount: u64,         // 领取奖励金额
+    pub net_amount: u64,     // 扣除转账手续费后用户实际收到的金额
+}
+
+#[event]
+pub struct UnstakeRequestedEvent {
+    pub pool: Pubkey,      // 池子地址
+    pub user: Pubkey,      // 用户公钥
+    pub amount: u64,       // 请求提取的金额
+    pub available_at: i64, // 可提取时间戳
+}
+
+#[event]
+pub struct UnstakeRequestCancelledEvent {
+    pub pool: Pubkey, // 池子地址
+    pub user: Pubkey, // 用户公钥
+    pub amount: u64,  // 重新计入质押的金额
+}
+
+#[event]
+pub struct UnstakedWithdrawnEvent {
+    pub pool: Pubkey,    // 池子地址
+    pub user: Pubkey,    // 用户公钥
+    pub amount: u64,     // 提取金额
+    pub net_amount: u64, // 扣除转账手续费后用户实际收到的金额
+}
+
+#[event]
+pub struct PokeEvent {
+    pub pool: Pubkey,         // 池子地址
+    pub user: Pubkey,         // 被重新加权的用户公钥
+    pub weighted_amount: u64, // 新的加权质押量
 }
 
+#[event]
+pub struct CompoundEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+} // 复利事件
+#[event]
+pub struct SetAutoCompoundEvent {
+    pub pool: Pubkey,
+    pub enabled: bool,
+} // 设置自动复利事件
+#[event]
+pub struct EarlyUnstakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub penalty: u64,
+} // 提前取消质押事件
+#[event]
+pub struct PenaltyBurnedEvent {
+    pub pool: Pubkey,
+    pub amount: u64,
+} // 罚金销毁事件
+#[event]
+pub struct PenaltyToTreasuryEvent {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+} // 罚金转入国库事件
+#[event]
+pub struct PenaltyRedistributedEvent {
+    pub pool: Pubkey,
+    pub amount: u64,
+} // 罚金分配给剩余质押者事件
+#[event]
+pub struct UpdateEarlyUnstakePenaltyEvent {
+    pub pool: Pubkey,
+    pub penalty_bps: u16,
+    pub scale_with_lock: bool,
+} // 更新提前取消质押罚金事件
+#[event]
+pub struct UpdatePenaltyDestinationEvent {
+    pub pool: Pubkey,
+    pub destination: PenaltyDestination,
+    pub treasury: Pubkey,
+} // 更新罚金去向事件
+#[event]
+pub struct EnableReceiptTokenEvent {
+    pub pool: Pubkey,
+    pub receipt_mint: Pubkey,
+} // 开启收据代币事件
+#[event]
+pub struct LiquidStakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub shares: u64,
+} // 流动性质押事件
+#[event]
+pub struct LiquidUnstakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub rewards: u64,
+    pub net_rewards: u64,
+} // 流动性取消质押事件
 #[event]
 pub struct UpdateRewardRateEvent {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
     pub new_rate: u64,
 } // 奖励率更新事件
 #[event]
 pub struct FundRewardsEvent {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
     pub amount: u64,
+    pub net_amount: u64,
 } // 注资事件
 #[event]
-pub struct ChangeAdminEvent {
+pub struct AddRewardStreamEvent {
+    pub pool: Pubkey,
+    pub reward_index: u8,
+    pub reward_mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub mint_extensions: u16,
+} // 新增奖励流事件
+#[event]
+pub struct NotifyRewardAmountEvent {
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub duration: i64,
+    pub reward_rate: u64,
+    pub period_finish: i64,
+} // 开启奖励周期事件
+#[event]
+pub struct RetireRewardStreamEvent {
+    pub pool: Pubkey,
+    pub reward_index: u8,
+    pub reward_mint: Pubkey,
+} // 退役奖励流事件
+#[event]
+pub struct UpdateMaxBoostEvent {
+    pub pool: Pubkey,
+    pub max_boost_bps: u16,
+} // 更新最大加速倍数事件
+#[event]
+pub struct UpdateCooldownDurationEvent {
+    pub pool: Pubkey,
+    pub new_duration: i64,
+} // 更新冷却期事件
+#[event]
+pub struct UpdateSolvencyHorizonEvent {
+    pub pool: Pubkey,
+    pub new_horizon: i64,
+} // 更新偿付能力检查期限事件
+#[event]
+pub struct AdminTransferProposedEvent {
+    pub pool: Pubkey,
+    pub current_admin: Pubkey,
+    pub pending_admin: Pubkey,
+} // 提名管理员事件
+#[event]
+pub struct AdminTransferAcceptedEvent {
+    pub pool: Pubkey,
+    pub previous_admin: Pubkey,
     pub new_admin: Pubkey,
-} // 更改管理员事件
+} // 接受管理员事件
 #[event]
-pub struct PauseEvent {} // 暂停事件
+pub struct AdminTransferCancelledEvent {
+    pub pool: Pubkey,
+    pub cancelled_admin: Pubkey,
+} // 撤销管理员提名事件
 #[event]
-pub struct UnpauseEvent {} // 恢复事件
+pub struct PauseEvent {
+    pub pool: Pubkey,
+} // 暂停事件
+#[event]
+pub struct UnpauseEvent {
+    pub pool: Pubkey,
+} // 恢复事件
 #[event]
 pub struct UpdateLockupDurationEvent {
+    pub pool: Pubkey,
     pub new_duration: i64,
 } // 更新锁定期事件
+#[event]
+pub struct RoleGrantedEvent {
+    pub pool: Pubkey,
+    pub role: Role,
+    pub account: Pubkey,
+    pub previous: Pubkey,
+} // 授予角色事件
+#[event]
+pub struct RoleRevokedEvent {
+    pub pool: Pubkey,
+    pub role: Role,
+    pub account: Pubkey,
+} // 撤销角色事件
+#[event]
+pub struct UpdateTimelockDelayEvent {
+    pub pool: Pubkey,
+    pub new_delay: i64,
+} // 更新时间锁延迟事件
+#[event]
+pub struct ChangeScheduledEvent {
+    pub pool: Pubkey,
+    pub change_id: u64,
+    pub action: TimelockAction,
+    pub eta: i64,
+} // 排队变更事件
+#[event]
+pub struct ChangeExecutedEvent {
+    pub pool: Pubkey,
+    pub change_id: u64,
+    pub action: TimelockAction,
+} // 执行变更事件
+#[event]
+pub struct ChangeCancelledEvent {
+    pub pool: Pubkey,
+    pub change_id: u64,
+    pub action: TimelockAction,
+} // 撤销变更事件
+#[event]
+pub struct UpdateParameterBoundsEvent {
+    pub pool: Pubkey,
+    pub bounds: ParameterBounds,
+} // 更新参数边界事件
+#[event]
+pub struct ShutdownEvent {
+    pub pool: Pubkey,
+    pub guardian: Pubkey,
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub remaining_balance: u64,
+    pub outstanding_claims: u64,
+} // 紧急提取质押代币并关停事件
+#[event]
+pub struct EmergencyExitEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub claim: u64,
+    pub amount: u64,
+    pub net_amount: u64,
+} // 关停后紧急退出事件
+#[event]
+pub struct EmergencyUnstakeEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub net_amount: u64,
+    pub forfeit_rewards: bool,
+} // 暂停期间紧急取消质押事件
+#[event]
+pub struct UpdateMaxPauseDurationEvent {
+    pub pool: Pubkey,
+    pub new_duration: i64,
+} // 更新最长暂停时间事件
 
 // 定义错误码，描述可能的失败场景
 #[error_code]
@@ -680,4 +4084,112 @@ pub enum StakingError {
     NotPaused,
     #[msg("Insufficient balance in reward vault.")] // 奖励金库余额不足
     InsufficientVaultBalance,
+    #[msg("Reward stream index is out of range or unused.")] // 奖励流索引无效
+    InvalidRewardIndex,
+    #[msg("All reward stream slots are in use.")] // 奖励流槽位已满
+    RewardStreamsFull,
+    #[msg("This reward mint already has a reward stream.")] // 奖励代币已存在奖励流
+    DuplicateRewardMint,
+    #[msg("Reward stream has been retired.")] // 奖励流已退役
+    RewardStreamInactive,
+    #[msg("Reward vault does not match the reward stream.")] // 奖励金库与奖励流不匹配
+    RewardVaultMismatch,
+    #[msg("Reward mint does not match the reward stream.")] // 奖励代币与奖励流不匹配
+    RewardMintMismatch,
+    #[msg("Reward duration must be greater than zero.")] // 奖励周期必须大于 0
+    InvalidRewardDuration,
+    #[msg("Reward amount is too small for the duration.")] // 奖励金额不足以按秒发放
+    RewardRateTooLow,
+    #[msg("Reward period has finished.")] // 奖励周期已结束
+    RewardPeriodFinished,
+    #[msg("Reward rate would make the pool insolvent within the horizon.")] // 奖励率超出承受能力
+    RewardRateUnsustainable,
+    #[msg("Withdrawal would leave committed rewards unfunded.")] // 提取金额超过未占用奖励
+    WithdrawExceedsUnreservedRewards,
+    #[msg("Solvency horizon must be greater than zero.")] // 偿付能力检查期限必须大于 0
+    InvalidSolvencyHorizon,
+    #[msg("Too many stake lots; unstake unlocked lots first.")] // 质押批次数量已达上限
+    TooManyStakeLots,
+    #[msg("This pool requires request_unstake and a cooldown.")] // 池子要求两阶段退出
+    CooldownRequired,
+    #[msg("This pool has no cooldown; use unstake.")] // 池子未启用冷却期
+    CooldownNotEnabled,
+    #[msg("Cooldown duration cannot be negative.")] // 冷却期不能为负
+    InvalidCooldownDuration,
+    #[msg("Too many pending unstake requests.")] // 取消质押请求数量已达上限
+    TooManyUnbondingRequests,
+    #[msg("Unstake request does not exist.")] // 取消质押请求不存在
+    InvalidUnbondingRequest,
+    #[msg("No unstake request has finished its cooldown.")] // 没有冷却期已结束的请求
+    NothingToWithdraw,
+    #[msg("Pending unstake requests must be withdrawn first.")] // 需先提取冷却中的金额
+    UnbondingPending,
+    #[msg("Boost lock duration must be between one week and four years.")] // 加速锁定期超出范围
+    InvalidLockDuration,
+    #[msg("Max boost must be between 1x and 4x.")] // 最大加速倍数超出范围
+    InvalidBoost,
+    #[msg("Liquid staking requires a pool without lockup.")] // 流动性质押要求池子无锁定期
+    LiquidStakingRequiresNoLockup,
+    #[msg("No reward stream pays out the staking token.")] // 没有以质押代币发放的奖励流
+    NoCompoundableStream,
+    #[msg("Only the staker can compound unless auto-compound is on.")] // 无权为该用户复利
+    CompoundNotPermitted,
+    #[msg("Early unstaking is not enabled for this pool.")] // 池子未开启提前取消质押
+    EarlyUnstakeDisabled,
+    #[msg("Penalty cannot exceed 100%.")] // 罚金超出上限
+    InvalidPenalty,
+    #[msg("Treasury account must be set.")] // 国库地址不能为空
+    InvalidTreasury,
+    #[msg("Treasury account does not match the pool.")] // 国库账户与池子配置不匹配
+    TreasuryMismatch,
+    #[msg("Mint has an extension this pool does not allow.")] // Mint 含有未被允许的危险扩展
+    UnsupportedMintExtension,
+    #[msg("Pending admin cannot be the default public key.")] // 不能提名空地址
+    InvalidPendingAdmin,
+    #[msg("There is no pending admin transfer.")] // 没有待确认的管理员提名
+    NoPendingAdmin,
+    #[msg("Signer is not the pending admin.")] // 签名者不是被提名的新管理员
+    NotPendingAdmin,
+    #[msg("Signer does not hold the required role.")] // 签名者未持有所需角色
+    MissingRole,
+    #[msg("Role holder cannot be the default public key.")] // 不能将角色授予空地址
+    InvalidRoleHolder,
+    #[msg("Role is not assigned.")] // 角色未被授予
+    RoleNotAssigned,
+    #[msg("A timelock is active; schedule this change instead.")] // 已开启时间锁，需排队执行
+    TimelockRequired,
+    #[msg("Invalid timelock delay.")] // 时间锁延迟无效
+    InvalidTimelockDelay,
+    #[msg("ETA is earlier than the timelock delay allows.")] // 执行时间早于时间锁允许的最早时间
+    EtaTooEarly,
+    #[msg("Queued change is not executable yet.")] // 排队变更尚未到执行时间
+    TimelockNotReady,
+    #[msg("Queued change is already executable.")] // 排队变更已到期，不能再撤销
+    ChangeAlreadyExecutable,
+    #[msg("Parameter bounds are inconsistent or exceed the program limits.")] // 参数边界无效
+    InvalidParameterBounds,
+    #[msg("Lockup duration is outside the configured bounds.")] // 锁定期超出边界
+    LockupDurationOutOfBounds,
+    #[msg("Cooldown duration is outside the configured bounds.")] // 冷却期超出边界
+    CooldownDurationOutOfBounds,
+    #[msg("Timelock delay is outside the configured bounds.")] // 时间锁延迟超出边界
+    TimelockDelayOutOfBounds,
+    #[msg("Reward rate exceeds the configured maximum.")] // 奖励率超过上限
+    RewardRateAboveMax,
+    #[msg("Reward rate would overflow the reward accumulator.")] // 奖励率会导致累加器溢出
+    RewardRateOverflow,
+    #[msg("Pool has been shut down.")] // 池子已关停
+    PoolShutdown,
+    #[msg("Pool is not shut down.")] // 池子未关停
+    PoolNotShutdown,
+    #[msg("Invalid maximum pause duration.")] // 最长暂停时间无效
+    InvalidPauseDuration,
+    #[msg("Maximum pause duration is outside the configured bounds.")] // 最长暂停时间超出边界
+    PauseDurationOutOfBounds,
+    #[msg("Solvency horizon is outside the configured bounds.")] // 偿付能力检查期限超出边界
+    SolvencyHorizonOutOfBounds,
+    #[msg("Reward duration is outside the configured bounds.")] // 奖励周期超出边界
+    RewardDurationOutOfBounds,
+    #[msg("Liquid staking requires a pool without cooldown.")] // 流动性质押要求池子无冷却期
+    LiquidStakingRequiresNoCooldown,
 }
@@ -0,0 +1,128 @@
+// 集成测试使用的转账钩子程序：每个 Mint 的 ExtraAccountMetaList 声明一个按 Mint 派生的计数器账户，
+// 每次转账由 Token-2022 调用 execute 使计数器加一，用于验证质押程序在转账时正确转发额外账户
+
+// 忽略 Clippy 对大型错误类型的警告，因 Anchor 的 Result 类型可能较大
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    spl_token_2022::{
+        extension::{
+            transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions,
+        },
+        state::Account as SplTokenAccount,
+    },
+    Mint, TokenAccount,
+};
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{
+    account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList,
+};
+use spl_transfer_hook_interface::instruction::{
+    ExecuteInstruction, InitializeExtraAccountMetaListInstruction,
+};
+
+declare_id!("GCDQ8HPSEwk8VsqTDtUUMbzRLUdMFyzAqxTmtEVoMRu7");
+
+#[program]
+pub mod test_transfer_hook {
+    use super::*;
+
+    // 创建 Mint 的 ExtraAccountMetaList 和计数器账户，额外账户为 PDA [b"counter", mint]
+    #[instruction(discriminator = InitializeExtraAccountMetaListInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn initialize_extra_account_meta_list(
+        ctx: Context<InitializeExtraAccountMetaList>,
+    ) -> Result<()> {
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas()?,
+        )?;
+        Ok(())
+    }
+
+    // 转账钩子：只接受 Token-2022 在转账过程中的调用，记录转账次数和最近一次的金额
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn execute(ctx: Context<Execute>, amount: u64) -> Result<()> {
+        let source = ctx.accounts.source.to_account_info();
+        let data = source.try_borrow_data()?;
+        let account = StateWithExtensions::<SplTokenAccount>::unpack(&data)?;
+        require!(
+            bool::from(account.get_extension::<TransferHookAccount>()?.transferring),
+            HookError::NotTransferring
+        );
+
+        let counter = &mut ctx.accounts.counter;
+        counter.transfers = counter
+            .transfers
+            .checked_add(1)
+            .ok_or(HookError::ArithmeticOverflow)?;
+        counter.last_amount = amount;
+        Ok(())
+    }
+}
+
+// ExtraAccountMetaList 声明的额外账户：按 Mint（execute 的第 1 个账户）派生的可写计数器
+fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
+    Ok(vec![ExtraAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: b"counter".to_vec(),
+            },
+            Seed::AccountKey { index: 1 },
+        ],
+        false,
+        true,
+    )?])
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: ExtraAccountMetaList 账户，地址由转账钩子接口规定的种子派生，内容由指令写入
+    #[account(
+        init,
+        payer = payer,
+        space = ExtraAccountMetaList::size_of(1)?,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(init, payer = payer, space = 8 + Counter::INIT_SPACE, seeds = [b"counter", mint.key().as_ref()], bump)]
+    pub counter: Account<'info, Counter>,
+    pub system_program: Program<'info, System>,
+}
+
+// 账户顺序由转账钩子接口规定：源账户、Mint、目标账户、转出权限、ExtraAccountMetaList，之后是额外账户
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    #[account(token::mint = mint)]
+    pub source: InterfaceAccount<'info, TokenAccount>,
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(token::mint = mint)]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: 源账户的所有者或代理，可以是质押程序的池子 PDA
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: ExtraAccountMetaList 账户，只校验地址
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    #[account(mut, seeds = [b"counter", mint.key().as_ref()], bump)]
+    pub counter: Account<'info, Counter>,
+}
+
+// 每个 Mint 的转账计数器
+#[account]
+#[derive(InitSpace)]
+pub struct Counter {
+    pub transfers: u64,   // 已执行钩子的转账次数
+    pub last_amount: u64, // 最近一次转账的金额
+}
+
+#[error_code]
+pub enum HookError {
+    #[msg("The hook can only be invoked during a transfer.")] // 只能在转账过程中调用
+    NotTransferring,
+    #[msg("Arithmetic overflow.")] // 算术溢出
+    ArithmeticOverflow,
+}
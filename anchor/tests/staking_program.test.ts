@@ -7,11 +7,39 @@ import { Program } from '@coral-xyz/anchor'
 // 导入质押程序的类型定义，来自编译后的 IDL 文件
 import { StakingProgram } from '../target/types/staking_program'
 
+// 导入测试用转账钩子程序的类型定义
+import { TestTransferHook } from '../target/types/test_transfer_hook'
+
 // 导入 Solana web3.js 库的必要组件，包括密钥对、公钥、系统程序和 SOL 单位常量
-import { Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL } from '@solana/web3.js'
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  Transaction,
+  TransactionInstruction,
+  AccountMeta,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js'
 
 // 导入 SPL Token 2022 程序的功能，用于创建和管理代币账户
-import { createMint, createAccount, mintTo, getAccount, TOKEN_2022_PROGRAM_ID } from '@solana/spl-token'
+import {
+  createMint,
+  createAccount,
+  mintTo,
+  getAccount,
+  getMint,
+  getMintLen,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createInitializeDefaultAccountStateInstruction,
+  createInitializeTransferHookInstruction,
+  addExtraAccountMetasForExecute,
+  AccountState,
+  ExtensionType,
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+} from '@solana/spl-token'
 
 // 导入 BN.js 库，用于处理大整数运算（Solana 中常用）
 import { BN } from 'bn.js'
@@ -28,6 +56,8 @@ describe('staking_program', () => {
   anchor.setProvider(provider)
   // 从工作空间加载质押程序实例，类型为 stakingProgram
   const program = anchor.workspace.StakingProgram as Program<StakingProgram>
+  // 从工作空间加载测试用转账钩子程序，每次转账使按 Mint 派生的计数器加一
+  const hookProgram = anchor.workspace.TestTransferHook as Program<TestTransferHook>
   // 获取提供者的连接对象，用于与 Solana 网络交互
   const connection = provider.connection
 
@@ -68,8 +98,12 @@ describe('staking_program', () => {
   let userStakeInfoPda: PublicKey
 
   // --- 定义测试参数 ---
-  // 定义奖励速率（每秒 100 个最小单位）
+  // 定义池子编号（同一质押代币可创建多个池子）
+  const POOL_ID = new BN(1)
+  // 定义奖励速率（每秒 100 个最小单位），由注资金额 / 奖励周期得出
   const REWARD_RATE = new BN(100)
+  // 定义奖励周期（500 秒）
+  const REWARD_DURATION = new BN(500)
   // 定义锁定期（2 秒）
   const LOCKUP_DURATION = new BN(2)
   // 定义质押数量（1000 个代币）
@@ -154,18 +188,39 @@ describe('staking_program', () => {
       ),
     ])
 
-    // 派生质押池的 PDA 地址，使用种子 "pool"
-    ;[poolPda] = PublicKey.findProgramAddressSync([Buffer.from('pool')], program.programId)
-    // 派生质押金库的 PDA 地址，使用种子 "staking_vault"
-    ;[stakingVaultPda] = PublicKey.findProgramAddressSync([Buffer.from('staking_vault')], program.programId)
-    // 派生奖励金库的 PDA 地址，使用种子 "reward_vault"
-    ;[rewardVaultPda] = PublicKey.findProgramAddressSync([Buffer.from('reward_vault')], program.programId)
-    // 派生用户质押信息的 PDA 地址，使用种子 "stake_info" 和用户公钥
-    ;[userStakeInfoPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from('stake_info'), user.publicKey.toBuffer()],
+    // 派生质押池及其金库、用户质押信息的 PDA 地址
+    ;({ poolPda, stakingVaultPda, rewardVaultPda, userStakeInfoPda } = derivePoolPdas(POOL_ID, user.publicKey))
+  })
+
+  // 派生指定池子编号对应的池子、金库和用户质押信息 PDA
+  function derivePoolPdas(poolId: BN, owner: PublicKey) {
+    // 池子 PDA，使用种子 "pool"、质押代币 Mint 和池子编号（小端 8 字节）
+    const [pool] = PublicKey.findProgramAddressSync(
+      [Buffer.from('pool'), stakingMint.toBuffer(), poolId.toArrayLike(Buffer, 'le', 8)],
       program.programId,
     )
-  })
+    // 质押金库 PDA，使用种子 "staking_vault" 和池子地址
+    const [stakingVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from('staking_vault'), pool.toBuffer()],
+      program.programId,
+    )
+    // 初始奖励流的金库 PDA，使用种子 "reward_vault"、池子地址和奖励代币 Mint
+    const [rewardVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from('reward_vault'), pool.toBuffer(), rewardMint.toBuffer()],
+      program.programId,
+    )
+    // 用户质押信息 PDA，使用种子 "stake_info"、池子地址和用户公钥
+    const [userStakeInfo] = PublicKey.findProgramAddressSync(
+      [Buffer.from('stake_info'), pool.toBuffer(), owner.toBuffer()],
+      program.programId,
+    )
+    return {
+      poolPda: pool,
+      stakingVaultPda: stakingVault,
+      rewardVaultPda: rewardVault,
+      userStakeInfoPda: userStakeInfo,
+    }
+  }
 
   // --- 测试套件：初始化 ---
   describe('Initialization', () => {
@@ -173,7 +228,7 @@ describe('staking_program', () => {
     it('should initialize the staking pool correctly', async () => {
       // 调用程序的 initialize 方法，设置奖励速率和锁定期
       await program.methods
-        .initialize(REWARD_RATE, LOCKUP_DURATION)
+        .initialize(POOL_ID, LOCKUP_DURATION, 0)
         .accounts({
           // 质押池账户
           pool: poolPda,
@@ -192,6 +247,7 @@ describe('staking_program', () => {
           // SPL Token 程序
           tokenProgram: TOKEN_2022_PROGRAM_ID,
           // 租金系统变量
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
           rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
         // 管理员签名交易
@@ -208,12 +264,15 @@ describe('staking_program', () => {
 
       // 验证质押池的管理员地址
       expect(poolAccount.admin.toBase58()).toBe(admin.publicKey.toBase58())
+      // 验证池子编号
+      expect(poolAccount.poolId.toString()).toBe(POOL_ID.toString())
       // 验证质押代币的 Mint 地址
       expect(poolAccount.stakingMint.toBase58()).toBe(stakingMint.toBase58())
       // 验证奖励代币的 Mint 地址
-      expect(poolAccount.rewardMint.toBase58()).toBe(rewardMint.toBase58())
-      // 验证奖励速率
-      expect(poolAccount.rewardRate.toString()).toBe(REWARD_RATE.toString())
+      expect(poolAccount.rewardStreams[0].mint.toBase58()).toBe(rewardMint.toBase58())
+      // 验证注资前奖励速率为 0，且尚无奖励周期
+      expect(poolAccount.rewardStreams[0].rewardRate.toString()).toBe('0')
+      expect(poolAccount.rewardStreams[0].periodFinish.toString()).toBe('0')
       // 验证锁定期
       expect(poolAccount.lockupDuration.toString()).toBe(LOCKUP_DURATION.toString())
       // 验证初始总质押量为 0
@@ -229,16 +288,16 @@ describe('staking_program', () => {
 
   // --- 测试套件：用户操作 ---
   describe('User Actions', () => {
-    // 在用户操作测试前，为奖励池注资
+    // 在用户操作测试前，为奖励池注资并开启奖励周期
     beforeAll(async () => {
-      // 调用程序的 fundRewards 方法，向奖励金库注入代币
+      // 调用程序的 notifyRewardAmount 方法，向奖励金库注入代币，奖励率 = 注资金额 / 周期
       await program.methods
-        .fundRewards(FUND_AMOUNT)
+        .notifyRewardAmount(0, FUND_AMOUNT, REWARD_DURATION)
         .accounts({
           // 质押池账户
           pool: poolPda,
           // 管理员公钥
-          admin: admin.publicKey,
+          funder: admin.publicKey,
           // 管理员的奖励代币账户
           funderWallet: adminRewardWallet,
           // 奖励金库账户
@@ -252,6 +311,11 @@ describe('staking_program', () => {
         .signers([admin])
         // 发送交易并等待确认
         .rpc()
+
+      // 验证奖励率和周期结束时间已设置
+      const poolAccount = await program.account.pool.fetch(poolPda)
+      expect(poolAccount.rewardStreams[0].rewardRate.toString()).toBe(REWARD_RATE.toString())
+      expect(poolAccount.rewardStreams[0].periodFinish.toNumber()).toBeGreaterThan(0)
     })
 
     // 测试用例：验证用户可以成功质押代币
@@ -263,7 +327,7 @@ describe('staking_program', () => {
 
       // 调用程序的 stake 方法，质押指定数量的代币
       await program.methods
-        .stake(STAKE_AMOUNT)
+        .stake(STAKE_AMOUNT, new BN(0))
         .accounts({
           // 用户公钥
           user: user.publicKey,
@@ -306,6 +370,12 @@ describe('staking_program', () => {
       expect(userStakeInfo.stakeAmount.toString()).toBe(STAKE_AMOUNT.toString())
       // 验证质押开始时间戳大于 0
       expect(userStakeInfo.stakeStartTimestamp.toNumber()).toBeGreaterThan(0)
+      // 验证记录了一个质押批次，解锁时间为存入时间 + 锁定期
+      expect(userStakeInfo.lots.length).toBe(1)
+      expect(userStakeInfo.lots[0].amount.toString()).toBe(STAKE_AMOUNT.toString())
+      expect(userStakeInfo.lots[0].unlockTimestamp.toNumber()).toBeGreaterThanOrEqual(
+        userStakeInfo.stakeStartTimestamp.toNumber() + LOCKUP_DURATION.toNumber(),
+      )
     })
 
     // 测试用例：验证质押 0 个代币会失败
@@ -313,7 +383,7 @@ describe('staking_program', () => {
       // 期望调用 stake 方法（质押 0 个代币）会抛出错误
       await expect(
         program.methods
-          .stake(new BN(0))
+          .stake(new BN(0), new BN(0))
           .accounts({
             // 用户公钥
             user: user.publicKey,
@@ -378,7 +448,7 @@ describe('staking_program', () => {
 
       // 调用程序的 claimRewards 方法，领取奖励
       await program.methods
-        .claimRewards()
+        .claimRewards(0)
         .accounts({
           // 用户公钥
           user: user.publicKey,
@@ -408,7 +478,7 @@ describe('staking_program', () => {
       // 验证用户奖励代币增加
       expect(Number(userRewardWalletAfter.amount)).toBeGreaterThan(Number(userRewardWalletBefore.amount))
       // 验证用户质押信息中的奖励清零
-      expect(userStakeInfo.rewards.toString()).toBe('0')
+      expect(userStakeInfo.rewards[0].toString()).toBe('0')
     })
 
     // 测试用例：验证无奖励可领取时会失败
@@ -416,7 +486,7 @@ describe('staking_program', () => {
       // 期望调用 claimRewards 方法会抛出错误（因为刚领取过奖励）
       await expect(
         program.methods
-          .claimRewards()
+          .claimRewards(0)
           .accounts({
             // 用户公钥
             user: user.publicKey,
@@ -492,10 +562,10 @@ describe('staking_program', () => {
       expect(info.stakeAmount.toString()).toBe('0')
 
       // 如果有剩余奖励，先领取
-      if (info.rewards.toNumber() > 0) {
+      if (info.rewards[0].toNumber() > 0) {
         // 调用程序的 claimRewards 方法，领取剩余奖励
         await program.methods
-          .claimRewards()
+          .claimRewards(0)
           .accounts({
             // 用户公钥
             user: user.publicKey,
@@ -527,6 +597,8 @@ describe('staking_program', () => {
         .accounts({
           // 用户公钥
           user: user.publicKey,
+          // 质押池账户
+          pool: poolPda,
           // 用户质押信息账户
           userStakeInfo: userStakeInfoPda,
         })
@@ -544,201 +616,2623 @@ describe('staking_program', () => {
       // 验证租金已返还，用户余额增加
       expect(userBalanceAfter).toBeGreaterThan(userBalanceBefore)
     })
-  })
 
-  // --- 测试套件：管理员操作 ---
-  describe('Admin Actions', () => {
-    // 测试用例：验证管理员可以更改奖励速率
-    it('should allow admin to change the reward rate', async () => {
-      // 定义新的奖励速率（200）
-      const NEW_RATE = new BN(200)
-      // 调用程序的 updateRewardRate 方法
+    // 测试用例：验证同一质押代币可以创建多个独立池子，且同一用户可同时在多个池子中质押
+    it('should support several independent pools for the same staking mint', async () => {
+      // 定义第二个池子的编号
+      const SECOND_POOL_ID = new BN(2)
+      // 派生第二个池子相关的 PDA
+      const second = derivePoolPdas(SECOND_POOL_ID, user.publicKey)
+
+      // 初始化第二个池子，复用相同的质押和奖励代币
       await program.methods
-        .updateRewardRate(NEW_RATE)
+        .initialize(SECOND_POOL_ID, new BN(0), 0)
         .accounts({
-          // 质押池账户
-          pool: poolPda,
-          // 管理员公钥
+          pool: second.poolPda,
           admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: second.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: second.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
-        // 管理员签名交易
         .signers([admin])
-        // 发送交易并等待确认
         .rpc()
-      // 获取质押池账户数据
-      const poolAccount = await program.account.pool.fetch(poolPda)
-      // 验证奖励速率已更新
-      expect(poolAccount.rewardRate.toString()).toBe(NEW_RATE.toString())
+
+      // 在第一个池子中质押
+      await program.methods
+        .stake(STAKE_AMOUNT, new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: poolPda,
+          userStakeInfo: userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+
+      // 在第二个池子中质押
+      await program.methods
+        .stake(STAKE_AMOUNT, new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: second.poolPda,
+          userStakeInfo: second.userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: second.stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+
+      // 验证两个池子的账本互相独立
+      const firstPool = await program.account.pool.fetch(poolPda)
+      const secondPool = await program.account.pool.fetch(second.poolPda)
+      expect(firstPool.totalStaked.toString()).toBe(STAKE_AMOUNT.toString())
+      expect(secondPool.totalStaked.toString()).toBe(STAKE_AMOUNT.toString())
+
+      // 验证用户在两个池子中各有一份质押记录
+      const firstInfo = await program.account.userStakeInfo.fetch(userStakeInfoPda)
+      const secondInfo = await program.account.userStakeInfo.fetch(second.userStakeInfoPda)
+      expect(firstInfo.pool.toBase58()).toBe(poolPda.toBase58())
+      expect(secondInfo.pool.toBase58()).toBe(second.poolPda.toBase58())
+      expect(secondInfo.stakeAmount.toString()).toBe(STAKE_AMOUNT.toString())
+
+      // 从第二个池子取消质押（锁定期为 0），不影响第一个池子
+      await program.methods
+        .unstake(STAKE_AMOUNT)
+        .accounts({
+          user: user.publicKey,
+          pool: second.poolPda,
+          userStakeInfo: second.userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: second.stakingVaultPda,
+          stakingMint: stakingMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+
+      expect((await program.account.pool.fetch(second.poolPda)).totalStaked.toString()).toBe('0')
+      expect((await program.account.pool.fetch(poolPda)).totalStaked.toString()).toBe(STAKE_AMOUNT.toString())
     })
 
-    // 测试用例：验证管理员可以暂停和取消暂停程序
-    it('should allow admin to pause and unpause the program', async () => {
-      // 调用程序的 pause 方法，暂停程序
+    // 测试用例：验证池子可以同时发放多种奖励代币，并可退役奖励流
+    it('should pay a second reward stream and allow retiring it', async () => {
+      // 创建合作方奖励代币及相关账户
+      const partnerMint = await createMint(
+        connection,
+        admin,
+        admin.publicKey,
+        null,
+        6,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      const adminPartnerWallet = await createAccount(
+        connection,
+        admin,
+        partnerMint,
+        admin.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      const userPartnerWallet = await createAccount(
+        connection,
+        user,
+        partnerMint,
+        user.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      await mintTo(
+        connection,
+        admin,
+        partnerMint,
+        adminPartnerWallet,
+        admin,
+        FUND_AMOUNT.toNumber(),
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      // 派生合作方奖励金库 PDA
+      const [partnerVaultPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), poolPda.toBuffer(), partnerMint.toBuffer()],
+        program.programId,
+      )
+
+      // 新增第二条奖励流
       await program.methods
-        .pause()
-        .accounts({ pool: poolPda, admin: admin.publicKey })
-        // 管理员签名交易
+        .addRewardStream(0)
+        .accounts({
+          pool: poolPda,
+          admin: admin.publicKey,
+          rewardMint: partnerMint,
+          rewardVault: partnerVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
         .signers([admin])
-        // 发送交易并等待确认
         .rpc()
-      // 获取质押池账户数据
-      let poolAccount = await program.account.pool.fetch(poolPda)
-      // 验证程序已暂停
-      expect(poolAccount.isPaused).toBe(true)
 
-      // 验证暂停后用户无法质押
+      // 同一奖励代币不能重复添加
       await expect(
         program.methods
-          .stake(new BN(100))
+          .addRewardStream(0)
           .accounts({
-            // 用户公钥
-            user: user.publicKey,
-            // 质押池账户
             pool: poolPda,
-            // 用户质押信息账户
-            userStakeInfo: userStakeInfoPda,
-            // 用户的质押代币账户
-            userStakingWallet: userStakingWallet,
-            // 质押金库账户
-            stakingVault: stakingVaultPda,
-            // 质押代币的 Mint
-            stakingMint: stakingMint,
-            // 系统程序
+            admin: admin.publicKey,
+            rewardMint: partnerMint,
+            rewardVault: partnerVaultPda,
             systemProgram: SystemProgram.programId,
-            // SPL Token 程序
             tokenProgram: TOKEN_2022_PROGRAM_ID,
           })
-          // 用户签名交易
-          .signers([user])
-          // 发送交易
+          .signers([admin])
           .rpc(),
-      ).rejects.toThrow(/ProgramPaused/) // 期望抛出 ProgramPaused 错误
-
-      // 调用程序的 unpause 方法，取消暂停
-      await program.methods
-        .unpause()
-        .accounts({ pool: poolPda, admin: admin.publicKey })
-        // 管理员签名交易
-        .signers([admin])
-        // 发送交易并等待确认
-        .rpc()
-      // 获取质押池账户数据
-      poolAccount = await program.account.pool.fetch(poolPda)
-      // 验证程序已取消暂停
-      expect(poolAccount.isPaused).toBe(false)
-    })
+      ).rejects.toThrow()
 
-    // 测试用例：验证管理员可以更换管理员公钥
-    it('should allow admin to change the admin key', async () => {
-      // 调用程序的 changeAdmin 方法，更换管理员
+      // 为第二条奖励流注资并开启奖励周期
       await program.methods
-        .changeAdmin(newAdmin.publicKey)
-        .accounts({ pool: poolPda, admin: admin.publicKey })
-        // 原管理员签名交易
+        .notifyRewardAmount(1, FUND_AMOUNT, REWARD_DURATION)
+        .accounts({
+          pool: poolPda,
+          funder: admin.publicKey,
+          funderWallet: adminPartnerWallet,
+          rewardVault: partnerVaultPda,
+          rewardMint: partnerMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
         .signers([admin])
-        // 发送交易并等待确认
         .rpc()
 
-      // 获取质押池账户数据
-      const poolAccount = await program.account.pool.fetch(poolPda)
-      // 验证新管理员公钥已设置
-      expect(poolAccount.admin.toBase58()).toBe(newAdmin.publicKey.toBase58())
+      // 等待奖励累积
+      await sleep(2000)
 
-      // 验证旧管理员无法再执行管理员操作
+      // 使用不匹配的金库领取应失败
       await expect(
-        program.methods.pause().accounts({ pool: poolPda, admin: admin.publicKey }).signers([admin]).rpc(),
-      ).rejects.toThrow() // 期望抛出 has_one 约束错误
+        program.methods
+          .claimRewards(1)
+          .accounts({
+            user: user.publicKey,
+            pool: poolPda,
+            userStakeInfo: userStakeInfoPda,
+            userRewardWallet: userPartnerWallet,
+            rewardVault: rewardVaultPda,
+            rewardMint: partnerMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+      ).rejects.toThrow()
 
-      // 验证新管理员可以执行管理员操作
+      // 领取第二条奖励流的奖励
       await program.methods
-        .pause()
-        .accounts({ pool: poolPda, admin: newAdmin.publicKey })
-        // 新管理员签名交易
-        .signers([newAdmin])
-        // 发送交易并等待确认
+        .claimRewards(1)
+        .accounts({
+          user: user.publicKey,
+          pool: poolPda,
+          userStakeInfo: userStakeInfoPda,
+          userRewardWallet: userPartnerWallet,
+          rewardVault: partnerVaultPda,
+          rewardMint: partnerMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
         .rpc()
 
-      // 恢复程序状态（取消暂停），方便后续测试
+      // 验证用户收到合作方奖励，且第一条奖励流的奖励仍保留待领
+      const partnerBalance = await getAccount(connection, userPartnerWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(partnerBalance.amount)).toBeGreaterThan(0)
+      const info = await program.account.userStakeInfo.fetch(userStakeInfoPda)
+      expect(info.rewards[1].toString()).toBe('0')
+      expect(info.rewards[0].toNumber()).toBeGreaterThan(0)
+
+      // 退役第二条奖励流
       await program.methods
-        .unpause()
-        .accounts({ pool: poolPda, admin: newAdmin.publicKey })
-        // 新管理员签名交易
-        .signers([newAdmin])
-        // 发送交易并等待确认
+        .retireRewardStream(1)
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
         .rpc()
-    })
+      const poolAccount = await program.account.pool.fetch(poolPda)
+      expect(poolAccount.rewardStreams[1].isActive).toBe(false)
+      expect(poolAccount.rewardStreams[1].rewardRate.toString()).toBe('0')
 
-    // 测试用例：验证非管理员无法执行管理员操作
-    it('should fail if a non-admin tries an admin action', async () => {
-      // 期望未授权用户调用 updateRewardRate 方法会失败
+      // 已退役的奖励流不能再设置奖励率
       await expect(
         program.methods
-          .updateRewardRate(new BN(999))
-          .accounts({
-            // 质押池账户
-            pool: poolPda,
-            // 未授权用户公钥
-            admin: unauthorizedUser.publicKey,
-          })
-          // 未授权用户签名交易
-          .signers([unauthorizedUser])
-          // 发送交易
+          .updateRewardRate(1, REWARD_RATE)
+          .accounts({ pool: poolPda, rateManager: admin.publicKey })
+          .signers([admin])
           .rpc(),
-      ).rejects.toThrow() // 期望抛出 has_one 约束错误
+      ).rejects.toThrow(/RewardStreamInactive/)
     })
 
-    // 测试用例：验证管理员可以紧急提取奖励代币
-    it('should allow admin to perform an emergency withdrawal of reward tokens', async () => {
-      // 定义要提取的代币数量
-      const amountToWithdraw = new BN(1000)
-      // 获取奖励金库的初始状态
-      const vaultBefore = await getAccount(connection, rewardVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
-      // 获取目标账户（未授权用户的奖励代币账户）的初始状态
-      const destinationWalletBefore = await getAccount(
-        connection,
-        unauthorizedUserRewardWallet,
-        undefined,
-        TOKEN_2022_PROGRAM_ID,
-      )
+    // 测试用例：验证奖励只累积到周期结束时间，承诺的奖励不超过注资
+    it('should stop accruing rewards at period_finish', async () => {
+      // 使用独立池子，锁定期为 0
+      const PERIOD_POOL_ID = new BN(3)
+      const periodPool = derivePoolPdas(PERIOD_POOL_ID, user.publicKey)
+      const PERIOD_AMOUNT = new BN(300)
+      const PERIOD_DURATION = new BN(2)
 
-      // 调用程序的 emergencyWithdrawRewardTokens 方法，提取奖励代币
       await program.methods
-        .emergencyWithdrawRewardTokens(amountToWithdraw)
+        .initialize(PERIOD_POOL_ID, new BN(0), 0)
         .accounts({
-          // 质押池账户
-          pool: poolPda,
-          // 当前管理员公钥（newAdmin）
-          admin: newAdmin.publicKey,
-          // 奖励代币的 Mint
+          pool: periodPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: periodPool.stakingVaultPda,
           rewardMint: rewardMint,
-          // 奖励金库账户
-          rewardVault: rewardVaultPda,
-          // 目标代币账户
-          destinationWallet: unauthorizedUserRewardWallet,
-          // SPL Token 程序
+          rewardVault: periodPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
           tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
         })
-        // 新管理员签名交易
-        .signers([newAdmin])
-        // 发送交易并等待确认
+        .signers([admin])
         .rpc()
 
-      // 获取奖励金库的最终状态
-      const vaultAfter = await getAccount(connection, rewardVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
-      // 获取目标账户的最终状态
-      const destinationWalletAfter = await getAccount(
-        connection,
-        unauthorizedUserRewardWallet,
-        undefined,
-        TOKEN_2022_PROGRAM_ID,
-      )
-
-      // 验证奖励金库代币减少了提取数量
-      expect(Number(vaultAfter.amount)).toBe(Number(vaultBefore.amount) - amountToWithdraw.toNumber())
-      // 验证目标账户代币增加了提取数量
-      expect(Number(destinationWalletAfter.amount)).toBe(
-        Number(destinationWalletBefore.amount) + amountToWithdraw.toNumber(),
-      )
+      // 先质押，再开启一个 2 秒、总额 300 的奖励周期
+      await program.methods
+        .stake(new BN(100), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: periodPool.poolPda,
+          userStakeInfo: periodPool.userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: periodPool.stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      await program.methods
+        .notifyRewardAmount(0, PERIOD_AMOUNT, PERIOD_DURATION)
+        .accounts({
+          pool: periodPool.poolPda,
+          funder: admin.publicKey,
+          funderWallet: adminRewardWallet,
+          rewardVault: periodPool.rewardVaultPda,
+          rewardMint: rewardMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc()
+
+      // 等待周期结束后再领取
+      await sleep(4000)
+      const before = await getAccount(connection, userRewardWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      await program.methods
+        .claimRewards(0)
+        .accounts({
+          user: user.publicKey,
+          pool: periodPool.poolPda,
+          userStakeInfo: periodPool.userStakeInfoPda,
+          userRewardWallet: userRewardWallet,
+          rewardVault: periodPool.rewardVaultPda,
+          rewardMint: rewardMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      const after = await getAccount(connection, userRewardWallet, undefined, TOKEN_2022_PROGRAM_ID)
+
+      // 验证领取的奖励不超过本周期注资金额
+      const claimed = Number(after.amount) - Number(before.amount)
+      expect(claimed).toBeGreaterThan(0)
+      expect(claimed).toBeLessThanOrEqual(PERIOD_AMOUNT.toNumber())
+
+      // 周期结束后不能再调整奖励率
+      await expect(
+        program.methods
+          .updateRewardRate(0, REWARD_RATE)
+          .accounts({ pool: periodPool.poolPda, rateManager: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/RewardPeriodFinished/)
+    })
+
+    // 测试用例：验证追加质押的批次独立锁定，不能借旧批次的解锁时间提前提取
+    it('should lock each deposit independently', async () => {
+      // 使用独立池子，锁定期为 3 秒
+      const LOTS_POOL_ID = new BN(4)
+      const lotsPool = derivePoolPdas(LOTS_POOL_ID, user.publicKey)
+      const LOT_AMOUNT = new BN(100)
+
+      await program.methods
+        .initialize(LOTS_POOL_ID, new BN(3), 0)
+        .accounts({
+          pool: lotsPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: lotsPool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: lotsPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+
+      const stakeLot = () =>
+        program.methods
+          .stake(LOT_AMOUNT, new BN(0))
+          .accounts({
+            user: user.publicKey,
+            pool: lotsPool.poolPda,
+            userStakeInfo: lotsPool.userStakeInfoPda,
+            userStakingWallet: userStakingWallet,
+            stakingVault: lotsPool.stakingVaultPda,
+            stakingMint: stakingMint,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc()
+      const unstakeLots = (amount: BN) =>
+        program.methods
+          .unstake(amount)
+          .accounts({
+            user: user.publicKey,
+            pool: lotsPool.poolPda,
+            userStakeInfo: lotsPool.userStakeInfoPda,
+            userStakingWallet: userStakingWallet,
+            stakingVault: lotsPool.stakingVaultPda,
+            stakingMint: stakingMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc()
+
+      // 第一笔存入，等待其解锁后再追加第二笔
+      await stakeLot()
+      await sleep(4000)
+      await stakeLot()
+
+      let info = await program.account.userStakeInfo.fetch(lotsPool.userStakeInfoPda)
+      expect(info.lots.length).toBe(2)
+
+      // 第二笔仍在锁定期，不能一次提取全部
+      await expect(unstakeLots(LOT_AMOUNT.muln(2))).rejects.toThrow(/LockupPeriodNotEnded/)
+
+      // 只能提取已解锁的第一笔
+      await unstakeLots(LOT_AMOUNT)
+      info = await program.account.userStakeInfo.fetch(lotsPool.userStakeInfoPda)
+      expect(info.stakeAmount.toString()).toBe(LOT_AMOUNT.toString())
+      expect(info.lots.length).toBe(1)
+    })
+
+    // 测试用例：验证启用冷却期后需两阶段退出，冷却中的金额停止计息
+    it('should require request_unstake and a cooldown when enabled', async () => {
+      // 使用独立池子，锁定期为 0，冷却期为 2 秒
+      const COOLDOWN_POOL_ID = new BN(5)
+      const cooldownPool = derivePoolPdas(COOLDOWN_POOL_ID, user.publicKey)
+
+      await program.methods
+        .initialize(COOLDOWN_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: cooldownPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: cooldownPool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: cooldownPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .updateCooldownDuration(new BN(2))
+        .accounts({ pool: cooldownPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      await program.methods
+        .stake(new BN(100), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: cooldownPool.poolPda,
+          userStakeInfo: cooldownPool.userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: cooldownPool.stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+
+      const unstakeAccounts = {
+        user: user.publicKey,
+        pool: cooldownPool.poolPda,
+        userStakeInfo: cooldownPool.userStakeInfoPda,
+        userStakingWallet: userStakingWallet,
+        stakingVault: cooldownPool.stakingVaultPda,
+        stakingMint: stakingMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      const unbondingAccounts = {
+        user: user.publicKey,
+        pool: cooldownPool.poolPda,
+        userStakeInfo: cooldownPool.userStakeInfoPda,
+      }
+
+      // 启用冷却期后不能直接取消质押
+      await expect(
+        program.methods.unstake(new BN(100)).accounts(unstakeAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/CooldownRequired/)
+
+      // 发起两笔取消质押请求，并撤销第二笔
+      await program.methods.requestUnstake(new BN(60)).accounts(unbondingAccounts).signers([user]).rpc()
+      await program.methods.requestUnstake(new BN(40)).accounts(unbondingAccounts).signers([user]).rpc()
+      await program.methods.cancelUnstakeRequest(1).accounts(unbondingAccounts).signers([user]).rpc()
+
+      let poolAccount = await program.account.pool.fetch(cooldownPool.poolPda)
+      let info = await program.account.userStakeInfo.fetch(cooldownPool.userStakeInfoPda)
+      expect(poolAccount.totalStaked.toString()).toBe('40')
+      expect(poolAccount.totalUnbonding.toString()).toBe('60')
+      expect(info.stakeAmount.toString()).toBe('40')
+      expect(info.unbonding.length).toBe(1)
+
+      // 冷却期未结束时无法提取
+      await expect(
+        program.methods.withdrawUnstaked().accounts(unstakeAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/NothingToWithdraw/)
+
+      // 冷却期结束后提取
+      await sleep(3000)
+      const before = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      await program.methods.withdrawUnstaked().accounts(unstakeAccounts).signers([user]).rpc()
+      const after = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(after.amount) - Number(before.amount)).toBe(60)
+
+      poolAccount = await program.account.pool.fetch(cooldownPool.poolPda)
+      info = await program.account.userStakeInfo.fetch(cooldownPool.userStakeInfoPda)
+      expect(poolAccount.totalUnbonding.toString()).toBe('0')
+      expect(info.unbonding.length).toBe(0)
+    })
+
+    // 测试用例：验证选择加速锁定期的质押获得更高权重，且任何人都可以 poke 重新加权
+    it('should boost the weight of long locks and allow anyone to poke', async () => {
+      // 使用独立池子，最大加速倍数为 4x
+      const BOOST_POOL_ID = new BN(6)
+      const boostPool = derivePoolPdas(BOOST_POOL_ID, user.publicKey)
+      const MAX_LOCK = new BN(4 * 365 * 24 * 60 * 60)
+
+      await program.methods
+        .initialize(BOOST_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: boostPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: boostPool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: boostPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .updateMaxBoost(40000)
+        .accounts({ pool: boostPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      const stakeAccounts = {
+        user: user.publicKey,
+        pool: boostPool.poolPda,
+        userStakeInfo: boostPool.userStakeInfoPda,
+        userStakingWallet: userStakingWallet,
+        stakingVault: boostPool.stakingVaultPda,
+        stakingMint: stakingMint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+
+      // 加速锁定期不足 1 周应被拒绝
+      await expect(
+        program.methods.stake(new BN(10), new BN(100)).accounts(stakeAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/InvalidLockDuration/)
+
+      // 锁定 4 年，获得接近 4x 的权重
+      await program.methods.stake(new BN(10), MAX_LOCK).accounts(stakeAccounts).signers([user]).rpc()
+      let info = await program.account.userStakeInfo.fetch(boostPool.userStakeInfoPda)
+      let poolAccount = await program.account.pool.fetch(boostPool.poolPda)
+      expect(info.weightedAmount.toNumber()).toBeGreaterThan(info.stakeAmount.toNumber())
+      expect(info.weightedAmount.toNumber()).toBeLessThanOrEqual(40)
+      expect(poolAccount.totalWeightedStake.toString()).toBe(info.weightedAmount.toString())
+
+      // 任何人都可以调用 poke，权重随剩余锁定期衰减且不会增加
+      await program.methods
+        .poke()
+        .accounts({ pool: boostPool.poolPda, userStakeInfo: boostPool.userStakeInfoPda })
+        .rpc()
+      const weightBefore = info.weightedAmount.toNumber()
+      info = await program.account.userStakeInfo.fetch(boostPool.userStakeInfoPda)
+      poolAccount = await program.account.pool.fetch(boostPool.poolPda)
+      expect(info.weightedAmount.toNumber()).toBeLessThanOrEqual(weightBefore)
+      expect(poolAccount.totalWeightedStake.toString()).toBe(info.weightedAmount.toString())
+    })
+
+    // 测试用例：验证收据代币按份额价格铸造和销毁，且份额桶累积质押代币计价的奖励
+    it('should mint receipt tokens on stake_liquid and redeem them with rewards', async () => {
+      // 使用独立池子，奖励代币与质押代币相同，使份额价格随奖励上涨
+      const LIQUID_POOL_ID = new BN(7)
+      const liquidPool = derivePoolPdas(LIQUID_POOL_ID, user.publicKey)
+      const [liquidRewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), liquidPool.poolPda.toBuffer(), stakingMint.toBuffer()],
+        program.programId,
+      )
+      const [receiptMint] = PublicKey.findProgramAddressSync(
+        [Buffer.from('receipt_mint'), liquidPool.poolPda.toBuffer()],
+        program.programId,
+      )
+      const LIQUID_AMOUNT = new BN(50)
+
+      await program.methods
+        .initialize(LIQUID_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: liquidPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: liquidPool.stakingVaultPda,
+          rewardMint: stakingMint,
+          rewardVault: liquidRewardVault,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+
+      // 管理员以质押代币注资奖励周期
+      await mintTo(
+        connection,
+        admin,
+        stakingMint,
+        adminStakingWallet,
+        admin,
+        1000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      await program.methods
+        .notifyRewardAmount(0, new BN(1000), new BN(100))
+        .accounts({
+          pool: liquidPool.poolPda,
+          funder: admin.publicKey,
+          funderWallet: adminStakingWallet,
+          rewardVault: liquidRewardVault,
+          rewardMint: stakingMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc()
+
+      const enableAccounts = {
+        pool: liquidPool.poolPda,
+        admin: admin.publicKey,
+        stakingMint: stakingMint,
+        receiptMint: receiptMint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      const cooldownAccounts = { pool: liquidPool.poolPda, admin: admin.publicKey }
+
+      // 有冷却期的池子不能开启收据代币，份额桶无法走两阶段退出
+      await program.methods.updateCooldownDuration(new BN(2)).accounts(cooldownAccounts).signers([admin]).rpc()
+      await expect(
+        program.methods.enableReceiptToken().accounts(enableAccounts).signers([admin]).rpc(),
+      ).rejects.toThrow(/LiquidStakingRequiresNoCooldown/)
+      await program.methods.updateCooldownDuration(new BN(0)).accounts(cooldownAccounts).signers([admin]).rpc()
+
+      // 开启收据代币，Mint 权限归池子
+      await program.methods.enableReceiptToken().accounts(enableAccounts).signers([admin]).rpc()
+      let poolAccount = await program.account.pool.fetch(liquidPool.poolPda)
+      expect(poolAccount.receiptMint.toBase58()).toBe(receiptMint.toBase58())
+
+      const userReceiptWallet = await createAccount(
+        connection,
+        user,
+        receiptMint,
+        user.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      const liquidAccounts = {
+        user: user.publicKey,
+        pool: liquidPool.poolPda,
+        userStakingWallet: userStakingWallet,
+        userReceiptWallet: userReceiptWallet,
+        stakingVault: liquidPool.stakingVaultPda,
+        stakingMint: stakingMint,
+        receiptMint: receiptMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+
+      // 有锁定期的池子不支持流动性质押
+      await program.methods
+        .updateLockupDuration(new BN(10))
+        .accounts({ pool: liquidPool.poolPda, rateManager: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await expect(
+        program.methods.stakeLiquid(LIQUID_AMOUNT).accounts(liquidAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/LiquidStakingRequiresNoLockup/)
+      await program.methods
+        .updateLockupDuration(new BN(0))
+        .accounts({ pool: liquidPool.poolPda, rateManager: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 首次存入按 1:1 铸造份额
+      await program.methods.stakeLiquid(LIQUID_AMOUNT).accounts(liquidAccounts).signers([user]).rpc()
+      const receipt = await getAccount(connection, userReceiptWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(receipt.amount.toString()).toBe(LIQUID_AMOUNT.toString())
+      poolAccount = await program.account.pool.fetch(liquidPool.poolPda)
+      expect(poolAccount.liquidPrincipal.toString()).toBe(LIQUID_AMOUNT.toString())
+      expect(poolAccount.totalWeightedStake.toString()).toBe(LIQUID_AMOUNT.toString())
+
+      // 开启收据代币后不能再设置冷却期，否则份额桶可绕过两阶段退出
+      await expect(
+        program.methods.updateCooldownDuration(new BN(2)).accounts(cooldownAccounts).signers([admin]).rpc(),
+      ).rejects.toThrow(/LiquidStakingRequiresNoCooldown/)
+
+      // 暂停期间不能赎回份额
+      await program.methods
+        .pause()
+        .accounts({ pool: liquidPool.poolPda, pauser: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await expect(
+        program.methods
+          .unstakeLiquid(LIQUID_AMOUNT)
+          .accounts({ ...liquidAccounts, rewardVault: liquidRewardVault })
+          .signers([user])
+          .rpc(),
+      ).rejects.toThrow(/ProgramPaused/)
+      await program.methods
+        .unpause()
+        .accounts({ pool: liquidPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 等待奖励累积后销毁全部份额，取回本金和份额桶的奖励
+      await sleep(2000)
+      const before = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      await program.methods
+        .unstakeLiquid(LIQUID_AMOUNT)
+        .accounts({ ...liquidAccounts, rewardVault: liquidRewardVault })
+        .signers([user])
+        .rpc()
+      const after = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(after.amount - before.amount)).toBeGreaterThan(LIQUID_AMOUNT.toNumber())
+      poolAccount = await program.account.pool.fetch(liquidPool.poolPda)
+      expect(poolAccount.liquidPrincipal.toNumber()).toBe(0)
+      expect(poolAccount.totalStaked.toNumber()).toBe(0)
+    })
+
+    // 测试用例：验证 compound 将质押代币奖励直接计入质押量，开启自动复利后任何人都可调用
+    it('should compound staking-token rewards into the stake', async () => {
+      // 复用上一个用例中奖励代币与质押代币相同的池子
+      const liquidPool = derivePoolPdas(new BN(7), user.publicKey)
+      const [liquidRewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), liquidPool.poolPda.toBuffer(), stakingMint.toBuffer()],
+        program.programId,
+      )
+
+      await program.methods
+        .stake(new BN(20), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: liquidPool.poolPda,
+          userStakeInfo: liquidPool.userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: liquidPool.stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      await sleep(2000)
+
+      const compoundAccounts = {
+        pool: liquidPool.poolPda,
+        userStakeInfo: liquidPool.userStakeInfoPda,
+        stakingVault: liquidPool.stakingVaultPda,
+        rewardVault: liquidRewardVault,
+        stakingMint: stakingMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+
+      // 未开启自动复利时，其他人不能为用户复利
+      await expect(
+        program.methods
+          .compound()
+          .accounts({ ...compoundAccounts, caller: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc(),
+      ).rejects.toThrow(/CompoundNotPermitted/)
+
+      // 用户本人复利，奖励计入质押量且不产生新的锁定
+      await program.methods
+        .compound()
+        .accounts({ ...compoundAccounts, caller: user.publicKey })
+        .signers([user])
+        .rpc()
+      let info = await program.account.userStakeInfo.fetch(liquidPool.userStakeInfoPda)
+      expect(info.stakeAmount.toNumber()).toBeGreaterThan(20)
+      expect(info.rewards[0].toNumber()).toBe(0)
+
+      // 开启自动复利后，任何人都可以为用户复利
+      await program.methods
+        .setAutoCompound(true)
+        .accounts({ pool: liquidPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await sleep(2000)
+      const stakedBefore = info.stakeAmount.toNumber()
+      await program.methods
+        .compound()
+        .accounts({ ...compoundAccounts, caller: unauthorizedUser.publicKey })
+        .signers([unauthorizedUser])
+        .rpc()
+      info = await program.account.userStakeInfo.fetch(liquidPool.userStakeInfoPda)
+      expect(info.stakeAmount.toNumber()).toBeGreaterThan(stakedBefore)
+
+      // 反复为用户复利只会并入已解锁批次，不会占满批次上限
+      const lotsBefore = info.lots.length
+      for (let i = 0; i < 3; i++) {
+        await sleep(1100)
+        await program.methods
+          .compound()
+          .accounts({ ...compoundAccounts, caller: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc()
+      }
+      info = await program.account.userStakeInfo.fetch(liquidPool.userStakeInfoPda)
+      expect(info.lots.length).toBe(lotsBefore)
+    })
+
+    // 测试用例：验证提前取消质押按配置扣除罚金，并按去向转入国库或销毁
+    it('should charge a penalty on unstake_early and route it to the configured destination', async () => {
+      // 使用独立池子，锁定期 1000 秒
+      const EARLY_POOL_ID = new BN(8)
+      const earlyPool = derivePoolPdas(EARLY_POOL_ID, user.publicKey)
+
+      await program.methods
+        .initialize(EARLY_POOL_ID, new BN(1000), 0)
+        .accounts({
+          pool: earlyPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: earlyPool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: earlyPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .stake(new BN(100), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: earlyPool.poolPda,
+          userStakeInfo: earlyPool.userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: earlyPool.stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+
+      const earlyAccounts = {
+        user: user.publicKey,
+        pool: earlyPool.poolPda,
+        userStakeInfo: earlyPool.userStakeInfoPda,
+        userStakingWallet: userStakingWallet,
+        stakingVault: earlyPool.stakingVaultPda,
+        stakingMint: stakingMint,
+        treasury: adminStakingWallet,
+        rewardVault: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+
+      // 未配置罚金时不允许提前退出
+      await expect(
+        program.methods.unstakeEarly(new BN(40)).accounts(earlyAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/EarlyUnstakeDisabled/)
+
+      // 配置 50% 固定罚金并转入国库
+      await program.methods
+        .updateEarlyUnstakePenalty(5000, false)
+        .accounts({ pool: earlyPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .updatePenaltyDestination({ treasury: {} }, adminStakingWallet)
+        .accounts({ pool: earlyPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      const userBefore = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      const treasuryBefore = await getAccount(connection, adminStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      await program.methods.unstakeEarly(new BN(40)).accounts(earlyAccounts).signers([user]).rpc()
+      const userAfter = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      const treasuryAfter = await getAccount(connection, adminStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(userAfter.amount - userBefore.amount)).toBe(20)
+      expect(Number(treasuryAfter.amount - treasuryBefore.amount)).toBe(20)
+
+      // 改为销毁罚金，质押代币供应量相应减少
+      await program.methods
+        .updatePenaltyDestination({ burn: {} }, PublicKey.default)
+        .accounts({ pool: earlyPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      const supplyBefore = (await getMint(connection, stakingMint, undefined, TOKEN_2022_PROGRAM_ID)).supply
+      await program.methods.unstakeEarly(new BN(60)).accounts(earlyAccounts).signers([user]).rpc()
+      const supplyAfter = (await getMint(connection, stakingMint, undefined, TOKEN_2022_PROGRAM_ID)).supply
+      expect(Number(supplyBefore - supplyAfter)).toBe(30)
+
+      const poolAccount = await program.account.pool.fetch(earlyPool.poolPda)
+      expect(poolAccount.penaltiesToTreasury.toNumber()).toBe(20)
+      expect(poolAccount.penaltiesBurned.toNumber()).toBe(30)
+      expect(poolAccount.totalStaked.toNumber()).toBe(0)
+    })
+
+    // 测试用例：验证质押代币可以是经典 SPL Token，奖励代币使用 Token-2022，且 Mint 必须属于传入的 Token 程序
+    it('should support a classic SPL Token staking mint with a Token-2022 reward mint', async () => {
+      // 创建经典 SPL Token 质押代币及用户账户
+      const classicMint = await createMint(
+        connection,
+        admin,
+        admin.publicKey,
+        null,
+        6,
+        undefined,
+        undefined,
+        TOKEN_PROGRAM_ID,
+      )
+      const userClassicWallet = await createAccount(
+        connection,
+        user,
+        classicMint,
+        user.publicKey,
+        undefined,
+        undefined,
+        TOKEN_PROGRAM_ID,
+      )
+      await mintTo(connection, admin, classicMint, userClassicWallet, admin, 100, [], undefined, TOKEN_PROGRAM_ID)
+
+      const CLASSIC_POOL_ID = new BN(1)
+      const [classicPool] = PublicKey.findProgramAddressSync(
+        [Buffer.from('pool'), classicMint.toBuffer(), CLASSIC_POOL_ID.toArrayLike(Buffer, 'le', 8)],
+        program.programId,
+      )
+      const [classicStakingVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('staking_vault'), classicPool.toBuffer()],
+        program.programId,
+      )
+      const [classicRewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), classicPool.toBuffer(), rewardMint.toBuffer()],
+        program.programId,
+      )
+      const [classicStakeInfo] = PublicKey.findProgramAddressSync(
+        [Buffer.from('stake_info'), classicPool.toBuffer(), user.publicKey.toBuffer()],
+        program.programId,
+      )
+      const initAccounts = {
+        pool: classicPool,
+        admin: admin.publicKey,
+        stakingMint: classicMint,
+        stakingVault: classicStakingVault,
+        rewardMint: rewardMint,
+        rewardVault: classicRewardVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      }
+
+      // 奖励代币 Mint 不属于传入的奖励 Token 程序时应被拒绝
+      await expect(
+        program.methods
+          .initialize(CLASSIC_POOL_ID, new BN(0), 0)
+          .accounts({ ...initAccounts, rewardTokenProgram: TOKEN_PROGRAM_ID })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintMintTokenProgram/)
+
+      await program.methods.initialize(CLASSIC_POOL_ID, new BN(0), 0).accounts(initAccounts).signers([admin]).rpc()
+
+      // 使用经典 SPL Token 程序质押和取消质押
+      await program.methods
+        .stake(new BN(50), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: classicPool,
+          userStakeInfo: classicStakeInfo,
+          userStakingWallet: userClassicWallet,
+          stakingVault: classicStakingVault,
+          stakingMint: classicMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      let vault = await getAccount(connection, classicStakingVault, undefined, TOKEN_PROGRAM_ID)
+      expect(Number(vault.amount)).toBe(50)
+
+      await program.methods
+        .unstake(new BN(50))
+        .accounts({
+          user: user.publicKey,
+          pool: classicPool,
+          userStakeInfo: classicStakeInfo,
+          userStakingWallet: userClassicWallet,
+          stakingVault: classicStakingVault,
+          stakingMint: classicMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      vault = await getAccount(connection, classicStakingVault, undefined, TOKEN_PROGRAM_ID)
+      expect(Number(vault.amount)).toBe(0)
+    })
+
+    // 测试用例：验证带冻结权限的 Mint 默认被拒绝，管理员明确允许后才能创建池子，并记录在池子上
+    it('should reject dangerous mint extensions unless explicitly allowed', async () => {
+      // 创建带冻结权限的质押代币
+      const freezableMint = await createMint(
+        connection,
+        admin,
+        admin.publicKey,
+        admin.publicKey,
+        6,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      const POOL = new BN(1)
+      const [freezablePool] = PublicKey.findProgramAddressSync(
+        [Buffer.from('pool'), freezableMint.toBuffer(), POOL.toArrayLike(Buffer, 'le', 8)],
+        program.programId,
+      )
+      const [freezableStakingVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('staking_vault'), freezablePool.toBuffer()],
+        program.programId,
+      )
+      const [freezableRewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), freezablePool.toBuffer(), rewardMint.toBuffer()],
+        program.programId,
+      )
+      const initAccounts = {
+        pool: freezablePool,
+        admin: admin.publicKey,
+        stakingMint: freezableMint,
+        stakingVault: freezableStakingVault,
+        rewardMint: rewardMint,
+        rewardVault: freezableRewardVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      }
+
+      // 未允许冻结权限时初始化失败
+      await expect(
+        program.methods.initialize(POOL, new BN(0), 0).accounts(initAccounts).signers([admin]).rpc(),
+      ).rejects.toThrow(/UnsupportedMintExtension/)
+
+      // 明确允许冻结权限（MINT_EXT_FREEZE_AUTHORITY = 1）后初始化成功，扩展记录在池子上
+      const MINT_EXT_FREEZE_AUTHORITY = 1
+      await program.methods
+        .initialize(POOL, new BN(0), MINT_EXT_FREEZE_AUTHORITY)
+        .accounts(initAccounts)
+        .signers([admin])
+        .rpc()
+      const poolAccount = await program.account.pool.fetch(freezablePool)
+      expect(poolAccount.stakingMintExtensions).toBe(MINT_EXT_FREEZE_AUTHORITY)
+      expect(poolAccount.rewardStreams[0].mintExtensions).toBe(0)
+
+      // DefaultAccountState 扩展只有默认状态为冻结时才记为 MINT_EXT_DEFAULT_FROZEN
+      const defaultStateMint = async (state: AccountState) => {
+        const mintKeypair = Keypair.generate()
+        const mintLen = getMintLen([ExtensionType.DefaultAccountState])
+        await sendAndConfirmTransaction(
+          connection,
+          new Transaction().add(
+            SystemProgram.createAccount({
+              fromPubkey: admin.publicKey,
+              newAccountPubkey: mintKeypair.publicKey,
+              space: mintLen,
+              lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+              programId: TOKEN_2022_PROGRAM_ID,
+            }),
+            createInitializeDefaultAccountStateInstruction(mintKeypair.publicKey, state, TOKEN_2022_PROGRAM_ID),
+            createInitializeMintInstruction(
+              mintKeypair.publicKey,
+              6,
+              admin.publicKey,
+              admin.publicKey,
+              TOKEN_2022_PROGRAM_ID,
+            ),
+          ),
+          [admin, mintKeypair],
+        )
+        const [pool] = PublicKey.findProgramAddressSync(
+          [Buffer.from('pool'), mintKeypair.publicKey.toBuffer(), POOL.toArrayLike(Buffer, 'le', 8)],
+          program.programId,
+        )
+        const [stakingVault] = PublicKey.findProgramAddressSync(
+          [Buffer.from('staking_vault'), pool.toBuffer()],
+          program.programId,
+        )
+        const [rewardVault] = PublicKey.findProgramAddressSync(
+          [Buffer.from('reward_vault'), pool.toBuffer(), rewardMint.toBuffer()],
+          program.programId,
+        )
+        return {
+          ...initAccounts,
+          pool,
+          stakingMint: mintKeypair.publicKey,
+          stakingVault,
+          rewardVault,
+        }
+      }
+
+      // 默认状态为 Initialized 的 Mint 只需允许冻结权限
+      const initializedAccounts = await defaultStateMint(AccountState.Initialized)
+      await program.methods
+        .initialize(POOL, new BN(0), MINT_EXT_FREEZE_AUTHORITY)
+        .accounts(initializedAccounts)
+        .signers([admin])
+        .rpc()
+      const initializedPool = await program.account.pool.fetch(initializedAccounts.pool)
+      expect(initializedPool.stakingMintExtensions).toBe(MINT_EXT_FREEZE_AUTHORITY)
+
+      // 默认冻结的 Mint 仍需明确允许（MINT_EXT_DEFAULT_FROZEN = 32）
+      const frozenAccounts = await defaultStateMint(AccountState.Frozen)
+      await expect(
+        program.methods
+          .initialize(POOL, new BN(0), MINT_EXT_FREEZE_AUTHORITY)
+          .accounts(frozenAccounts)
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/UnsupportedMintExtension/)
+    })
+
+    // 测试用例：验证带转账手续费的质押代币只按金库实际收到的金额记账，最后一个取消质押的用户仍可全额提取
+    it('should credit only the net amount for transfer-fee staking mints', async () => {
+      // 创建 1% 转账手续费的 Token-2022 质押代币
+      const feeMintKeypair = Keypair.generate()
+      const feeMint = feeMintKeypair.publicKey
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig])
+      const lamports = await connection.getMinimumBalanceForRentExemption(mintLen)
+      await sendAndConfirmTransaction(
+        connection,
+        new Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: admin.publicKey,
+            newAccountPubkey: feeMint,
+            space: mintLen,
+            lamports,
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeTransferFeeConfigInstruction(
+            feeMint,
+            admin.publicKey,
+            admin.publicKey,
+            100,
+            BigInt(1_000_000),
+            TOKEN_2022_PROGRAM_ID,
+          ),
+          createInitializeMintInstruction(feeMint, 6, admin.publicKey, null, TOKEN_2022_PROGRAM_ID),
+        ),
+        [admin, feeMintKeypair],
+      )
+      const userFeeWallet = await createAccount(
+        connection,
+        user,
+        feeMint,
+        user.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+      await mintTo(connection, admin, feeMint, userFeeWallet, admin, 1000, [], undefined, TOKEN_2022_PROGRAM_ID)
+
+      const FEE_POOL_ID = new BN(1)
+      const [feePool] = PublicKey.findProgramAddressSync(
+        [Buffer.from('pool'), feeMint.toBuffer(), FEE_POOL_ID.toArrayLike(Buffer, 'le', 8)],
+        program.programId,
+      )
+      const [feeStakingVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('staking_vault'), feePool.toBuffer()],
+        program.programId,
+      )
+      const [feeRewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), feePool.toBuffer(), rewardMint.toBuffer()],
+        program.programId,
+      )
+      const [feeStakeInfo] = PublicKey.findProgramAddressSync(
+        [Buffer.from('stake_info'), feePool.toBuffer(), user.publicKey.toBuffer()],
+        program.programId,
+      )
+      await program.methods
+        .initialize(FEE_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: feePool,
+          admin: admin.publicKey,
+          stakingMint: feeMint,
+          stakingVault: feeStakingVault,
+          rewardMint: rewardMint,
+          rewardVault: feeRewardVault,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+
+      // 质押 1000，金库收到 990，只记入 990
+      await program.methods
+        .stake(new BN(1000), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: feePool,
+          userStakeInfo: feeStakeInfo,
+          userStakingWallet: userFeeWallet,
+          stakingVault: feeStakingVault,
+          stakingMint: feeMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      const info = await program.account.userStakeInfo.fetch(feeStakeInfo)
+      const poolAccount = await program.account.pool.fetch(feePool)
+      const vault = await getAccount(connection, feeStakingVault, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(info.stakeAmount.toNumber()).toBe(990)
+      expect(poolAccount.totalStaked.toNumber()).toBe(990)
+      expect(Number(vault.amount)).toBe(990)
+
+      // 全额取消质押不会因金库余额不足而失败
+      await program.methods
+        .unstake(info.stakeAmount)
+        .accounts({
+          user: user.publicKey,
+          pool: feePool,
+          userStakeInfo: feeStakeInfo,
+          userStakingWallet: userFeeWallet,
+          stakingVault: feeStakingVault,
+          stakingMint: feeMint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+      const vaultAfter = await getAccount(connection, feeStakingVault, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(vaultAfter.amount)).toBe(0)
+    })
+
+    // 测试用例：验证质押代币和奖励代币都带转账钩子时，质押、取消质押、领取和注资都会转发额外账户并触发钩子
+    it('should forward transfer hook accounts for hooked staking and reward mints', async () => {
+      // 创建启用 TransferHook 扩展的 Token-2022 Mint，并初始化其 ExtraAccountMetaList 和计数器
+      const createHookedMint = async () => {
+        const mintKeypair = Keypair.generate()
+        const mintLen = getMintLen([ExtensionType.TransferHook])
+        await sendAndConfirmTransaction(
+          connection,
+          new Transaction().add(
+            SystemProgram.createAccount({
+              fromPubkey: admin.publicKey,
+              newAccountPubkey: mintKeypair.publicKey,
+              space: mintLen,
+              lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+              programId: TOKEN_2022_PROGRAM_ID,
+            }),
+            createInitializeTransferHookInstruction(
+              mintKeypair.publicKey,
+              admin.publicKey,
+              hookProgram.programId,
+              TOKEN_2022_PROGRAM_ID,
+            ),
+            createInitializeMintInstruction(mintKeypair.publicKey, 6, admin.publicKey, null, TOKEN_2022_PROGRAM_ID),
+          ),
+          [admin, mintKeypair],
+        )
+        const [extraAccountMetaList] = PublicKey.findProgramAddressSync(
+          [Buffer.from('extra-account-metas'), mintKeypair.publicKey.toBuffer()],
+          hookProgram.programId,
+        )
+        const [counter] = PublicKey.findProgramAddressSync(
+          [Buffer.from('counter'), mintKeypair.publicKey.toBuffer()],
+          hookProgram.programId,
+        )
+        await hookProgram.methods
+          .initializeExtraAccountMetaList()
+          .accounts({
+            payer: admin.publicKey,
+            extraAccountMetaList,
+            mint: mintKeypair.publicKey,
+            counter,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc()
+        return { mint: mintKeypair.publicKey, counter }
+      }
+      // 按 Mint 的 ExtraAccountMetaList 解析一笔转账需要附加的账户（额外账户、钩子程序和 ExtraAccountMetaList）
+      const hookAccounts = async (
+        mint: PublicKey,
+        source: PublicKey,
+        destination: PublicKey,
+        authority: PublicKey,
+        amount: number,
+      ): Promise<AccountMeta[]> => {
+        const transfer = new TransactionInstruction({
+          programId: TOKEN_2022_PROGRAM_ID,
+          keys: [source, mint, destination, authority].map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: false,
+          })),
+        })
+        await addExtraAccountMetasForExecute(
+          connection,
+          transfer,
+          hookProgram.programId,
+          source,
+          mint,
+          destination,
+          authority,
+          amount,
+          'confirmed',
+        )
+        return transfer.keys.slice(4)
+      }
+
+      const staking = await createHookedMint()
+      const reward = await createHookedMint()
+      const [userHookStakingWallet, adminHookRewardWallet, userHookRewardWallet] = await Promise.all([
+        createAccount(connection, user, staking.mint, user.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID),
+        createAccount(connection, admin, reward.mint, admin.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID),
+        createAccount(connection, user, reward.mint, user.publicKey, undefined, undefined, TOKEN_2022_PROGRAM_ID),
+      ])
+      // 铸币不经过转账钩子
+      await Promise.all([
+        mintTo(
+          connection,
+          admin,
+          staking.mint,
+          userHookStakingWallet,
+          admin,
+          1000,
+          [],
+          undefined,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        mintTo(
+          connection,
+          admin,
+          reward.mint,
+          adminHookRewardWallet,
+          admin,
+          2000,
+          [],
+          undefined,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+      ])
+
+      const HOOK_POOL_ID = new BN(1)
+      const [hookPool] = PublicKey.findProgramAddressSync(
+        [Buffer.from('pool'), staking.mint.toBuffer(), HOOK_POOL_ID.toArrayLike(Buffer, 'le', 8)],
+        program.programId,
+      )
+      const [hookStakingVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('staking_vault'), hookPool.toBuffer()],
+        program.programId,
+      )
+      const [hookRewardVault] = PublicKey.findProgramAddressSync(
+        [Buffer.from('reward_vault'), hookPool.toBuffer(), reward.mint.toBuffer()],
+        program.programId,
+      )
+      const [hookStakeInfo] = PublicKey.findProgramAddressSync(
+        [Buffer.from('stake_info'), hookPool.toBuffer(), user.publicKey.toBuffer()],
+        program.programId,
+      )
+      const initAccounts = {
+        pool: hookPool,
+        admin: admin.publicKey,
+        stakingMint: staking.mint,
+        stakingVault: hookStakingVault,
+        rewardMint: reward.mint,
+        rewardVault: hookRewardVault,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      }
+
+      // 转账钩子属于危险扩展，需明确允许（MINT_EXT_TRANSFER_HOOK = 512）
+      await expect(
+        program.methods.initialize(HOOK_POOL_ID, new BN(0), 0).accounts(initAccounts).signers([admin]).rpc(),
+      ).rejects.toThrow(/UnsupportedMintExtension/)
+      const MINT_EXT_TRANSFER_HOOK = 512
+      await program.methods
+        .initialize(HOOK_POOL_ID, new BN(0), MINT_EXT_TRANSFER_HOOK)
+        .accounts(initAccounts)
+        .signers([admin])
+        .rpc()
+      const poolAccount = await program.account.pool.fetch(hookPool)
+      expect(poolAccount.stakingMintExtensions).toBe(MINT_EXT_TRANSFER_HOOK)
+      expect(poolAccount.rewardStreams[0].mintExtensions).toBe(MINT_EXT_TRANSFER_HOOK)
+
+      const fundAccounts = {
+        pool: hookPool,
+        funder: admin.publicKey,
+        funderWallet: adminHookRewardWallet,
+        rewardVault: hookRewardVault,
+        rewardMint: reward.mint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      // 注资：奖励代币从注资者钱包转入奖励金库
+      await program.methods
+        .fundRewards(0, new BN(500))
+        .accounts(fundAccounts)
+        .remainingAccounts(
+          await hookAccounts(reward.mint, adminHookRewardWallet, hookRewardVault, admin.publicKey, 500),
+        )
+        .signers([admin])
+        .rpc()
+      // 开启奖励周期，同样经过转账钩子
+      await program.methods
+        .notifyRewardAmount(0, new BN(1000), new BN(100))
+        .accounts(fundAccounts)
+        .remainingAccounts(
+          await hookAccounts(reward.mint, adminHookRewardWallet, hookRewardVault, admin.publicKey, 1000),
+        )
+        .signers([admin])
+        .rpc()
+
+      const stakeAccounts = {
+        user: user.publicKey,
+        pool: hookPool,
+        userStakeInfo: hookStakeInfo,
+        userStakingWallet: userHookStakingWallet,
+        stakingVault: hookStakingVault,
+        stakingMint: staking.mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      // 未附加钩子账户时 Token-2022 无法调用钩子，质押失败
+      await expect(
+        program.methods.stake(new BN(1000), new BN(0)).accounts(stakeAccounts).signers([user]).rpc(),
+      ).rejects.toThrow()
+      // 质押：质押代币从用户钱包转入质押金库
+      await program.methods
+        .stake(new BN(1000), new BN(0))
+        .accounts(stakeAccounts)
+        .remainingAccounts(
+          await hookAccounts(staking.mint, userHookStakingWallet, hookStakingVault, user.publicKey, 1000),
+        )
+        .signers([user])
+        .rpc()
+      const vault = await getAccount(connection, hookStakingVault, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(vault.amount)).toBe(1000)
+
+      // 领取：奖励代币由池子 PDA 从奖励金库转给用户
+      await sleep(2000)
+      await program.methods
+        .claimRewards(0)
+        .accounts({
+          user: user.publicKey,
+          pool: hookPool,
+          userStakeInfo: hookStakeInfo,
+          userRewardWallet: userHookRewardWallet,
+          rewardVault: hookRewardVault,
+          rewardMint: reward.mint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(await hookAccounts(reward.mint, hookRewardVault, userHookRewardWallet, hookPool, 0))
+        .signers([user])
+        .rpc()
+      const claimed = await getAccount(connection, userHookRewardWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(claimed.amount)).toBeGreaterThan(0)
+
+      // 取消质押：质押代币由池子 PDA 从质押金库转回用户
+      await program.methods
+        .unstake(new BN(1000))
+        .accounts({
+          user: user.publicKey,
+          pool: hookPool,
+          userStakeInfo: hookStakeInfo,
+          userStakingWallet: userHookStakingWallet,
+          stakingVault: hookStakingVault,
+          stakingMint: staking.mint,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          await hookAccounts(staking.mint, hookStakingVault, userHookStakingWallet, hookPool, 1000),
+        )
+        .signers([user])
+        .rpc()
+      const unstaked = await getAccount(connection, userHookStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(unstaked.amount)).toBe(1000)
+
+      // 每笔转账都触发了对应 Mint 的钩子：质押代币 2 次（质押、取消质押），奖励代币 3 次（两次注资、领取）
+      const stakingCounter = await hookProgram.account.counter.fetch(staking.counter)
+      const rewardCounter = await hookProgram.account.counter.fetch(reward.counter)
+      expect(stakingCounter.transfers.toNumber()).toBe(2)
+      expect(stakingCounter.lastAmount.toNumber()).toBe(1000)
+      expect(rewardCounter.transfers.toNumber()).toBe(3)
+      expect(rewardCounter.lastAmount.toNumber()).toBe(Number(claimed.amount))
+    })
+  })
+
+  // --- 测试套件：管理员操作 ---
+  describe('Admin Actions', () => {
+    // 测试用例：验证管理员可以更改奖励速率
+    it('should allow admin to change the reward rate', async () => {
+      // 定义新的奖励速率（50），金库余额足以覆盖周期剩余时间的发放
+      const NEW_RATE = new BN(50)
+      // 调用程序的 updateRewardRate 方法
+      await program.methods
+        .updateRewardRate(0, NEW_RATE)
+        .accounts({
+          // 质押池账户
+          pool: poolPda,
+          // 管理员公钥
+          rateManager: admin.publicKey,
+        })
+        // 管理员签名交易
+        .signers([admin])
+        // 发送交易并等待确认
+        .rpc()
+      // 获取质押池账户数据
+      const poolAccount = await program.account.pool.fetch(poolPda)
+      // 验证奖励速率已更新
+      expect(poolAccount.rewardStreams[0].rewardRate.toString()).toBe(NEW_RATE.toString())
+    })
+
+    // 测试用例：验证池子跟踪奖励负债，且拒绝金库无法承受的奖励率
+    it('should track reward liabilities and reject unsustainable reward rates', async () => {
+      // 验证账本余额与负债：负债不超过账本余额，账本余额不超过金库实际余额
+      const poolAccount = await program.account.pool.fetch(poolPda)
+      const stream = poolAccount.rewardStreams[0]
+      const vault = await getAccount(connection, rewardVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(stream.totalRewardsOwed.toNumber()).toBeLessThanOrEqual(stream.vaultBalance.toNumber())
+      expect(stream.vaultBalance.toNumber()).toBeLessThanOrEqual(Number(vault.amount))
+
+      // 远超金库余额的奖励率应被拒绝
+      await expect(
+        program.methods
+          .updateRewardRate(0, FUND_AMOUNT)
+          .accounts({ pool: poolPda, rateManager: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/RewardRateUnsustainable/)
+
+      // 紧急提取超过未占用余额的奖励应被拒绝
+      await expect(
+        program.methods
+          .emergencyWithdrawRewardTokens(0, stream.vaultBalance)
+          .accounts({
+            pool: poolPda,
+            guardian: admin.publicKey,
+            rewardMint: rewardMint,
+            rewardVault: rewardVaultPda,
+            destinationWallet: adminRewardWallet,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/WithdrawExceedsUnreservedRewards/)
+
+      // 偿付能力检查期限必须大于 0
+      await expect(
+        program.methods
+          .updateSolvencyHorizon(new BN(0))
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/InvalidSolvencyHorizon/)
+    })
+
+    // 测试用例：验证管理员设置的参数受边界约束
+    it('should enforce parameter bounds on admin setters', async () => {
+      const poolAccount = await program.account.pool.fetch(poolPda)
+      const bounds = poolAccount.bounds
+
+      // 锁定期不能为负，也不能超过上限
+      for (const duration of [new BN(-1), bounds.maxLockupDuration.addn(1)]) {
+        await expect(
+          program.methods
+            .updateLockupDuration(duration)
+            .accounts({ pool: poolPda, rateManager: admin.publicKey })
+            .signers([admin])
+            .rpc(),
+        ).rejects.toThrow(/LockupDurationOutOfBounds/)
+      }
+
+      // 奖励率不能超过上限
+      await expect(
+        program.methods
+          .updateRewardRate(0, bounds.maxRewardRate.addn(1))
+          .accounts({ pool: poolPda, rateManager: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/RewardRateAboveMax/)
+
+      // 冷却期不能超过上限
+      await expect(
+        program.methods
+          .updateCooldownDuration(bounds.maxCooldownDuration.addn(1))
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/CooldownDurationOutOfBounds/)
+
+      // 偿付能力检查期限、奖励周期和最长暂停时间同样按边界校验
+      await expect(
+        program.methods
+          .updateSolvencyHorizon(bounds.maxSolvencyHorizon.addn(1))
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/SolvencyHorizonOutOfBounds/)
+      await expect(
+        program.methods
+          .notifyRewardAmount(0, new BN(1000), bounds.maxRewardDuration.addn(1))
+          .accounts({
+            pool: poolPda,
+            funder: admin.publicKey,
+            funderWallet: adminRewardWallet,
+            rewardVault: rewardVaultPda,
+            rewardMint: rewardMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/RewardDurationOutOfBounds/)
+      const ONE_YEAR = 365 * 24 * 60 * 60
+      await expect(
+        program.methods
+          .updateMaxPauseDuration(new BN(ONE_YEAR + 1))
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/PauseDurationOutOfBounds/)
+
+      // 设置最长暂停时间上限后，不能再关闭暂停时间限制
+      await program.methods
+        .updateMaxPauseDuration(new BN(100))
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .updateParameterBounds({ ...bounds, maxPauseDuration: new BN(100) })
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await expect(
+        program.methods
+          .updateMaxPauseDuration(new BN(0))
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/PauseDurationOutOfBounds/)
+      await program.methods
+        .updateParameterBounds(bounds)
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .updateMaxPauseDuration(new BN(0))
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 下限大于上限的边界无效
+      await expect(
+        program.methods
+          .updateParameterBounds({ ...bounds, minLockupDuration: new BN(10), maxLockupDuration: new BN(5) })
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/InvalidParameterBounds/)
+
+      // 新边界必须容纳当前锁定期
+      await expect(
+        program.methods
+          .updateParameterBounds({ ...bounds, minLockupDuration: poolAccount.lockupDuration.addn(1) })
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/LockupDurationOutOfBounds/)
+
+      // 收紧奖励率上限后，超过新上限的奖励率被拒绝
+      await program.methods
+        .updateParameterBounds({ ...bounds, maxRewardRate: new BN(1000) })
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await expect(
+        program.methods
+          .updateRewardRate(0, new BN(1001))
+          .accounts({ pool: poolPda, rateManager: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/RewardRateAboveMax/)
+      await program.methods
+        .updateParameterBounds(bounds)
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+    })
+
+    // 测试用例：验证管理员可以暂停和取消暂停程序
+    it('should allow admin to pause and unpause the program', async () => {
+      // 调用程序的 pause 方法，暂停程序
+      await program.methods
+        .pause()
+        .accounts({ pool: poolPda, pauser: admin.publicKey })
+        // 管理员签名交易
+        .signers([admin])
+        // 发送交易并等待确认
+        .rpc()
+      // 获取质押池账户数据
+      let poolAccount = await program.account.pool.fetch(poolPda)
+      // 验证程序已暂停
+      expect(poolAccount.isPaused).toBe(true)
+
+      // 验证暂停后用户无法质押
+      await expect(
+        program.methods
+          .stake(new BN(100), new BN(0))
+          .accounts({
+            // 用户公钥
+            user: user.publicKey,
+            // 质押池账户
+            pool: poolPda,
+            // 用户质押信息账户
+            userStakeInfo: userStakeInfoPda,
+            // 用户的质押代币账户
+            userStakingWallet: userStakingWallet,
+            // 质押金库账户
+            stakingVault: stakingVaultPda,
+            // 质押代币的 Mint
+            stakingMint: stakingMint,
+            // 系统程序
+            systemProgram: SystemProgram.programId,
+            // SPL Token 程序
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          // 用户签名交易
+          .signers([user])
+          // 发送交易
+          .rpc(),
+      ).rejects.toThrow(/ProgramPaused/) // 期望抛出 ProgramPaused 错误
+
+      // 调用程序的 unpause 方法，取消暂停
+      await program.methods
+        .unpause()
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        // 管理员签名交易
+        .signers([admin])
+        // 发送交易并等待确认
+        .rpc()
+      // 获取质押池账户数据
+      poolAccount = await program.account.pool.fetch(poolPda)
+      // 验证程序已取消暂停
+      expect(poolAccount.isPaused).toBe(false)
+    })
+
+    // 测试用例：验证开启时间锁后参数变更需排队，到期后任何人都可以执行，到期前管理员可以撤销
+    it('should queue, execute and cancel timelocked parameter changes', async () => {
+      // 使用独立池子，避免影响其他测试
+      const TIMELOCK_POOL_ID = new BN(9)
+      const timelockPool = derivePoolPdas(TIMELOCK_POOL_ID, user.publicKey)
+      const TIMELOCK_DELAY = 2
+
+      await program.methods
+        .initialize(TIMELOCK_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: timelockPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: timelockPool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: timelockPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .updateTimelockDelay(new BN(TIMELOCK_DELAY))
+        .accounts({ pool: timelockPool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 开启时间锁后不能直接修改锁定期
+      await expect(
+        program.methods
+          .updateLockupDuration(new BN(100))
+          .accounts({ pool: timelockPool.poolPda, rateManager: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/TimelockRequired/)
+
+      // 改变奖励率、退出条件、暂停上限、参数边界、罚金去向或复利方式的其他 setter 同样只能排队
+      const adminAccounts = { pool: timelockPool.poolPda, admin: admin.publicKey }
+      const timelockBounds = {
+        ...(await program.account.pool.fetch(timelockPool.poolPda)).bounds,
+        maxPauseDuration: new BN(1000),
+      }
+      const fundAccounts = {
+        pool: timelockPool.poolPda,
+        funder: admin.publicKey,
+        funderWallet: adminRewardWallet,
+        rewardVault: timelockPool.rewardVaultPda,
+        rewardMint: rewardMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      for (const direct of [
+        program.methods.notifyRewardAmount(0, new BN(1000), new BN(100)).accounts(fundAccounts),
+        program.methods.retireRewardStream(0).accounts(adminAccounts),
+        program.methods.updateCooldownDuration(new BN(5)).accounts(adminAccounts),
+        program.methods.updateMaxBoost(20_000).accounts(adminAccounts),
+        program.methods.updateEarlyUnstakePenalty(500, false).accounts(adminAccounts),
+        program.methods.updateMaxPauseDuration(new BN(60)).accounts(adminAccounts),
+        program.methods.updateParameterBounds(timelockBounds).accounts(adminAccounts),
+        program.methods.updatePenaltyDestination({ treasury: {} }, adminStakingWallet).accounts(adminAccounts),
+        program.methods.setAutoCompound(false).accounts(adminAccounts),
+      ]) {
+        await expect(direct.signers([admin]).rpc()).rejects.toThrow(/TimelockRequired/)
+      }
+      // 开启时间锁后注资不改变奖励率，新周期通过排队的 notifyRewardAmount 从已注资余额开启
+      await program.methods
+        .fundRewards(0, new BN(1000))
+        .accounts(fundAccounts)
+        .signers([admin])
+        .rpc()
+
+      const queuedChangePda = (changeId: number) =>
+        PublicKey.findProgramAddressSync(
+          [
+            Buffer.from('queued_change'),
+            timelockPool.poolPda.toBuffer(),
+            new BN(changeId).toArrayLike(Buffer, 'le', 8),
+          ],
+          program.programId,
+        )[0]
+      const now = async () => (await connection.getBlockTime(await connection.getSlot()))!
+
+      // eta 早于 timelock_delay 时拒绝排队
+      await expect(
+        program.methods
+          .scheduleChange({ updateLockupDuration: { newDuration: new BN(100) } }, new BN(await now()))
+          .accounts({ pool: timelockPool.poolPda, queuedChange: queuedChangePda(0), admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/EtaTooEarly/)
+
+      // 排队修改锁定期（变更 0）和提名新管理员（变更 1）
+      const eta = new BN((await now()) + TIMELOCK_DELAY + 1)
+      await program.methods
+        .scheduleChange({ updateLockupDuration: { newDuration: new BN(100) } }, eta)
+        .accounts({ pool: timelockPool.poolPda, queuedChange: queuedChangePda(0), admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await program.methods
+        .scheduleChange({ proposeAdmin: { newAdmin: newAdmin.publicKey } }, eta)
+        .accounts({ pool: timelockPool.poolPda, queuedChange: queuedChangePda(1), admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      const queued = await program.account.queuedChange.fetch(queuedChangePda(0))
+      expect(queued.eta.toNumber()).toBe(eta.toNumber())
+
+      // 排队开启奖励周期（变更 2）、修改冷却期（变更 3）、加速倍数（变更 4）、罚金（变更 5）、
+      // 参数边界（变更 6）、最长暂停时间（变更 7）、罚金去向（变更 8）和自动复利（变更 9）
+      const queuedActions = [
+        { notifyRewardAmount: { rewardIndex: 0, amount: new BN(1000), duration: new BN(100) } },
+        { updateCooldownDuration: { newDuration: new BN(5) } },
+        { updateMaxBoost: { maxBoostBps: 20_000 } },
+        { updateEarlyUnstakePenalty: { penaltyBps: 500, scaleWithLock: true } },
+        { updateParameterBounds: { bounds: timelockBounds } },
+        { updateMaxPauseDuration: { newDuration: new BN(60) } },
+        { updatePenaltyDestination: { destination: { treasury: {} }, treasury: adminStakingWallet } },
+        { setAutoCompound: { enabled: false } },
+      ]
+      for (const [index, action] of queuedActions.entries()) {
+        await program.methods
+          .scheduleChange(action, eta)
+          .accounts({
+            pool: timelockPool.poolPda,
+            queuedChange: queuedChangePda(index + 2),
+            admin: admin.publicKey,
+          })
+          .signers([admin])
+          .rpc()
+      }
+
+      // 到期前无法执行
+      await expect(
+        program.methods
+          .executeChange()
+          .accounts({
+            pool: timelockPool.poolPda,
+            queuedChange: queuedChangePda(0),
+            rentPayer: admin.publicKey,
+          })
+          .rpc(),
+      ).rejects.toThrow(/TimelockNotReady/)
+
+      // 到期前管理员撤销提名新管理员的变更
+      await program.methods
+        .cancelChange()
+        .accounts({
+          pool: timelockPool.poolPda,
+          queuedChange: queuedChangePda(1),
+          admin: admin.publicKey,
+          rentPayer: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc()
+      expect(await connection.getAccountInfo(queuedChangePda(1))).toBeNull()
+
+      // 到期后任何人（此处为测试钱包）都可以执行，执行后变更账户被关闭
+      await sleep((TIMELOCK_DELAY + 2) * 1000)
+      await program.methods
+        .executeChange()
+        .accounts({ pool: timelockPool.poolPda, queuedChange: queuedChangePda(0), rentPayer: admin.publicKey })
+        .rpc()
+      expect(await connection.getAccountInfo(queuedChangePda(0))).toBeNull()
+      for (const changeId of [2, 3, 4, 5, 6, 7, 8, 9]) {
+        await program.methods
+          .executeChange()
+          .accounts({ pool: timelockPool.poolPda, queuedChange: queuedChangePda(changeId), rentPayer: admin.publicKey })
+          .rpc()
+      }
+
+      const poolAccount = await program.account.pool.fetch(timelockPool.poolPda)
+      expect(poolAccount.lockupDuration.toNumber()).toBe(100)
+      expect(poolAccount.pendingAdmin.toBase58()).toBe(PublicKey.default.toBase58())
+      expect(poolAccount.rewardStreams[0].rewardRate.toNumber()).toBe(10)
+      expect(poolAccount.cooldownDuration.toNumber()).toBe(5)
+      expect(poolAccount.maxBoostBps).toBe(20_000)
+      expect(poolAccount.earlyUnstakePenaltyBps).toBe(500)
+      expect(poolAccount.penaltyScalesWithLock).toBe(true)
+      expect(poolAccount.bounds.maxPauseDuration.toNumber()).toBe(1000)
+      expect(poolAccount.maxPauseDuration.toNumber()).toBe(60)
+      expect(poolAccount.penaltyDestination).toEqual({ treasury: {} })
+      expect(poolAccount.penaltyTreasury.toBase58()).toBe(adminStakingWallet.toBase58())
+      expect(poolAccount.autoCompound).toBe(false)
+      expect(poolAccount.nextChangeId.toNumber()).toBe(10)
+    })
+
+    // 测试用例：验证紧急提取质押代币后池子关停，用户按比例取回剩余本金
+    it('should shut down the pool on emergency withdrawal and let users exit pro-rata', async () => {
+      // 使用独立池子，管理员和用户各质押 100
+      const SHUTDOWN_POOL_ID = new BN(10)
+      const shutdownPool = derivePoolPdas(SHUTDOWN_POOL_ID, user.publicKey)
+      const adminStakeInfoPda = derivePoolPdas(SHUTDOWN_POOL_ID, admin.publicKey).userStakeInfoPda
+
+      await program.methods
+        .initialize(SHUTDOWN_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: shutdownPool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: shutdownPool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: shutdownPool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+      await mintTo(connection, admin, stakingMint, adminStakingWallet, admin, 100, [], undefined, TOKEN_2022_PROGRAM_ID)
+      await mintTo(connection, admin, stakingMint, userStakingWallet, admin, 100, [], undefined, TOKEN_2022_PROGRAM_ID)
+      for (const [staker, stakeInfo, wallet] of [
+        [admin, adminStakeInfoPda, adminStakingWallet],
+        [user, shutdownPool.userStakeInfoPda, userStakingWallet],
+      ] as const) {
+        await program.methods
+          .stake(new BN(100), new BN(0))
+          .accounts({
+            user: staker.publicKey,
+            pool: shutdownPool.poolPda,
+            userStakeInfo: stakeInfo,
+            userStakingWallet: wallet,
+            stakingVault: shutdownPool.stakingVaultPda,
+            stakingMint: stakingMint,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([staker])
+          .rpc()
+      }
+
+      // 关停前无法紧急退出
+      const exitAccounts = {
+        user: user.publicKey,
+        pool: shutdownPool.poolPda,
+        userStakeInfo: shutdownPool.userStakeInfoPda,
+        userStakingWallet: userStakingWallet,
+        stakingVault: shutdownPool.stakingVaultPda,
+        stakingMint: stakingMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      await expect(program.methods.emergencyExit().accounts(exitAccounts).signers([user]).rpc()).rejects.toThrow(
+        /PoolNotShutdown/,
+      )
+
+      // 守护者提取一半质押代币，池子进入关停状态
+      await program.methods
+        .emergencyWithdrawStakedTokens(new BN(100))
+        .accounts({
+          pool: shutdownPool.poolPda,
+          guardian: admin.publicKey,
+          stakingVault: shutdownPool.stakingVaultPda,
+          stakingMint: stakingMint,
+          destinationWallet: adminStakingWallet,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc()
+      let poolAccount = await program.account.pool.fetch(shutdownPool.poolPda)
+      expect(poolAccount.isShutdown).toBe(true)
+      expect(poolAccount.shutdownClaims.toNumber()).toBe(200)
+      expect(poolAccount.shutdownBalance.toNumber()).toBe(100)
+
+      // 关停后不能再质押
+      await expect(
+        program.methods
+          .stake(new BN(10), new BN(0))
+          .accounts({
+            user: user.publicKey,
+            pool: shutdownPool.poolPda,
+            userStakeInfo: shutdownPool.userStakeInfoPda,
+            userStakingWallet: userStakingWallet,
+            stakingVault: shutdownPool.stakingVaultPda,
+            stakingMint: stakingMint,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+      ).rejects.toThrow(/PoolShutdown/)
+
+      // 用户按比例取回剩余本金的一半
+      const userBefore = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      await program.methods.emergencyExit().accounts(exitAccounts).signers([user]).rpc()
+      const userAfter = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(userAfter.amount - userBefore.amount)).toBe(50)
+
+      const info = await program.account.userStakeInfo.fetch(shutdownPool.userStakeInfoPda)
+      expect(info.stakeAmount.toNumber()).toBe(0)
+      poolAccount = await program.account.pool.fetch(shutdownPool.poolPda)
+      expect(poolAccount.totalStaked.toNumber()).toBe(100)
+      expect(poolAccount.shutdownClaims.toNumber()).toBe(100)
+      expect(poolAccount.shutdownBalance.toNumber()).toBe(50)
+
+      // 已退出的仓位不能重复退出
+      await expect(program.methods.emergencyExit().accounts(exitAccounts).signers([user]).rpc()).rejects.toThrow(
+        /InsufficientStakeAmount/,
+      )
+    })
+
+    // 测试用例：验证暂停期间用户可以紧急取回全部本金，且暂停超过最长时间后自动失效
+    it('should allow emergency_unstake while paused and expire long pauses', async () => {
+      // 使用独立池子，用户锁定 1 周质押 100
+      const PAUSE_POOL_ID = new BN(11)
+      const pausePool = derivePoolPdas(PAUSE_POOL_ID, user.publicKey)
+      const ONE_WEEK = new BN(7 * 24 * 60 * 60)
+
+      await program.methods
+        .initialize(PAUSE_POOL_ID, new BN(0), 0)
+        .accounts({
+          pool: pausePool.poolPda,
+          admin: admin.publicKey,
+          stakingMint: stakingMint,
+          stakingVault: pausePool.stakingVaultPda,
+          rewardMint: rewardMint,
+          rewardVault: pausePool.rewardVaultPda,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          rewardTokenProgram: TOKEN_2022_PROGRAM_ID,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([admin])
+        .rpc()
+      await mintTo(connection, admin, stakingMint, userStakingWallet, admin, 200, [], undefined, TOKEN_2022_PROGRAM_ID)
+      const stakeAccounts = {
+        user: user.publicKey,
+        pool: pausePool.poolPda,
+        userStakeInfo: pausePool.userStakeInfoPda,
+        userStakingWallet: userStakingWallet,
+        stakingVault: pausePool.stakingVaultPda,
+        stakingMint: stakingMint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      await program.methods.stake(new BN(100), ONE_WEEK).accounts(stakeAccounts).signers([user]).rpc()
+
+      const exitAccounts = {
+        user: user.publicKey,
+        pool: pausePool.poolPda,
+        userStakeInfo: pausePool.userStakeInfoPda,
+        userStakingWallet: userStakingWallet,
+        stakingVault: pausePool.stakingVaultPda,
+        stakingMint: stakingMint,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      // 未暂停时不能紧急取消质押
+      await expect(
+        program.methods.emergencyUnstake(true).accounts(exitAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/NotPaused/)
+
+      await program.methods
+        .pause()
+        .accounts({ pool: pausePool.poolPda, pauser: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 暂停期间无视锁定期取回全部本金
+      await sleep(1500)
+      const poolBefore = await program.account.pool.fetch(pausePool.poolPda)
+      const userBefore = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      await program.methods.emergencyUnstake(true).accounts(exitAccounts).signers([user]).rpc()
+      const userAfter = await getAccount(connection, userStakingWallet, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(Number(userAfter.amount - userBefore.amount)).toBe(100)
+
+      // 放弃奖励时仍先结算全局奖励累加器，再移除用户的加权质押量
+      let poolAccount = await program.account.pool.fetch(pausePool.poolPda)
+      expect(poolAccount.lastUpdateTimestamp.toNumber()).toBeGreaterThan(poolBefore.lastUpdateTimestamp.toNumber())
+      expect(poolAccount.totalStaked.toNumber()).toBe(0)
+      expect(poolAccount.totalWeightedStake.toNumber()).toBe(0)
+      const info = await program.account.userStakeInfo.fetch(pausePool.userStakeInfoPda)
+      expect(info.stakeAmount.toNumber()).toBe(0)
+      expect(info.lots.length).toBe(0)
+
+      // 暂停期间不能正常质押；设置最长暂停时间后，超时的暂停自动失效
+      await expect(
+        program.methods.stake(new BN(50), new BN(0)).accounts(stakeAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/ProgramPaused/)
+      await program.methods
+        .updateMaxPauseDuration(new BN(1))
+        .accounts({ pool: pausePool.poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+      await sleep(3000)
+      await program.methods.stake(new BN(50), new BN(0)).accounts(stakeAccounts).signers([user]).rpc()
+
+      poolAccount = await program.account.pool.fetch(pausePool.poolPda)
+      expect(poolAccount.isPaused).toBe(true)
+      expect(poolAccount.totalStaked.toNumber()).toBe(50)
+
+      // 暂停标志仍在，但超时后 emergency_unstake 同样按未暂停处理
+      await expect(
+        program.methods.emergencyUnstake(false).accounts(exitAccounts).signers([user]).rpc(),
+      ).rejects.toThrow(/NotPaused/)
+    })
+
+    // 测试用例：验证管理员提名可以被撤销
+    it('should allow admin to cancel a pending admin transfer', async () => {
+      // 提名新管理员
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 撤销提名
+      await program.methods
+        .cancelAdminTransfer()
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc()
+
+      // 验证提名已清空，被撤销的地址无法再接受
+      const poolAccount = await program.account.pool.fetch(poolPda)
+      expect(poolAccount.pendingAdmin.toBase58()).toBe(PublicKey.default.toBase58())
+      await expect(
+        program.methods
+          .acceptAdmin()
+          .accounts({ pool: poolPda, newAdmin: newAdmin.publicKey })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/NotPendingAdmin/)
+
+      // 没有待确认的提名时无法撤销
+      await expect(
+        program.methods
+          .cancelAdminTransfer()
+          .accounts({ pool: poolPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc(),
+      ).rejects.toThrow(/NoPendingAdmin/)
+    })
+
+    // 测试用例：验证管理员权限需经提名和接受两步转移
+    it('should allow admin to change the admin key', async () => {
+      // 原管理员提名新管理员
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({ pool: poolPda, admin: admin.publicKey })
+        // 原管理员签名交易
+        .signers([admin])
+        // 发送交易并等待确认
+        .rpc()
+
+      // 提名后管理员尚未变更
+      let poolAccount = await program.account.pool.fetch(poolPda)
+      expect(poolAccount.admin.toBase58()).toBe(admin.publicKey.toBase58())
+      expect(poolAccount.pendingAdmin.toBase58()).toBe(newAdmin.publicKey.toBase58())
+
+      // 非被提名地址无法接受
+      await expect(
+        program.methods.acceptAdmin().accounts({ pool: poolPda, newAdmin: user.publicKey }).signers([user]).rpc(),
+      ).rejects.toThrow(/NotPendingAdmin/)
+
+      // 被提名的新管理员签名接受
+      await program.methods
+        .acceptAdmin()
+        .accounts({ pool: poolPda, newAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc()
+
+      // 获取质押池账户数据
+      poolAccount = await program.account.pool.fetch(poolPda)
+      // 验证新管理员公钥已设置，提名已清空
+      expect(poolAccount.admin.toBase58()).toBe(newAdmin.publicKey.toBase58())
+      expect(poolAccount.pendingAdmin.toBase58()).toBe(PublicKey.default.toBase58())
+
+      // 验证旧管理员无法再执行管理员操作
+      await expect(
+        program.methods.pause().accounts({ pool: poolPda, pauser: admin.publicKey }).signers([admin]).rpc(),
+      ).rejects.toThrow(/MissingRole/) // 旧管理员不再持有任何角色
+
+      // 验证新管理员可以执行管理员操作
+      await program.methods
+        .pause()
+        .accounts({ pool: poolPda, pauser: newAdmin.publicKey })
+        // 新管理员签名交易
+        .signers([newAdmin])
+        // 发送交易并等待确认
+        .rpc()
+
+      // 恢复程序状态（取消暂停），方便后续测试
+      await program.methods
+        .unpause()
+        .accounts({ pool: poolPda, admin: newAdmin.publicKey })
+        // 新管理员签名交易
+        .signers([newAdmin])
+        // 发送交易并等待确认
+        .rpc()
+    })
+
+    // 测试用例：验证非管理员无法执行管理员操作
+    it('should fail if a non-admin tries an admin action', async () => {
+      // 期望未授权用户调用 updateRewardRate 方法会失败
+      await expect(
+        program.methods
+          .updateRewardRate(0, new BN(999))
+          .accounts({
+            // 质押池账户
+            pool: poolPda,
+            // 未授权用户公钥
+            rateManager: unauthorizedUser.publicKey,
+          })
+          // 未授权用户签名交易
+          .signers([unauthorizedUser])
+          // 发送交易
+          .rpc(),
+      ).rejects.toThrow(/MissingRole/) // 期望抛出缺少角色错误
+    })
+
+    // 测试用例：验证管理员可以授予和撤销角色
+    it('should allow admin to grant and revoke roles', async () => {
+      // 将暂停者角色授予未授权用户
+      await program.methods
+        .grantRole({ pauser: {} }, unauthorizedUser.publicKey)
+        .accounts({ pool: poolPda, admin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc()
+
+      let poolAccount = await program.account.pool.fetch(poolPda)
+      expect(poolAccount.pauser.toBase58()).toBe(unauthorizedUser.publicKey.toBase58())
+
+      // 暂停者可以暂停程序
+      await program.methods
+        .pause()
+        .accounts({ pool: poolPda, pauser: unauthorizedUser.publicKey })
+        .signers([unauthorizedUser])
+        .rpc()
+
+      // 暂停者无法恢复程序，也无法调整奖励率
+      await expect(
+        program.methods
+          .unpause()
+          .accounts({ pool: poolPda, admin: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc(),
+      ).rejects.toThrow()
+      await expect(
+        program.methods
+          .updateRewardRate(0, new BN(999))
+          .accounts({ pool: poolPda, rateManager: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc(),
+      ).rejects.toThrow(/MissingRole/)
+
+      // 管理员恢复程序并撤销角色
+      await program.methods.unpause().accounts({ pool: poolPda, admin: newAdmin.publicKey }).signers([newAdmin]).rpc()
+      await program.methods
+        .revokeRole({ pauser: {} })
+        .accounts({ pool: poolPda, admin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc()
+
+      poolAccount = await program.account.pool.fetch(poolPda)
+      expect(poolAccount.pauser.toBase58()).toBe(PublicKey.default.toBase58())
+
+      // 撤销后无法再暂停，且不能重复撤销
+      await expect(
+        program.methods
+          .pause()
+          .accounts({ pool: poolPda, pauser: unauthorizedUser.publicKey })
+          .signers([unauthorizedUser])
+          .rpc(),
+      ).rejects.toThrow(/MissingRole/)
+      await expect(
+        program.methods
+          .revokeRole({ pauser: {} })
+          .accounts({ pool: poolPda, admin: newAdmin.publicKey })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/RoleNotAssigned/)
+    })
+
+    // 测试用例：验证管理员可以紧急提取奖励代币
+    it('should allow admin to perform an emergency withdrawal of reward tokens', async () => {
+      // 定义要提取的代币数量
+      const amountToWithdraw = new BN(1000)
+      // 获取奖励金库的初始状态
+      const vaultBefore = await getAccount(connection, rewardVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
+      // 获取目标账户（未授权用户的奖励代币账户）的初始状态
+      const destinationWalletBefore = await getAccount(
+        connection,
+        unauthorizedUserRewardWallet,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+
+      // 调用程序的 emergencyWithdrawRewardTokens 方法，提取奖励代币
+      await program.methods
+        .emergencyWithdrawRewardTokens(0, amountToWithdraw)
+        .accounts({
+          // 质押池账户
+          pool: poolPda,
+          // 当前管理员公钥（newAdmin）
+          guardian: newAdmin.publicKey,
+          // 奖励代币的 Mint
+          rewardMint: rewardMint,
+          // 奖励金库账户
+          rewardVault: rewardVaultPda,
+          // 目标代币账户
+          destinationWallet: unauthorizedUserRewardWallet,
+          // SPL Token 程序
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        // 新管理员签名交易
+        .signers([newAdmin])
+        // 发送交易并等待确认
+        .rpc()
+
+      // 获取奖励金库的最终状态
+      const vaultAfter = await getAccount(connection, rewardVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
+      // 获取目标账户的最终状态
+      const destinationWalletAfter = await getAccount(
+        connection,
+        unauthorizedUserRewardWallet,
+        undefined,
+        TOKEN_2022_PROGRAM_ID,
+      )
+
+      // 验证奖励金库代币减少了提取数量
+      expect(Number(vaultAfter.amount)).toBe(Number(vaultBefore.amount) - amountToWithdraw.toNumber())
+      // 验证目标账户代币增加了提取数量
+      expect(Number(destinationWalletAfter.amount)).toBe(
+        Number(destinationWalletBefore.amount) + amountToWithdraw.toNumber(),
+      )
+    })
+  })
+
+  describe('View Instructions', () => {
+    // 确保用户在池子 1 中持有仓位（此前的测试可能已关闭质押信息账户）
+    beforeAll(async () => {
+      await program.methods
+        .stake(new BN(10), new BN(0))
+        .accounts({
+          user: user.publicKey,
+          pool: poolPda,
+          userStakeInfo: userStakeInfoPda,
+          userStakingWallet: userStakingWallet,
+          stakingVault: stakingVaultPda,
+          stakingMint: stakingMint,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+    })
+
+    // 测试用例：验证待领奖励视图按当前时间结算，且不修改链上账户
+    it('should return pending rewards at the current clock without writing state', async () => {
+      const before = await program.account.userStakeInfo.fetch(userStakeInfoPda)
+      const view = await program.methods
+        .getPendingRewards(user.publicKey)
+        .accounts({ pool: poolPda, userStakeInfo: userStakeInfoPda })
+        .view()
+      const after = await program.account.userStakeInfo.fetch(userStakeInfoPda)
+
+      expect(view.pool.toBase58()).toBe(poolPda.toBase58())
+      expect(view.user.toBase58()).toBe(user.publicKey.toBase58())
+      expect(view.rewardMints[0].toBase58()).toBe(rewardMint.toBase58())
+      // 视图包含已记账的奖励和自上次同步以来新累积的奖励
+      expect(view.rewards[0].gte(before.rewards[0])).toBe(true)
+      // 账户未被修改
+      expect(after.rewards[0].toString()).toBe(before.rewards[0].toString())
+      expect(after.rewardPerTokenPaid[0].toString()).toBe(before.rewardPerTokenPaid[0].toString())
+    })
+
+    // 测试用例：验证用户仓位视图与链上质押信息一致
+    it('should return the user position with lock and unbonding details', async () => {
+      const info = await program.account.userStakeInfo.fetch(userStakeInfoPda)
+      const view = await program.methods
+        .getUserPosition(user.publicKey)
+        .accounts({ pool: poolPda, userStakeInfo: userStakeInfoPda })
+        .view()
+
+      expect(view.stakeAmount.toString()).toBe(info.stakeAmount.toString())
+      expect(view.lockedAmount.add(view.unlockedAmount).toString()).toBe(info.stakeAmount.toString())
+      expect(view.weightedAmount.toString()).toBe(info.weightedAmount.toString())
+      expect(view.withdrawableAmount.lte(view.unbondingAmount)).toBe(true)
+      if (view.lockedAmount.isZero()) {
+        expect(view.nextUnlockTimestamp.toNumber()).toBe(0)
+      } else {
+        expect(view.nextUnlockTimestamp.toNumber()).toBeGreaterThan(view.timestamp.toNumber())
+        expect(view.finalUnlockTimestamp.gte(view.nextUnlockTimestamp)).toBe(true)
+      }
+    })
+
+    // 测试用例：验证池子状态视图返回总量、金库余额和奖励流参数
+    it('should return the pool state with vault balances and reward stream inputs', async () => {
+      const pool = await program.account.pool.fetch(poolPda)
+      const vault = await getAccount(connection, stakingVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
+      // 已配置奖励流的金库按槽位顺序作为 remaining accounts 传入
+      const rewardVaults = pool.rewardStreams
+        .filter((stream) => !stream.mint.equals(PublicKey.default))
+        .map((stream) => ({ pubkey: stream.vault, isWritable: false, isSigner: false }))
+      const view = await program.methods
+        .getPoolState()
+        .accounts({ pool: poolPda, stakingVault: stakingVaultPda })
+        .remainingAccounts(rewardVaults)
+        .view()
+
+      expect(view.totalStaked.toString()).toBe(pool.totalStaked.toString())
+      expect(view.totalWeightedStake.toString()).toBe(pool.totalWeightedStake.toString())
+      expect(view.stakingVaultBalance.toString()).toBe(vault.amount.toString())
+      expect(view.rewardStreams[0].mint.toBase58()).toBe(rewardMint.toBase58())
+      expect(view.rewardStreams[0].rewardRate.toString()).toBe(pool.rewardStreams[0].rewardRate.toString())
+      // 累计奖励指数只会按当前时间向前结算
+      expect(view.rewardStreams[0].rewardPerTokenStored.gte(pool.rewardStreams[0].rewardPerTokenStored)).toBe(true)
+      // 账本余额旁返回奖励金库的实际余额
+      const rewardVault = await getAccount(connection, rewardVaultPda, undefined, TOKEN_2022_PROGRAM_ID)
+      expect(view.rewardStreams[0].vaultAmount.toString()).toBe(rewardVault.amount.toString())
+      expect(view.rewardStreams[0].vaultBalance.toString()).toBe(pool.rewardStreams[0].vaultBalance.toString())
+
+      // 缺少金库或传入其他账户时拒绝
+      await expect(
+        program.methods.getPoolState().accounts({ pool: poolPda, stakingVault: stakingVaultPda }).view(),
+      ).rejects.toThrow(/RewardVaultMismatch/)
+      await expect(
+        program.methods
+          .getPoolState()
+          .accounts({ pool: poolPda, stakingVault: stakingVaultPda })
+          .remainingAccounts(rewardVaults.map(() => ({ pubkey: stakingVaultPda, isWritable: false, isSigner: false })))
+          .view(),
+      ).rejects.toThrow(/RewardVaultMismatch/)
+    })
+
+    // 测试用例：验证查询没有质押信息的用户时失败
+    it('should fail to view a user without a stake info account', async () => {
+      const { userStakeInfoPda: missing } = derivePoolPdas(POOL_ID, unauthorizedUser.publicKey)
+      await expect(
+        program.methods
+          .getPendingRewards(unauthorizedUser.publicKey)
+          .accounts({ pool: poolPda, userStakeInfo: missing })
+          .view(),
+      ).rejects.toThrow(/AccountNotInitialized/)
+    })
+  })
+
+  describe('Account Validation', () => {
+    // 其他池子的金库，用于伪造账户
+    const otherPool = () => derivePoolPdas(new BN(9), user.publicKey)
+
+    // 测试用例：验证取消质押时质押代币 Mint 必须与池子一致
+    it('should reject unstake with a spoofed staking mint', async () => {
+      await expect(
+        program.methods
+          .unstake(new BN(1))
+          .accounts({
+            user: user.publicKey,
+            pool: poolPda,
+            userStakeInfo: userStakeInfoPda,
+            userStakingWallet: userStakingWallet,
+            stakingVault: stakingVaultPda,
+            stakingMint: rewardMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintHasOne/)
+    })
+
+    // 测试用例：验证领取奖励时奖励金库必须是本池子的金库
+    it('should reject claim_rewards with another pool reward vault', async () => {
+      await expect(
+        program.methods
+          .claimRewards(0)
+          .accounts({
+            user: user.publicKey,
+            pool: poolPda,
+            userStakeInfo: userStakeInfoPda,
+            userRewardWallet: userRewardWallet,
+            rewardVault: otherPool().rewardVaultPda,
+            rewardMint: rewardMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintSeeds/)
+    })
+
+    // 测试用例：验证注资时奖励金库必须是本池子的金库
+    it('should reject fund_rewards into another pool reward vault', async () => {
+      await expect(
+        program.methods
+          .fundRewards(0, new BN(1))
+          .accounts({
+            pool: poolPda,
+            funder: newAdmin.publicKey,
+            funderWallet: adminRewardWallet,
+            rewardVault: otherPool().rewardVaultPda,
+            rewardMint: rewardMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintSeeds/)
+    })
+
+    // 测试用例：验证紧急提取质押代币时金库和 Mint 必须与池子一致
+    it('should reject emergency_withdraw_staked_tokens with a spoofed vault or mint', async () => {
+      const withdrawAccounts = {
+        pool: poolPda,
+        guardian: newAdmin.publicKey,
+        stakingVault: stakingVaultPda,
+        stakingMint: stakingMint,
+        destinationWallet: userStakingWallet,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      await expect(
+        program.methods
+          .emergencyWithdrawStakedTokens(new BN(1))
+          .accounts({ ...withdrawAccounts, stakingVault: otherPool().stakingVaultPda })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintHasOne/)
+      await expect(
+        program.methods
+          .emergencyWithdrawStakedTokens(new BN(1))
+          .accounts({ ...withdrawAccounts, stakingMint: rewardMint, destinationWallet: userRewardWallet })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintHasOne/)
+      await expect(
+        program.methods
+          .emergencyWithdrawStakedTokens(new BN(1))
+          .accounts({ ...withdrawAccounts, destinationWallet: userRewardWallet })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintTokenMint/)
+    })
+
+    // 测试用例：验证紧急提取奖励代币时奖励金库必须是本池子的金库
+    it('should reject emergency_withdraw_reward_tokens with a spoofed vault or destination', async () => {
+      const withdrawAccounts = {
+        pool: poolPda,
+        guardian: newAdmin.publicKey,
+        rewardMint: rewardMint,
+        rewardVault: rewardVaultPda,
+        destinationWallet: unauthorizedUserRewardWallet,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+      }
+      await expect(
+        program.methods
+          .emergencyWithdrawRewardTokens(0, new BN(1))
+          .accounts({ ...withdrawAccounts, rewardVault: otherPool().rewardVaultPda })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintSeeds/)
+      await expect(
+        program.methods
+          .emergencyWithdrawRewardTokens(0, new BN(1))
+          .accounts({ ...withdrawAccounts, destinationWallet: userStakingWallet })
+          .signers([newAdmin])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintTokenMint/)
+    })
+
+    // 测试用例：验证复利时奖励金库必须是本池子质押代币奖励流的金库
+    it('should reject compound with a reward vault that is not the staking-token stream vault', async () => {
+      await expect(
+        program.methods
+          .compound()
+          .accounts({
+            caller: user.publicKey,
+            pool: poolPda,
+            userStakeInfo: userStakeInfoPda,
+            stakingVault: stakingVaultPda,
+            rewardVault: rewardVaultPda,
+            stakingMint: stakingMint,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          })
+          .signers([user])
+          .rpc(),
+      ).rejects.toThrow(/ConstraintSeeds/)
     })
   })
 })